@@ -0,0 +1,38 @@
+//! Times the work every report does, over made-up histories of 10000, 100000,
+//! and 1000000 games. Run with `cargo bench`; criterion compares each run with
+//! the last and says which steps got faster or slower.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use protour::bench;
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const PLAYERS: usize = 12;
+
+fn pipeline(c: &mut Criterion) {
+    for size in SIZES {
+        let path = std::env::temp_dir().join(format!("protour-bench-{}.csv", size));
+        bench::save(&path, &bench::generate(size, PLAYERS, 0)).expect("writing the data file");
+        let games = bench::load(&path).expect("loading the data file");
+
+        let mut group = c.benchmark_group(format!("{} games", size));
+        group.throughput(Throughput::Elements(size as u64));
+        // a step over a million games takes seconds, so ten samples will do
+        group.sample_size(10);
+        group.bench_function("load", |b| {
+            b.iter(|| bench::load(black_box(&path)).expect("loading the data file"))
+        });
+        group.bench_function("matchups", |b| {
+            b.iter(|| bench::matchups(black_box(&games)))
+        });
+        group.bench_function("elo", |b| b.iter(|| bench::elo(black_box(&games))));
+        group.bench_function("trueskill", |b| {
+            b.iter(|| bench::trueskill(black_box(&games)))
+        });
+        group.finish();
+        std::fs::remove_file(&path).ok();
+    }
+}
+
+criterion_group!(benches, pipeline);
+criterion_main!(benches);
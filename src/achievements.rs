@@ -0,0 +1,200 @@
+use crate::{Archetype, ColorIdentity, Deck, GameLog};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// An achievement tracks one player's games in order and says when it's earned.
+trait Rule {
+    /// Returns a description for each achievement this game earns.
+    fn observe(&mut self, game: &GameLog, deck: Deck, won: bool) -> Option<String>;
+}
+
+#[derive(Default)]
+struct NewArchetypeWin {
+    won_with: BTreeSet<Archetype>,
+}
+
+impl Rule for NewArchetypeWin {
+    fn observe(&mut self, _: &GameLog, deck: Deck, won: bool) -> Option<String> {
+        let archetype = deck.1?;
+        (won && self.won_with.insert(archetype)).then(|| format!("First win with {}", archetype))
+    }
+}
+
+pub const STREAK: u32 = 5;
+
+#[derive(Default)]
+struct WinStreak {
+    current: u32,
+}
+
+impl Rule for WinStreak {
+    fn observe(&mut self, _: &GameLog, _: Deck, won: bool) -> Option<String> {
+        self.current = if won { self.current + 1 } else { 0 };
+        (self.current == STREAK).then(|| format!("{}-game win streak", STREAK))
+    }
+}
+
+const MONO_COLORS: [ColorIdentity; 5] = [
+    ColorIdentity::White,
+    ColorIdentity::Blue,
+    ColorIdentity::Black,
+    ColorIdentity::Red,
+    ColorIdentity::Green,
+];
+
+#[derive(Default)]
+struct AllMonoColors {
+    won_with: BTreeSet<ColorIdentity>,
+}
+
+impl Rule for AllMonoColors {
+    fn observe(&mut self, _: &GameLog, deck: Deck, won: bool) -> Option<String> {
+        if !won || !MONO_COLORS.contains(&deck.0) || !self.won_with.insert(deck.0) {
+            return None;
+        }
+        (self.won_with.len() == MONO_COLORS.len())
+            .then(|| "Won with all five mono colors".to_string())
+    }
+}
+
+/// Beating every other player on the roster at least once, head to head or
+/// in a pod.
+struct BeatEveryone<'a> {
+    /// Players not beaten yet
+    left: BTreeSet<&'a str>,
+    /// The losing seats of each pod
+    losers: BTreeMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> BeatEveryone<'a> {
+    fn new(games: &'a [GameLog], player: &str, roster: &'a [String]) -> Self {
+        let mut losers: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        games
+            .iter()
+            .filter(|game| game.won < game.lost)
+            .for_each(|game| {
+                if let Some(pod) = &game.pod {
+                    losers.entry(pod).or_default().push(&game.player);
+                }
+            });
+        Self {
+            left: roster
+                .iter()
+                .map(String::as_str)
+                .filter(|other| *other != player)
+                .collect(),
+            losers,
+        }
+    }
+}
+
+impl Rule for BeatEveryone<'_> {
+    fn observe(&mut self, game: &GameLog, _: Deck, won: bool) -> Option<String> {
+        if !won || self.left.is_empty() {
+            return None;
+        }
+        let beaten = match &game.pod {
+            Some(pod) => self.losers.get(pod.as_str()).cloned().unwrap_or_default(),
+            None => game.opponent.as_deref().into_iter().collect(),
+        };
+        beaten.iter().for_each(|player| {
+            self.left.remove(*player);
+        });
+        self.left
+            .is_empty()
+            .then(|| "Beat every other player on the roster".to_string())
+    }
+}
+
+fn rules<'a>(games: &'a [GameLog], player: &str, roster: &'a [String]) -> Vec<Box<dyn Rule + 'a>> {
+    vec![
+        Box::<NewArchetypeWin>::default(),
+        Box::<WinStreak>::default(),
+        Box::<AllMonoColors>::default(),
+        Box::new(BeatEveryone::new(games, player, roster)),
+    ]
+}
+
+#[derive(Debug, Serialize)]
+pub struct Earned {
+    pub player: String,
+    pub achievement: String,
+    /// Game that earned it
+    pub game: u32,
+    pub date: Option<NaiveDate>,
+}
+
+/// Replays the log in order and collects every achievement the player earned,
+/// among the players of `roster`.
+pub fn earned(games: &[GameLog], player: &str, roster: &[String]) -> Vec<Earned> {
+    let mut rules = rules(games, player, roster);
+    let mut earned = Vec::new();
+    games
+        .iter()
+        .filter(|game| game.player == player && !game.is_draw())
+        .filter_map(|game| Some((game, Deck::from_str(&game.deck).ok()?)))
+        .for_each(|(game, deck)| {
+            let won = game.won > game.lost;
+            rules.iter_mut().for_each(|rule| {
+                if let Some(achievement) = rule.observe(game, deck, won) {
+                    earned.push(Earned {
+                        player: player.to_string(),
+                        achievement,
+                        game: game.id,
+                        date: game.date,
+                    });
+                }
+            });
+        });
+    earned
+}
+
+pub fn print(games: &[GameLog], players: &[String], roster: &[String]) {
+    players.iter().for_each(|player| {
+        println!("{}:", player);
+        let earned = earned(games, player, roster);
+        if earned.is_empty() {
+            println!("  nothing yet");
+        }
+        earned.iter().for_each(|earned| match earned.date {
+            Some(date) => println!("  {} (#{}, {})", earned.achievement, earned.game, date),
+            None => println!("  {} (#{})", earned.achievement, earned.game),
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    #[test]
+    fn beating_everyone_takes_an_opponent_or_a_pod_seat_each() {
+        let roster: Vec<String> = ["Grant", "Noah", "Isaac"].map(String::from).into();
+        let pod = |id, player: &str, won, lost| GameLog {
+            pod: Some("FNM 1".to_string()),
+            player: player.into(),
+            won,
+            lost,
+            opponent: None,
+            ..test_game(id)
+        };
+        let games = vec![
+            test_game(1),
+            pod(2, "Grant", 1, 0),
+            pod(3, "Isaac", 0, 1),
+            pod(4, "Noah", 0, 1),
+        ];
+        let beat_everyone = |player| {
+            earned(&games, player, &roster)
+                .into_iter()
+                .filter(|earned| earned.achievement.starts_with("Beat every"))
+                .map(|earned| earned.game)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(beat_everyone("Grant"), [2]);
+        assert!(beat_everyone("Noah").is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//! MTG Arena's `Player.log`, followed live through a session. With detailed
+//! logs turned on (Options, Account, Detailed Logs), Arena writes a JSON line
+//! as each match ends naming its players and who won each game, which is all
+//! this relies on. Like Magic Online's logs, they don't say which archetypes
+//! were played, so those come from the command line or a prompt.
+
+use crate::config::{Aliases, Config};
+use crate::edit::GameFields;
+use crate::{add_game, store};
+use clap::Subcommand;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+// how often the log is checked for new lines
+const POLL: Duration = Duration::from_secs(1);
+const MATCH_COMPLETED: &str = "MatchGameRoomStateType_MatchCompleted";
+
+#[derive(Debug, Subcommand)]
+pub enum ArenaCommand {
+    /// Follow Player.log while you play, recording each match as it ends;
+    /// stop with Ctrl-C
+    Watch {
+        /// Arena's Player.log, if it isn't where Arena keeps it by default
+        #[arg(long)]
+        log: Option<PathBuf>,
+        /// Record matches without asking first
+        #[arg(long, requires_all = ["deck", "opp_deck"])]
+        yes: bool,
+        /// Deck you're playing; asked for each match if left out
+        #[arg(long)]
+        deck: Option<String>,
+        /// Deck your opponents are playing; asked for each match if left out
+        #[arg(long)]
+        opp_deck: Option<String>,
+        /// Your Arena username, when it isn't aliased to someone on the roster
+        #[arg(long = "as")]
+        username: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogMessage {
+    match_game_room_state_changed_event: Option<StateChanged>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateChanged {
+    game_room_info: GameRoomInfo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GameRoomInfo {
+    state_type: String,
+    game_room_config: GameRoomConfig,
+    final_match_result: Option<FinalMatchResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GameRoomConfig {
+    match_id: String,
+    reserved_players: Vec<ReservedPlayer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReservedPlayer {
+    player_name: String,
+    team_id: u32,
+    #[serde(default)]
+    event_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FinalMatchResult {
+    result_list: Vec<MatchResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchResult {
+    scope: String,
+    #[serde(default)]
+    winning_team_id: Option<u32>,
+}
+
+/// A finished match as the log tells it.
+struct Completed {
+    id: String,
+    /// Each player's Arena name and team
+    players: Vec<(String, u32)>,
+    /// The winning team of each game
+    winners: Vec<u32>,
+    event: String,
+}
+
+/// The match a log line reports finished, if it's one that does.
+fn completed(line: &str) -> Option<Completed> {
+    if !line.contains(MATCH_COMPLETED) {
+        return None;
+    }
+    let message: LogMessage = serde_json::from_str(&line[line.find('{')?..]).ok()?;
+    let info = message.match_game_room_state_changed_event?.game_room_info;
+    if info.state_type != MATCH_COMPLETED {
+        return None;
+    }
+    let config = info.game_room_config;
+    Some(Completed {
+        id: config.match_id,
+        event: config
+            .reserved_players
+            .first()
+            .map(|player| player.event_id.clone())
+            .unwrap_or_default(),
+        players: config
+            .reserved_players
+            .into_iter()
+            .map(|player| (player.player_name, player.team_id))
+            .collect(),
+        winners: info
+            .final_match_result?
+            .result_list
+            .into_iter()
+            .filter(|result| result.scope == "MatchScope_Game")
+            .filter_map(|result| result.winning_team_id)
+            .collect(),
+    })
+}
+
+/// Where Arena writes its log on Windows and macOS.
+fn default_log() -> Option<PathBuf> {
+    if let Some(profile) = std::env::var_os("USERPROFILE") {
+        return Some(
+            PathBuf::from(profile).join(r"AppData\LocalLow\Wizards Of The Coast\MTGA\Player.log"),
+        );
+    }
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join("Library/Logs/Wizards Of The Coast/MTGA/Player.log"))
+}
+
+/// Who an Arena name is on the roster: its alias, or the name without the
+/// `#12345` Arena adds to it.
+fn roster_name(name: &str, aliases: &Aliases, roster: &[String]) -> Option<String> {
+    let bare = name.split_once('#').map_or(name, |(bare, _)| bare);
+    [name, bare]
+        .into_iter()
+        .map(|name| aliases.player(name))
+        .find(|player| roster.contains(player))
+}
+
+/// The fields of the game a finished match makes, from the point of view of
+/// `me` or of whichever player is on the roster.
+fn fields(
+    completed: &Completed,
+    me: Option<&str>,
+    aliases: &Aliases,
+    roster: &[String],
+) -> Result<GameFields, Box<dyn Error>> {
+    let names = || completed.players.iter().map(|(name, _)| name.as_str());
+    let (name, team) = completed
+        .players
+        .iter()
+        .find(|(name, _)| match me {
+            Some(me) => name == me || name.split_once('#').is_some_and(|(bare, _)| bare == me),
+            None => roster_name(name, aliases, roster).is_some(),
+        })
+        .ok_or_else(|| match me {
+            Some(me) => format!("{} didn't play in match {}", me, completed.id),
+            None => format!(
+                "neither {} is on the roster, pass --as or add a player alias",
+                names().collect::<Vec<_>>().join(" nor ")
+            ),
+        })?;
+    let opponent = names().find(|other| other != name).unwrap_or_default();
+    let won = completed
+        .winners
+        .iter()
+        .filter(|winner| *winner == team)
+        .count() as u32;
+    let traditional = completed.event.contains("Traditional");
+    Ok(GameFields {
+        date: None,
+        player: Some(roster_name(name, aliases, roster).unwrap_or_else(|| name.clone())),
+        deck: None,
+        won: Some(won),
+        lost: Some(completed.winners.len() as u32 - won),
+        opp_deck: None,
+        opponent: roster_name(opponent, aliases, roster),
+        notes: Some(format!("Arena vs {}", opponent)),
+        event: None,
+        best_of: Some(if traditional || completed.winners.len() > 1 {
+            3
+        } else {
+            1
+        }),
+        pod: None,
+        mulligans: None,
+        opp_mulligans: None,
+        won_roll: None,
+        on_play: None,
+        team: None,
+    })
+}
+
+fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    print!("{} [Y/n] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(!answer.trim().to_lowercase().starts_with('n'))
+}
+
+/// Records one finished match, asking for what's missing and, unless `yes`,
+/// whether to record it at all.
+fn record(
+    data: &Path,
+    config: &Config,
+    mut fields: GameFields,
+    yes: bool,
+) -> Result<(), Box<dyn Error>> {
+    println!(
+        "Match finished: {} {} - {}, {}",
+        fields.player.as_deref().unwrap_or_default(),
+        fields.won.unwrap_or_default(),
+        fields.lost.unwrap_or_default(),
+        fields.notes.as_deref().unwrap_or_default()
+    );
+    if !yes {
+        fields.prompt_missing()?;
+        if !confirm("Record it?")? {
+            println!("Skipped");
+            return Ok(());
+        }
+    }
+    let game = fields.into_game(&config.aliases)?;
+    let games = store::load_games(data, &config.aliases)?;
+    let last = games.iter().map(|game| game.id).max().unwrap_or(0);
+    let added = add_game(data, games, game, &config.roster, "arena watch", None)?;
+    match added[0].id {
+        id if id <= last => println!("Merged with game #{}, already reported", id),
+        id => println!("Added game #{}", id),
+    }
+    Ok(())
+}
+
+/// Follows the log from its end, recording every match that finishes. Arena
+/// starts a fresh log each time it's opened, so one that shrinks is read again
+/// from the top.
+fn watch(
+    data: &Path,
+    config: &Config,
+    log: &Path,
+    yes: bool,
+    deck: Option<String>,
+    opp_deck: Option<String>,
+    me: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(log).map_err(|err| format!("{}: {}", log.display(), err))?;
+    let mut offset = file.seek(SeekFrom::End(0))?;
+    let mut pending = Vec::new();
+    let mut recorded = BTreeSet::new();
+    println!("Watching {} for finished matches", log.display());
+    loop {
+        let len = std::fs::metadata(log)?.len();
+        if len < offset {
+            file = File::open(log)?;
+            offset = 0;
+            pending.clear();
+        }
+        if len == offset {
+            thread::sleep(POLL);
+            continue;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        offset += Read::by_ref(&mut file)
+            .take(len - offset)
+            .read_to_end(&mut pending)? as u64;
+        // a line still being written is kept for the next read
+        let Some(end) = pending.iter().rposition(|byte| *byte == b'\n') else {
+            continue;
+        };
+        let lines: Vec<u8> = pending.drain(..=end).collect();
+        for line in String::from_utf8_lossy(&lines).lines() {
+            let Some(completed) = completed(line) else {
+                continue;
+            };
+            if !recorded.insert(completed.id.clone()) {
+                continue;
+            }
+            let result =
+                fields(&completed, me, &config.aliases, &config.roster).and_then(|mut fields| {
+                    fields.deck = deck.clone();
+                    fields.opp_deck = opp_deck.clone();
+                    record(data, config, fields, yes)
+                });
+            if let Err(err) = result {
+                warn!("couldn't record match {}: {}", completed.id, err);
+            }
+        }
+    }
+}
+
+pub fn run(command: ArenaCommand, data: &Path, config: &Config) -> Result<(), Box<dyn Error>> {
+    match command {
+        ArenaCommand::Watch {
+            log,
+            yes,
+            deck,
+            opp_deck,
+            username,
+        } => {
+            let log = log
+                .or_else(default_log)
+                .ok_or("couldn't tell where Arena keeps Player.log, pass --log")?;
+            watch(data, config, &log, yes, deck, opp_deck, username.as_deref())
+        }
+    }
+}
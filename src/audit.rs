@@ -0,0 +1,207 @@
+use crate::{crypt, GameLog};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Action {
+    Add,
+    Edit,
+    Delete,
+    /// Reverts a mutation of the most recent command that hasn't been undone yet
+    Undo,
+}
+
+/// Who's making changes, when it isn't the account protour runs under, e.g.
+/// on a shared machine.
+pub const USER_VAR: &str = "PROTOUR_USER";
+
+/// One mutation of the data file, with the game as it was before and after.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Local>,
+    /// Who made the change; unknown for entries logged before this was kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+    pub action: Action,
+    pub id: u32,
+    pub before: Option<GameLog>,
+    pub after: Option<GameLog>,
+    /// Written by the same command as the entry before it, so undone with it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub with_previous: bool,
+}
+
+/// The person running protour: `PROTOUR_USER`, or else the account name.
+pub fn user() -> Option<String> {
+    [USER_VAR, "USER", "USERNAME"]
+        .into_iter()
+        .find_map(|var| std::env::var(var).ok())
+        .filter(|user| !user.is_empty())
+}
+
+impl Entry {
+    /// A change made now by whoever's running protour.
+    pub fn new(action: Action, id: u32, before: Option<GameLog>, after: Option<GameLog>) -> Self {
+        Self {
+            at: Local::now(),
+            by: user(),
+            action,
+            id,
+            before,
+            after,
+            with_previous: false,
+        }
+    }
+
+    /// The same change, made on someone else's behalf, e.g. through the bot.
+    pub fn by(self, by: &str) -> Self {
+        Self {
+            by: Some(by.to_string()),
+            ..self
+        }
+    }
+
+    /// What the change did, e.g. "won 2 -> 1" for an edit.
+    pub fn changes(&self) -> String {
+        let summary = |game: &GameLog| {
+            format!(
+                "{} ({}) {} - {} {}",
+                game.player, game.deck, game.won, game.lost, game.opp_deck
+            )
+        };
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => {
+                let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+                    (serde_json::to_value(before), serde_json::to_value(after))
+                else {
+                    return String::new();
+                };
+                let show = |value: &Value| match value {
+                    Value::String(text) => text.clone(),
+                    Value::Null => "none".to_string(),
+                    value => value.to_string(),
+                };
+                let changed: Vec<String> = before
+                    .iter()
+                    .filter(|(field, value)| after.get(*field) != Some(value))
+                    .map(|(field, value)| {
+                        let after = after.get(field).unwrap_or(&Value::Null);
+                        format!("{} {} -> {}", field, show(value), show(after))
+                    })
+                    .collect();
+                changed.join(", ")
+            }
+            (None, Some(game)) | (Some(game), None) => summary(game),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// The latest `count` changes, newest first, to game `id` or by `by` if
+/// given.
+pub fn print(entries: &[Entry], id: Option<u32>, by: Option<&str>, count: usize) {
+    let shown: Vec<&Entry> = entries
+        .iter()
+        .rev()
+        .filter(|entry| id.is_none_or(|id| entry.id == id))
+        .filter(|entry| by.is_none_or(|by| entry.by.as_deref() == Some(by)))
+        .take(count)
+        .collect();
+    if shown.is_empty() {
+        println!("No changes logged.");
+        return;
+    }
+    shown.iter().for_each(|entry| {
+        println!(
+            "{}  {:<6} #{:<4} by {}: {}",
+            entry.at.format("%Y-%m-%d %H:%M"),
+            entry.action.to_string(),
+            entry.id,
+            entry.by.as_deref().unwrap_or("unknown"),
+            entry.changes()
+        )
+    });
+}
+
+/// The audit log lives next to the data file: `data.csv` logs to `data.audit.jsonl`,
+/// and an encrypted `data.csv.age` to an encrypted `data.audit.jsonl.age`.
+pub fn path_for(data: &Path) -> PathBuf {
+    let path = crypt::plain_path(data).with_extension("audit.jsonl");
+    if crypt::is_encrypted(data) {
+        crypt::encrypted_path(&path)
+    } else {
+        path
+    }
+}
+
+/// Logs the changes one command made, to be undone together.
+pub fn append(data: &Path, entries: &[Entry]) -> Result<(), Box<dyn Error>> {
+    let grouped: Vec<Entry> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| Entry {
+            with_previous: i > 0,
+            ..entry.clone()
+        })
+        .collect();
+    extend(data, &grouped)
+}
+
+/// Appends entries from another log as they are.
+pub fn extend(data: &Path, entries: &[Entry]) -> Result<(), Box<dyn Error>> {
+    let path = path_for(data);
+    let mut lines = Vec::new();
+    for entry in entries {
+        writeln!(lines, "{}", serde_json::to_string(entry)?)?;
+    }
+    // an encrypted log can't be appended to in place, so it's rewritten whole
+    if crypt::is_encrypted(&path) {
+        let mut contents = if path.exists() {
+            crypt::read(&path)?
+        } else {
+            Vec::new()
+        };
+        contents.extend(lines);
+        return crypt::write(&path, &contents);
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&lines)?;
+    Ok(())
+}
+
+pub fn read(data: &Path) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let path = path_for(data);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for line in crypt::read(&path)?.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+/// The commands whose changes can still be undone, each as the entries it
+/// wrote, most recent last: every undo in the log cancels the latest command
+/// before it.
+pub fn undo_stack(entries: &[Entry]) -> Vec<&[Entry]> {
+    let mut stack = Vec::new();
+    for command in entries.chunk_by(|_, next| next.with_previous) {
+        if command[0].action == Action::Undo {
+            stack.pop();
+        } else {
+            stack.push(command);
+        }
+    }
+    stack
+}
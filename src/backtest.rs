@@ -0,0 +1,280 @@
+//! Scoring of win probabilities predicted before each game was played.
+
+use crate::config::RatingsConfig;
+use crate::{ratings, sample, Deck, GameLog, Matchup, MATCHUP_PRIOR_GAMES};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A predicted chance the player wins, and whether they did.
+#[derive(Debug, Clone, Copy)]
+pub struct Prediction {
+    pub probability: f64,
+    pub won: bool,
+}
+
+// keeps a confidently wrong prediction from making the log loss infinite
+const CLAMP: f64 = 1e-6;
+
+impl Prediction {
+    fn outcome(&self) -> f64 {
+        if self.won {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn log_loss(predictions: &[Prediction]) -> f64 {
+    let total: f64 = predictions
+        .iter()
+        .map(|prediction| {
+            let p = prediction.probability.clamp(CLAMP, 1.0 - CLAMP);
+            if prediction.won {
+                -p.ln()
+            } else {
+                -(1.0 - p).ln()
+            }
+        })
+        .sum();
+    total / predictions.len().max(1) as f64
+}
+
+pub fn brier(predictions: &[Prediction]) -> f64 {
+    let total: f64 = predictions
+        .iter()
+        .map(|prediction| (prediction.probability - prediction.outcome()).powi(2))
+        .sum();
+    total / predictions.len().max(1) as f64
+}
+
+/// One line of scores per model, lower is better for both.
+pub fn print_scores(models: &[(&str, Vec<Prediction>)]) {
+    println!(
+        "{:<14} {:>6} {:>9} {:>7}",
+        "Model", "Games", "Log loss", "Brier"
+    );
+    models.iter().for_each(|(name, predictions)| {
+        println!(
+            "{:<14} {:>6} {:>9.4} {:>7.4}",
+            name,
+            predictions.len(),
+            log_loss(predictions),
+            brier(predictions)
+        )
+    });
+}
+
+// width of each calibration bucket, as a probability
+const BUCKET: f64 = 0.1;
+
+/// Predictions bucketed by probability, each bucket's average prediction
+/// against how often those players actually won.
+pub fn print_calibration(name: &str, predictions: &[Prediction]) {
+    let buckets = (1.0 / BUCKET).round() as usize;
+    let mut totals = vec![(0u32, 0.0, 0u32); buckets];
+    predictions.iter().for_each(|prediction| {
+        let bucket = ((prediction.probability / BUCKET) as usize).min(buckets - 1);
+        let total = &mut totals[bucket];
+        total.0 += 1;
+        total.1 += prediction.probability;
+        total.2 += prediction.won as u32;
+    });
+    println!("{} calibration:", name);
+    println!(
+        "  {:<11} {:>6} {:>10} {:>8}",
+        "Predicted", "Games", "Average", "Actual"
+    );
+    totals
+        .iter()
+        .enumerate()
+        .filter(|(_, (count, _, _))| *count > 0)
+        .for_each(|(i, (count, sum, wins))| {
+            println!(
+                "  {:>3.0}-{:>3.0}%   {:>6} {:>9.1}% {:>7.1}%{}",
+                i as f64 * BUCKET * 100.0,
+                (i + 1) as f64 * BUCKET * 100.0,
+                count,
+                sum / *count as f64 * 100.0,
+                *wins as f64 / *count as f64 * 100.0,
+                sample::marker(*count)
+            )
+        });
+}
+
+/// Predicts every decided, non-mirror match from the matchup matrix built out
+/// of the games before it, shrunk toward 50% the same way deck EV is.
+pub fn matchup_predictions(games: &[GameLog]) -> Vec<Prediction> {
+    let mut matchups: BTreeMap<(Deck, Deck), Matchup> = BTreeMap::new();
+    let mut predictions = Vec::new();
+    for game in ratings::chronological(games) {
+        let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+        if let (Ok(deck), Ok(opponent)) = decks {
+            if deck != opponent && !game.is_draw() && game.pod.is_none() && !game.is_complement() {
+                let (wins, losses) = matchups
+                    .get(&(deck, opponent))
+                    .map_or((0, 0), |matchup| (matchup.win, matchup.loss));
+                predictions.push(Prediction {
+                    probability: (wins as f64 + 0.5 * MATCHUP_PRIOR_GAMES)
+                        / ((wins + losses) as f64 + MATCHUP_PRIOR_GAMES),
+                    won: game.won > game.lost,
+                });
+            }
+        }
+        game.matchups().into_iter().for_each(|matchup| {
+            let entry = matchups
+                .entry(matchup.key())
+                .or_insert(Matchup::new(matchup.deck, matchup.opponent));
+            entry.win += matchup.win;
+            entry.loss += matchup.loss;
+        });
+    }
+    predictions
+}
+
+pub fn print_matchups(games: &[GameLog]) {
+    let predictions = matchup_predictions(games);
+    let coin_flip: Vec<Prediction> = predictions
+        .iter()
+        .map(|prediction| Prediction {
+            probability: 0.5,
+            ..*prediction
+        })
+        .collect();
+    print_scores(&[
+        ("Matchup matrix", predictions.clone()),
+        ("Coin flip", coin_flip),
+    ]);
+    println!();
+    print_calibration("Matchup matrix", &predictions);
+}
+
+/// What was known about a game before it was played, and how it went.
+#[derive(Serialize)]
+struct Features<'a> {
+    id: u32,
+    date: Option<NaiveDate>,
+    player: &'a str,
+    opponent: Option<&'a str>,
+    deck: &'a str,
+    opp_deck: &'a str,
+    best_of: u32,
+    on_play: Option<bool>,
+    won_roll: Option<bool>,
+    mulligans: Option<u32>,
+    opp_mulligans: Option<u32>,
+    player_elo: Option<f64>,
+    opponent_elo: Option<f64>,
+    elo_probability: Option<f64>,
+    trueskill_probability: Option<f64>,
+    matchup_wins: u32,
+    matchup_losses: u32,
+    matchup_probability: f64,
+    won: u32,
+    lost: u32,
+    /// 1 for a win, 0 for a loss, and 0.5 for a draw
+    result: f64,
+}
+
+/// Writes the features of every head-to-head match as CSV, oldest first,
+/// for training prediction models outside protour; a match recorded for both
+/// players is written once, from the side its decks are counted from. Ratings and the matchup
+/// record are as they stood before the game. The opponent's Elo is theirs
+/// after their last game on an earlier day, so a game never sees the other
+/// side of itself.
+pub fn write_predictions(
+    path: &Path,
+    games: &[GameLog],
+    config: &RatingsConfig,
+) -> Result<(), Box<dyn Error>> {
+    let elo = ratings::elo_before(games, config);
+    let trueskill = ratings::trueskill_before(games, config);
+    let mut history: BTreeMap<&str, Vec<(Option<NaiveDate>, f64)>> = BTreeMap::new();
+    ratings::elo_history(games, config)
+        .into_iter()
+        .for_each(|(game, rating)| {
+            history
+                .entry(game.player.as_str())
+                .or_default()
+                .push((game.date, rating))
+        });
+    let rating_before = |player: &str, date: Option<NaiveDate>| {
+        let ratings = history.get(player)?;
+        let earlier = ratings.partition_point(|(played, _)| *played < date);
+        earlier.checked_sub(1).map(|last| ratings[last].1)
+    };
+
+    let mut matchups: BTreeMap<(Deck, Deck), Matchup> = BTreeMap::new();
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut written = 0;
+    for game in ratings::chronological(games) {
+        if game.pod.is_some() || game.is_complement() {
+            continue;
+        }
+        let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+        let (wins, losses) = match decks {
+            (Ok(deck), Ok(opponent)) => matchups
+                .get(&(deck, opponent))
+                .map_or((0, 0), |matchup| (matchup.win, matchup.loss)),
+            _ => (0, 0),
+        };
+        let mirror = matches!(decks, (Ok(deck), Ok(opponent)) if deck == opponent);
+        let matchup_probability = if mirror {
+            0.5
+        } else {
+            (wins as f64 + 0.5 * MATCHUP_PRIOR_GAMES)
+                / ((wins + losses) as f64 + MATCHUP_PRIOR_GAMES)
+        };
+        writer.serialize(Features {
+            id: game.id,
+            date: game.date,
+            player: &game.player,
+            opponent: game.opponent.as_deref(),
+            deck: game.deck.trim(),
+            opp_deck: game.opp_deck.trim(),
+            best_of: game.best_of(),
+            on_play: game.on_play,
+            won_roll: game.won_roll,
+            mulligans: game.mulligans,
+            opp_mulligans: game.opp_mulligans,
+            player_elo: elo.get(&game.id).map(|(rating, _)| *rating),
+            opponent_elo: game
+                .opponent
+                .as_deref()
+                .and_then(|opponent| rating_before(opponent, game.date)),
+            elo_probability: elo.get(&game.id).map(|(_, probability)| *probability),
+            trueskill_probability: trueskill.get(&game.id).copied(),
+            matchup_wins: wins,
+            matchup_losses: losses,
+            matchup_probability,
+            won: game.won,
+            lost: game.lost,
+            result: if game.is_draw() {
+                0.5
+            } else if game.won > game.lost {
+                1.0
+            } else {
+                0.0
+            },
+        })?;
+        written += 1;
+        game.matchups().into_iter().for_each(|matchup| {
+            let entry = matchups
+                .entry(matchup.key())
+                .or_insert(Matchup::new(matchup.deck, matchup.opponent));
+            entry.win += matchup.win;
+            entry.loss += matchup.loss;
+        });
+    }
+    writer.flush()?;
+    println!(
+        "Wrote the features of {} games to {}",
+        written,
+        path.display()
+    );
+    Ok(())
+}
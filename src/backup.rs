@@ -0,0 +1,76 @@
+use chrono::Local;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const BACKUP_DIR: &str = ".protour-backups";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+fn backup_dir(data: &Path) -> PathBuf {
+    data.with_file_name(BACKUP_DIR)
+}
+
+fn stem(data: &Path) -> String {
+    data.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Copies the data file to `.protour-backups/<name>-<timestamp>.csv` next to it.
+pub fn create(data: &Path) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    if !data.exists() {
+        return Ok(None);
+    }
+    let dir = backup_dir(data);
+    fs::create_dir_all(&dir)?;
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT);
+    let backup = dir.join(format!("{}-{}.csv", stem(data), timestamp));
+    fs::copy(data, &backup)?;
+    info!(backup = %backup.display(), "backed up data file");
+    Ok(Some(backup))
+}
+
+/// Backups of this data file as `(timestamp, path)`, oldest first.
+pub fn list(data: &Path) -> Result<Vec<(String, PathBuf)>, Box<dyn Error>> {
+    let dir = backup_dir(data);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let prefix = format!("{}-", stem(data));
+    let mut backups: Vec<(String, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let timestamp = name.strip_prefix(&prefix)?.strip_suffix(".csv")?;
+            Some((timestamp.to_string(), path))
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Replaces the data file with the backup whose timestamp starts with
+/// `timestamp`, backing up the current file first so the restore can be undone.
+pub fn restore(data: &Path, timestamp: &str) -> Result<String, Box<dyn Error>> {
+    let backups = list(data)?;
+    let matches: Vec<&(String, PathBuf)> = backups
+        .iter()
+        .filter(|(candidate, _)| candidate.starts_with(timestamp))
+        .collect();
+    let (restored, path) = match matches.as_slice() {
+        [only] => *only,
+        [] => return Err(format!("no backup matching '{}'", timestamp).into()),
+        _ => {
+            return Err(format!(
+                "'{}' matches {} backups, give more of the timestamp",
+                timestamp,
+                matches.len()
+            )
+            .into())
+        }
+    };
+    create(data)?;
+    fs::copy(path, data)?;
+    Ok(restored.clone())
+}
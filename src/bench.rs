@@ -0,0 +1,158 @@
+//! Made-up game histories of any size, and the work every report does over
+//! them: loading the data file, building the matchups, and rating the players.
+//! `benches/pipeline.rs` times that work with criterion, which keeps each
+//! run's timings to compare the next against; `bench-data generate` writes a
+//! history out to try a report on.
+//!
+//! The histories are shaped like a league's: a roster of players of varied
+//! skill, a metagame where a few popular decks make up most of the field, and
+//! matches spread over the last few years. The same seed always makes the
+//! same history.
+
+use crate::config::{Aliases, RatingsConfig};
+use crate::ratings::{self, INITIAL_RATING};
+use crate::{build_matchups, store, Archetype, ColorIdentity, Deck, GameLog};
+use chrono::{Days, Local};
+use clap::Subcommand;
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+
+// how far back the first generated match is
+const DAYS: u64 = 3 * 365;
+// distinct decks in the generated metagame
+const DECKS: usize = 40;
+// spread of player skill and deck strength, in Elo points either side of even
+const SKILL: f64 = 200.0;
+const STRENGTH: f64 = 100.0;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum BenchDataCommand {
+    /// Write a made-up data file, e.g. of 10000, 100000, or 1000000 games
+    Generate {
+        /// Where to write it
+        out: PathBuf,
+        #[arg(long, default_value_t = 10_000)]
+        games: usize,
+        #[arg(long, default_value_t = 12)]
+        players: usize,
+        /// Seed for the generator; the same one makes the same data
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+}
+
+/// `count` games between `players` made-up players, from `seed`.
+pub fn generate(count: usize, players: usize, seed: u64) -> Vec<GameLog> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let players: Vec<(String, f64)> = (1..=players.max(1))
+        .map(|n| (format!("Player {}", n), rng.random_range(-SKILL..=SKILL)))
+        .collect();
+    let all: Vec<Deck> = ColorIdentity::iter()
+        .flat_map(|color| Archetype::iter().map(move |archetype| Deck(color, Some(archetype))))
+        .collect();
+    let decks: Vec<(String, f64)> = (0..DECKS.min(all.len()))
+        .map(|_| {
+            let deck = all[rng.random_range(0..all.len())];
+            (deck.to_string(), rng.random_range(-STRENGTH..=STRENGTH))
+        })
+        .collect();
+    // the nth most popular deck is played 1/n as often as the most popular
+    let weights: Vec<f64> = (1..=decks.len()).map(|rank| 1.0 / rank as f64).collect();
+    let total: f64 = weights.iter().sum();
+    let mut pick = |rng: &mut StdRng| {
+        let mut at = rng.random_range(0.0..total);
+        let i = weights
+            .iter()
+            .position(|weight| {
+                at -= weight;
+                at < 0.0
+            })
+            .unwrap_or(decks.len() - 1);
+        &decks[i]
+    };
+
+    let first = Local::now().date_naive() - Days::new(DAYS);
+    (0..count)
+        .map(|i| {
+            let (player, skill) = &players[rng.random_range(0..players.len())];
+            let (deck, strength) = pick(&mut rng);
+            let (opp_deck, opp_strength) = pick(&mut rng);
+            let chance = ratings::expected(
+                INITIAL_RATING + skill + strength,
+                INITIAL_RATING + opp_strength,
+            );
+            // best of three, played out game by game
+            let (mut won, mut lost) = (0, 0);
+            while won < 2 && lost < 2 {
+                if rng.random_bool(chance) {
+                    won += 1;
+                } else {
+                    lost += 1;
+                }
+            }
+            GameLog {
+                id: i as u32 + 1,
+                date: Some(first + Days::new(i as u64 * DAYS / count as u64)),
+                player: player.into(),
+                deck: deck.into(),
+                won,
+                lost,
+                opp_deck: opp_deck.into(),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Writes `games` to a fresh data file at `path`, replacing any there.
+pub fn save(path: &Path, games: &[GameLog]) -> Result<(), Box<dyn Error>> {
+    // a file left by an earlier run isn't worth a backup
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    store::save_games(path, games)
+}
+
+/// Loads the data file at `path`, as every report does first.
+pub fn load(path: &Path) -> Result<Vec<GameLog>, Box<dyn Error>> {
+    store::load_games(path, &Aliases::default())
+}
+
+/// Builds the matchups of every deck against every other, returning how many
+/// there are.
+pub fn matchups(games: &[GameLog]) -> usize {
+    build_matchups(games).len()
+}
+
+/// Each player's Elo rating after `games`.
+pub fn elo(games: &[GameLog]) -> BTreeMap<String, f64> {
+    ratings::elo(games, &RatingsConfig::default())
+}
+
+/// Each player's TrueSkill rating after `games`.
+pub fn trueskill(games: &[GameLog]) -> BTreeMap<String, f64> {
+    ratings::trueskill(games, &RatingsConfig::default())
+        .into_iter()
+        .map(|(player, skill)| (player, skill.mu))
+        .collect()
+}
+
+pub fn run(command: &BenchDataCommand) -> Result<(), Box<dyn Error>> {
+    match command {
+        BenchDataCommand::Generate {
+            out,
+            games,
+            players,
+            seed,
+        } => {
+            let games = generate(*games, *players, *seed);
+            store::save_games(out, &games)?;
+            println!("Wrote {} games to {}", games.len(), out.display());
+        }
+    }
+    Ok(())
+}
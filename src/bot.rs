@@ -0,0 +1,205 @@
+use crate::config::Config;
+use crate::edit::GameFields;
+use crate::{add_game, build_matchups, league, player_wins_losses, store, Deck, GameLog};
+use poise::serenity_prelude as serenity;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tokio::sync::Mutex;
+use tracing::info;
+
+type BotError = Box<dyn Error + Send + Sync>;
+type Context<'a> = poise::Context<'a, Bot, BotError>;
+
+pub struct Bot {
+    data: PathBuf,
+    config: Config,
+    /// Held while a command reads and rewrites the data file
+    writing: Mutex<()>,
+}
+
+impl Bot {
+    /// Games are reloaded for every command so results recorded from the CLI show up.
+    fn games(&self) -> Result<Vec<GameLog>, BotError> {
+        store::load_games(&self.data, &self.config.aliases).map_err(|e| e.to_string().into())
+    }
+
+    fn player(&self, user: &serenity::User) -> String {
+        self.config.aliases.player(&user.name)
+    }
+
+    fn deck(&self, spec: &str) -> Result<Deck, BotError> {
+        let spec = self.config.aliases.deck(spec);
+        Deck::from_str(&spec).map_err(|_| format!("unknown deck '{}'", spec).into())
+    }
+}
+
+/// Wraps report lines in a code block so Discord keeps the columns aligned.
+fn code_block(lines: &[String]) -> String {
+    format!("```\n{}\n```", lines.join("\n"))
+}
+
+/// Record one of your matches
+// every argument is a slash command option
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command)]
+async fn result(
+    ctx: Context<'_>,
+    #[description = "Deck you played"] deck: String,
+    #[description = "Games you won"] won: u32,
+    #[description = "Games you lost"] lost: u32,
+    #[description = "Deck your opponent played"] opp_deck: String,
+    #[description = "Who you played, to record the match for them too"] opponent: Option<String>,
+    notes: Option<String>,
+    #[description = "Event the match was part of"] event: Option<String>,
+    #[description = "Match length, if not best of three"] best_of: Option<u32>,
+) -> Result<(), BotError> {
+    let bot = ctx.data();
+    let fields = GameFields {
+        date: None,
+        player: Some(bot.player(ctx.author())),
+        deck: Some(deck),
+        won: Some(won),
+        lost: Some(lost),
+        opp_deck: Some(opp_deck),
+        opponent,
+        notes,
+        event,
+        best_of,
+        pod: None,
+        mulligans: None,
+        opp_mulligans: None,
+        won_roll: None,
+        on_play: None,
+        team: None,
+    };
+    let reply = {
+        let _writing = bot.writing.lock().await;
+        let game = fields
+            .into_game(&bot.config.aliases)
+            .map_err(|e| e.to_string())?;
+        let summary = format!(
+            "{} ({}) {} - {} {}",
+            game.player, game.deck, game.won, game.lost, game.opp_deck
+        );
+        let by = format!("{} on Discord", ctx.author().name);
+        let id = add_game(
+            &bot.data,
+            bot.games()?,
+            game,
+            &bot.config.roster,
+            "discord",
+            Some(&by),
+        )
+        .map_err(|e| e.to_string())?[0]
+            .id;
+        info!(id, "recorded game from discord");
+        format!("Added game #{}: {}", id, summary)
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// League table, or everyone's record when no league is running
+#[poise::command(slash_command)]
+async fn standings(
+    ctx: Context<'_>,
+    #[description = "Only count games up to the end of this week"] week: Option<u32>,
+) -> Result<(), BotError> {
+    let bot = ctx.data();
+    let games = bot.games()?;
+    let lines = match &bot.config.league {
+        Some(config) => league::standings(config, &games, &bot.config.roster, week),
+        None => bot
+            .config
+            .roster
+            .iter()
+            .map(|player| {
+                let (wins, losses) = player_wins_losses(&games, player);
+                format!("{}: {} - {}", player, wins, losses)
+            })
+            .collect(),
+    };
+    ctx.say(code_block(&lines)).await?;
+    Ok(())
+}
+
+/// Head-to-head record between two decks
+#[poise::command(slash_command)]
+async fn matchup(
+    ctx: Context<'_>,
+    #[description = "e.g. Rb Aggro"] deck: String,
+    #[description = "e.g. Grixis"] opponent: String,
+) -> Result<(), BotError> {
+    let bot = ctx.data();
+    let (deck, opponent) = (bot.deck(&deck)?, bot.deck(&opponent)?);
+    let matchups = build_matchups(&bot.games()?);
+    let reply = match matchups.get(&(deck, opponent)) {
+        Some(matchup) => format!("{}\n{}", matchup, matchup.complement()),
+        None => format!("No games recorded for {} vs. {}.", deck, opponent),
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// Your overall record and how each of your decks has done
+#[poise::command(slash_command)]
+async fn mystats(ctx: Context<'_>) -> Result<(), BotError> {
+    let bot = ctx.data();
+    let player = bot.player(ctx.author());
+    let games = bot.games()?;
+    let (wins, losses) = player_wins_losses(&games, &player);
+    let mut lines = vec![format!("{}'s record: {} - {}", player, wins, losses)];
+
+    let mut decks: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| game.player == player && !game.is_draw())
+        .for_each(|game| {
+            let record = decks.entry(&game.deck).or_insert((0, 0));
+            if game.won > game.lost {
+                record.0 += 1;
+            } else {
+                record.1 += 1;
+            }
+        });
+    let mut decks: Vec<(&str, (u32, u32))> = decks.into_iter().collect();
+    decks.sort_by_key(|(_, (wins, losses))| std::cmp::Reverse(wins + losses));
+    decks.iter().for_each(|(deck, (wins, losses))| {
+        lines.push(format!("  {}: {} - {}", deck, wins, losses))
+    });
+    ctx.say(code_block(&lines)).await?;
+    Ok(())
+}
+
+/// Connects to Discord and serves slash commands until the process is stopped.
+pub fn run(token: &str, data: PathBuf, config: Config) -> Result<(), Box<dyn Error>> {
+    let bot = Bot {
+        data,
+        config,
+        writing: Mutex::new(()),
+    };
+    let framework = poise::Framework::builder()
+        .options(poise::FrameworkOptions {
+            commands: vec![result(), standings(), matchup(), mystats()],
+            ..Default::default()
+        })
+        .setup(|ctx, ready, framework| {
+            Box::pin(async move {
+                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                info!(user = %ready.user.name, "bot connected");
+                Ok(bot)
+            })
+        })
+        .build();
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let mut client =
+            serenity::ClientBuilder::new(token, serenity::GatewayIntents::non_privileged())
+                .framework(framework)
+                .await?;
+        client.start().await
+    })?;
+    Ok(())
+}
@@ -0,0 +1,339 @@
+//! Single- and double-elimination playoff brackets. A bracket is stored as its
+//! seeds and the winner of each match played; who meets whom is worked out
+//! again from those whenever it's needed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Seed(usize),
+    Winner(usize),
+    Loser(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Winners,
+    Losers,
+    /// The grand final, and its reset if the losers' bracket champion wins it
+    Final,
+}
+
+#[derive(Debug)]
+struct Match {
+    side: Side,
+    round: usize,
+    slots: [Slot; 2],
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bracket {
+    pub double: bool,
+    /// Players best seed first
+    pub seeds: Vec<String>,
+    /// Winner of each match played, by match number
+    #[serde(default)]
+    pub winners: BTreeMap<usize, String>,
+}
+
+/// Seeds in bracket order, so the best seeds can only meet late: 1, 8, 4, 5,
+/// 2, 7, 3, 6 for eight players.
+fn seed_order(players: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < players {
+        let size = order.len() * 2;
+        order = order
+            .iter()
+            .flat_map(|&seed| [seed, size - 1 - seed])
+            .collect();
+    }
+    order
+}
+
+/// Every match of a bracket for `players` players, in the order they can be
+/// played.
+fn matches(players: usize, double: bool) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut add = |matches: &mut Vec<Match>, side, round, slots| {
+        matches.push(Match { side, round, slots });
+        matches.len() - 1
+    };
+
+    let order = seed_order(players);
+    let mut rounds: Vec<Vec<usize>> = vec![order
+        .chunks(2)
+        .map(|pair| {
+            add(
+                &mut matches,
+                Side::Winners,
+                1,
+                [Slot::Seed(pair[0]), Slot::Seed(pair[1])],
+            )
+        })
+        .collect()];
+    while let Some(previous) = rounds.last().filter(|round| round.len() > 1) {
+        let round = rounds.len() + 1;
+        let next = previous
+            .clone()
+            .chunks(2)
+            .map(|pair| {
+                add(
+                    &mut matches,
+                    Side::Winners,
+                    round,
+                    [Slot::Winner(pair[0]), Slot::Winner(pair[1])],
+                )
+            })
+            .collect();
+        rounds.push(next);
+    }
+    if !double {
+        return matches;
+    }
+
+    let mut round = 1;
+    let mut survivors: Vec<Slot> = rounds[0]
+        .chunks(2)
+        .map(|pair| {
+            Slot::Winner(add(
+                &mut matches,
+                Side::Losers,
+                round,
+                [Slot::Loser(pair[0]), Slot::Loser(pair[1])],
+            ))
+        })
+        .collect();
+    for winners_round in 1..rounds.len() {
+        // players dropping down meet survivors from the other half of the
+        // bracket, so they don't replay the match that sent them down
+        round += 1;
+        let dropping = rounds[winners_round].iter().rev().map(|m| Slot::Loser(*m));
+        survivors = survivors
+            .clone()
+            .into_iter()
+            .zip(dropping)
+            .map(|(survivor, dropped)| {
+                Slot::Winner(add(&mut matches, Side::Losers, round, [survivor, dropped]))
+            })
+            .collect();
+        if winners_round + 1 < rounds.len() {
+            round += 1;
+            survivors = survivors
+                .clone()
+                .chunks(2)
+                .map(|pair| {
+                    Slot::Winner(add(&mut matches, Side::Losers, round, [pair[0], pair[1]]))
+                })
+                .collect();
+        }
+    }
+    let champions = [Slot::Winner(rounds[rounds.len() - 1][0]), survivors[0]];
+    add(&mut matches, Side::Final, 1, champions);
+    add(&mut matches, Side::Final, 2, champions);
+    matches
+}
+
+impl Bracket {
+    pub fn new(seeds: Vec<String>, double: bool) -> Result<Self, String> {
+        let smallest = if double { 4 } else { 2 };
+        if seeds.len() < smallest || !seeds.len().is_power_of_two() {
+            return Err(format!(
+                "a bracket needs a power of two players, at least {}; got {}",
+                smallest,
+                seeds.len()
+            ));
+        }
+        Ok(Self {
+            double,
+            seeds,
+            winners: BTreeMap::new(),
+        })
+    }
+
+    fn matches(&self) -> Vec<Match> {
+        matches(self.seeds.len(), self.double)
+    }
+
+    fn player(&self, matches: &[Match], slot: Slot) -> Option<&str> {
+        match slot {
+            Slot::Seed(seed) => Some(&self.seeds[seed]),
+            Slot::Winner(m) => self.winners.get(&m).map(String::as_str),
+            Slot::Loser(m) => {
+                let winner = self.winners.get(&m)?;
+                self.players(matches, m)
+                    .into_iter()
+                    .flatten()
+                    .find(|player| *player != winner)
+            }
+        }
+    }
+
+    fn players(&self, matches: &[Match], m: usize) -> [Option<&str>; 2] {
+        matches[m].slots.map(|slot| self.player(matches, slot))
+    }
+
+    /// Whether match `m` has to be played at all: the grand final reset only
+    /// is when the losers' bracket champion wins the first grand final.
+    fn needed(&self, matches: &[Match], m: usize) -> bool {
+        let this = &matches[m];
+        if this.side != Side::Final || this.round == 1 {
+            return true;
+        }
+        let [_, challenger] = self.players(matches, m - 1);
+        challenger.is_some() && self.winners.get(&(m - 1)).map(String::as_str) == challenger
+    }
+
+    /// Matches whose players are both known that haven't been played yet.
+    fn playable(&self, matches: &[Match]) -> Vec<usize> {
+        (0..matches.len())
+            .filter(|m| !self.winners.contains_key(m) && self.needed(matches, *m))
+            .filter(|m| self.players(matches, *m).iter().all(Option::is_some))
+            .collect()
+    }
+
+    /// Records `winner` winning the match they're playing now, returning its
+    /// number.
+    pub fn advance(&mut self, winner: &str) -> Result<usize, String> {
+        let matches = self.matches();
+        let m = self
+            .playable(&matches)
+            .into_iter()
+            .find(|m| self.players(&matches, *m).contains(&Some(winner)))
+            .ok_or_else(|| format!("{} has no bracket match to play", winner))?;
+        self.winners.insert(m, winner.to_string());
+        Ok(m + 1)
+    }
+
+    pub fn champion(&self) -> Option<&str> {
+        let matches = self.matches();
+        let last = matches.len() - 1;
+        if !self.double {
+            return self.winners.get(&last).map(String::as_str);
+        }
+        match self.winners.get(&last) {
+            Some(winner) => Some(winner),
+            None if !self.needed(&matches, last) => {
+                self.winners.get(&(last - 1)).map(String::as_str)
+            }
+            None => None,
+        }
+    }
+
+    /// The bracket round by round, with every result in and who's waiting on
+    /// whom in the matches still to come.
+    pub fn lines(&self) -> Vec<String> {
+        let matches = self.matches();
+        let mut lines = Vec::new();
+        let mut heading = None;
+        for (m, this) in matches.iter().enumerate() {
+            if !self.needed(&matches, m) {
+                continue;
+            }
+            let title = match (this.side, self.double) {
+                (Side::Winners, false) => format!("Round {}", this.round),
+                (Side::Winners, true) => format!("Winners bracket round {}", this.round),
+                (Side::Losers, _) => format!("Losers bracket round {}", this.round),
+                (Side::Final, _) if this.round == 1 => "Grand final".to_string(),
+                (Side::Final, _) => "Grand final reset".to_string(),
+            };
+            if heading.as_ref() != Some(&title) {
+                lines.push(format!("{}:", title));
+                heading = Some(title);
+            }
+            let names: Vec<String> = this
+                .slots
+                .iter()
+                .map(|slot| match (self.player(&matches, *slot), slot) {
+                    (Some(player), _) => player.to_string(),
+                    (None, Slot::Winner(m)) => format!("winner of {}", m + 1),
+                    (None, Slot::Loser(m)) => format!("loser of {}", m + 1),
+                    (None, Slot::Seed(_)) => unreachable!("seeds are always known"),
+                })
+                .collect();
+            let result = self
+                .winners
+                .get(&m)
+                .map_or(String::new(), |winner| format!(": {} won", winner));
+            lines.push(format!(
+                "  {}. {} vs. {}{}",
+                m + 1,
+                names[0],
+                names[1],
+                result
+            ));
+        }
+        if let Some(champion) = self.champion() {
+            lines.push(format!("Champion: {}", champion));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bracket(players: &[&str], double: bool) -> Bracket {
+        Bracket::new(players.iter().map(|p| p.to_string()).collect(), double).unwrap()
+    }
+
+    #[test]
+    fn best_seeds_meet_last() {
+        assert_eq!(seed_order(2), [0, 1]);
+        assert_eq!(seed_order(4), [0, 3, 1, 2]);
+        assert_eq!(seed_order(8), [0, 7, 3, 4, 1, 6, 2, 5]);
+        let order = seed_order(16);
+        // every first-round pairing adds up to the same, the best against the worst
+        assert!(order.chunks(2).all(|pair| pair[0] + pair[1] == 15));
+        // and the top two seeds are in opposite halves
+        assert!(order[..8].contains(&0) && order[8..].contains(&1));
+    }
+
+    #[test]
+    fn a_bracket_needs_a_power_of_two_players() {
+        assert!(Bracket::new(vec!["Ana".to_string()], false).is_err());
+        assert!(Bracket::new(vec!["Ana".to_string(); 6], false).is_err());
+        assert!(Bracket::new(vec!["Ana".to_string(); 2], true).is_err());
+        assert!(Bracket::new(vec!["Ana".to_string(); 4], true).is_ok());
+    }
+
+    #[test]
+    fn single_elimination_plays_down_to_a_champion() {
+        let mut bracket = bracket(&["Ana", "Bea", "Cal", "Dan"], false);
+        let matches = bracket.matches();
+        assert_eq!(bracket.players(&matches, 0), [Some("Ana"), Some("Dan")]);
+        assert_eq!(bracket.players(&matches, 1), [Some("Bea"), Some("Cal")]);
+        assert_eq!(bracket.advance("Ana"), Ok(1));
+        assert_eq!(bracket.advance("Cal"), Ok(2));
+        assert!(bracket.advance("Dan").is_err());
+        assert_eq!(bracket.advance("Cal"), Ok(3));
+        assert_eq!(bracket.champion(), Some("Cal"));
+    }
+
+    #[test]
+    fn the_grand_final_resets_only_if_the_losers_side_wins_it() {
+        // Ana goes through unbeaten; Bea loses to her, then comes back
+        // through the losers' bracket
+        let to_the_final = || {
+            let mut bracket = bracket(&["Ana", "Bea", "Cal", "Dan"], true);
+            for winner in ["Ana", "Bea", "Ana", "Cal", "Bea"] {
+                bracket.advance(winner).unwrap();
+            }
+            bracket
+        };
+        let mut bracket = to_the_final();
+        assert_eq!(
+            bracket.players(&bracket.matches(), 5),
+            [Some("Ana"), Some("Bea")]
+        );
+        assert_eq!(bracket.advance("Ana"), Ok(6));
+        assert_eq!(bracket.champion(), Some("Ana"));
+
+        let mut bracket = to_the_final();
+        assert_eq!(bracket.advance("Bea"), Ok(6));
+        assert_eq!(bracket.champion(), None);
+        assert_eq!(bracket.advance("Bea"), Ok(7));
+        assert_eq!(bracket.champion(), Some("Bea"));
+    }
+}
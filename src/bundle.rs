@@ -0,0 +1,165 @@
+//! Anonymized game data for sharing between playgroups.
+
+use crate::config::Aliases;
+use crate::GameLog;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Bumped whenever the bundle format changes in a way older readers can't handle.
+pub const SCHEMA: u32 = 1;
+/// JSON Schema for bundles, for tools that write them.
+pub const JSON_SCHEMA: &str = include_str!("bundle.schema.json");
+
+/// One game with everything that could identify someone left out: players are
+/// numbered, and notes, events, and pod and team names are dropped.
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleGame {
+    date: Option<NaiveDate>,
+    player: String,
+    deck: String,
+    won: u32,
+    lost: u32,
+    opp_deck: String,
+    #[serde(default)]
+    best_of: Option<u32>,
+    /// Numbered like the players, so seats of one pod stay together
+    #[serde(default)]
+    pod: Option<String>,
+    #[serde(default)]
+    mulligans: Option<u32>,
+    #[serde(default)]
+    opp_mulligans: Option<u32>,
+    #[serde(default)]
+    won_roll: Option<bool>,
+    /// Numbered like the pods
+    #[serde(default)]
+    team: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    schema: u32,
+    exported: NaiveDate,
+    /// Version of protour that wrote the bundle
+    generator: String,
+    games: Vec<BundleGame>,
+}
+
+/// Numbers names in the order they're first seen: `Player 1`, `Player 2`, ...
+fn pseudonyms<'a>(names: impl Iterator<Item = &'a str>, label: &str) -> BTreeMap<&'a str, String> {
+    let mut pseudonyms = BTreeMap::new();
+    for name in names {
+        let next = pseudonyms.len() + 1;
+        pseudonyms
+            .entry(name)
+            .or_insert_with(|| format!("{} {}", label, next));
+    }
+    pseudonyms
+}
+
+pub fn write(path: &Path, games: &[GameLog]) -> Result<(), Box<dyn Error>> {
+    let players = pseudonyms(games.iter().map(|game| game.player.as_str()), "Player");
+    let pods = pseudonyms(games.iter().filter_map(|game| game.pod.as_deref()), "Pod");
+    let teams = pseudonyms(games.iter().filter_map(|game| game.team.as_deref()), "Team");
+    let bundle = Bundle {
+        schema: SCHEMA,
+        exported: Local::now().date_naive(),
+        generator: format!("protour {}", env!("CARGO_PKG_VERSION")),
+        games: games
+            .iter()
+            .map(|game| BundleGame {
+                date: game.date,
+                player: players[game.player.as_str()].clone(),
+                deck: game.deck.trim().to_string(),
+                won: game.won,
+                lost: game.lost,
+                opp_deck: game.opp_deck.trim().to_string(),
+                best_of: game.best_of,
+                pod: game.pod.as_deref().map(|pod| pods[pod].clone()),
+                mulligans: game.mulligans(),
+                opp_mulligans: game.opp_mulligans,
+                won_roll: game.won_roll,
+                team: game.team.as_deref().map(|team| teams[team].clone()),
+            })
+            .collect(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&bundle)?)?;
+    Ok(())
+}
+
+/// Every place `document` breaks the bundle schema, as JSON pointers and what's wrong there.
+fn validate(document: &serde_json::Value) -> Result<(), Vec<String>> {
+    let schema: serde_json::Value =
+        serde_json::from_str(JSON_SCHEMA).expect("bundle schema is valid JSON");
+    let validator = jsonschema::options()
+        .should_validate_formats(true)
+        .build(&schema)
+        .expect("bundle schema is a valid JSON Schema");
+    let errors: Vec<String> = validator
+        .iter_errors(document)
+        .map(|error| {
+            let path = error.instance_path().to_string();
+            let path = if path.is_empty() { "/" } else { &path };
+            format!("{}: {}", path, error)
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Reads a bundle's games, with its players, pods, and teams placed under `source` so
+/// they can't be mistaken for ours or another bundle's.
+pub fn read(path: &Path, source: &str, aliases: &Aliases) -> Result<Vec<GameLog>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+    let document: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|err| format!("{} isn't valid JSON: {}", path.display(), err))?;
+    // a newer bundle can fail this version's schema, so say why first
+    let schema = document.get("schema").and_then(serde_json::Value::as_u64);
+    if let Some(schema) = schema.filter(|schema| *schema > SCHEMA as u64) {
+        return Err(format!(
+            "{} is a version {} bundle, but this protour reads up to version {}",
+            path.display(),
+            schema,
+            SCHEMA
+        )
+        .into());
+    }
+    validate(&document).map_err(|errors| {
+        format!(
+            "{} doesn't match the bundle schema: {}",
+            path.display(),
+            errors.join("; ")
+        )
+    })?;
+    let bundle: Bundle = serde_json::from_value(document)?;
+    Ok(bundle
+        .games
+        .into_iter()
+        .enumerate()
+        .map(|(i, game)| GameLog {
+            // the bundle's games are numbered from 1, like CSV lines after the header
+            line: i as u64 + 1,
+            date: game.date,
+            player: format!("{} {}", source, game.player).into(),
+            deck: aliases.deck(&game.deck).into(),
+            won: game.won,
+            lost: game.lost,
+            opp_deck: aliases.deck(&game.opp_deck).into(),
+            best_of: game.best_of,
+            pod: game.pod.map(|pod| format!("{} {}", source, pod)),
+            mulligans: game.mulligans,
+            opp_mulligans: game.opp_mulligans,
+            won_roll: game.won_roll,
+            team: game.team.map(|team| format!("{} {}", source, team)),
+            ..GameLog::default()
+        })
+        .collect())
+}
@@ -0,0 +1,63 @@
+//! Responses from outside services, cached on disk next to the data file so
+//! reports still build without a connection: `data.csv` keeps them in
+//! `data.cache/`. Each integration decides how long its responses stay
+//! fresh; with `--offline`, whatever is cached is used however old it is.
+
+use crate::crypt;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub struct Cache {
+    dir: PathBuf,
+    pub offline: bool,
+}
+
+/// 64-bit FNV-1a, which unlike std's hasher is the same across Rust
+/// releases, so a cache outlives toolchain updates.
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl Cache {
+    pub fn new(data: &Path, offline: bool) -> Self {
+        Self {
+            dir: crypt::plain_path(data).with_extension("cache"),
+            offline,
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}", fnv1a(key)))
+    }
+
+    /// What's cached under `key` and how long ago it was stored.
+    pub fn get(&self, key: &str) -> Option<(Vec<u8>, Duration)> {
+        let path = self.path(key);
+        let age = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or_default()
+            })?;
+        Some((fs::read(path).ok()?, age))
+    }
+
+    /// What's cached under `key` if it's younger than `ttl`, or, offline, at
+    /// any age.
+    pub fn fresh(&self, key: &str, ttl: Duration) -> Option<Vec<u8>> {
+        self.get(key)
+            .filter(|(_, age)| self.offline || *age < ttl)
+            .map(|(body, _)| body)
+    }
+
+    pub fn put(&self, key: &str, body: &[u8]) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        crypt::write(&self.path(key), body)
+    }
+}
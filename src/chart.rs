@@ -0,0 +1,126 @@
+use crate::config::{Config, RatingsConfig};
+use crate::event::Events;
+use crate::{image, ratings, GameLog};
+use chrono::NaiveDate;
+use clap::Subcommand;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum ChartCommand {
+    /// Players' Elo over time, one line each
+    Rating {
+        /// Player to chart; repeat to compare several
+        #[arg(long, required = true)]
+        player: Vec<String>,
+        /// Render the chart as a PNG instead of printing it
+        #[arg(long)]
+        png: Option<PathBuf>,
+        #[command(flatten)]
+        filter: ratings::Filter,
+    },
+}
+
+// size of the chart printed to the terminal
+const ROWS: usize = 15;
+const COLUMNS: usize = 60;
+const MARKERS: [char; 6] = ['*', 'o', '+', 'x', '#', '@'];
+
+type Series = (String, Vec<(NaiveDate, f64)>);
+
+/// Each player's Elo at the end of every day they played a dated game.
+fn rating_series(games: &[GameLog], config: &RatingsConfig, players: &[String]) -> Vec<Series> {
+    let history = ratings::elo_history(games, config);
+    players
+        .iter()
+        .map(|player| {
+            let daily: BTreeMap<NaiveDate, f64> = history
+                .iter()
+                .filter(|(game, _)| game.player == *player)
+                .filter_map(|(game, rating)| Some((game.date?, *rating)))
+                .collect();
+            (player.clone(), daily.into_iter().collect())
+        })
+        .collect()
+}
+
+fn print(title: &str, series: &[Series]) {
+    let points = || series.iter().flat_map(|(_, points)| points);
+    let (Some(first), Some(last)) = (
+        points().map(|(date, _)| *date).min(),
+        points().map(|(date, _)| *date).max(),
+    ) else {
+        println!("No dated games to chart.");
+        return;
+    };
+    let low = points().map(|(_, value)| *value).fold(f64::MAX, f64::min);
+    let high = points().map(|(_, value)| *value).fold(f64::MIN, f64::max);
+    let span = (high - low).max(1.0);
+    let days = (last - first).num_days().max(1) as f64;
+
+    let mut grid = vec![vec![' '; COLUMNS]; ROWS];
+    series.iter().enumerate().for_each(|(i, (_, points))| {
+        points.iter().for_each(|(date, value)| {
+            let column = ((*date - first).num_days() as f64 / days * (COLUMNS - 1) as f64) as usize;
+            let row = ((high - value) / span * (ROWS - 1) as f64).round() as usize;
+            grid[row][column] = MARKERS[i % MARKERS.len()];
+        })
+    });
+
+    println!("{}", title);
+    let legend: Vec<String> = series
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| format!("{} {}", MARKERS[i % MARKERS.len()], name))
+        .collect();
+    println!("{}\n", legend.join("   "));
+    grid.iter().enumerate().for_each(|(row, cells)| {
+        let label = if row == 0 || row == ROWS - 1 || row == ROWS / 2 {
+            format!("{:>5.0}", high - span * row as f64 / (ROWS - 1) as f64)
+        } else {
+            String::new()
+        };
+        println!("{:>5} |{}", label, cells.iter().collect::<String>());
+    });
+    println!("{:>5} +{}", "", "-".repeat(COLUMNS));
+    let (first, last) = (first.to_string(), last.to_string());
+    println!(
+        "{:>5}  {}{:>width$}",
+        "",
+        first,
+        last,
+        width = COLUMNS - first.len()
+    );
+}
+
+pub fn run(
+    command: ChartCommand,
+    games: Vec<GameLog>,
+    events: &Events,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        ChartCommand::Rating {
+            player,
+            png,
+            filter,
+        } => {
+            let games = filter.apply(games, events, config.league.as_ref())?;
+            let players: Vec<String> = player
+                .iter()
+                .map(|player| config.aliases.player(player))
+                .collect();
+            let series = rating_series(&games, &config.ratings, &players);
+            if let Some((player, _)) = series.iter().find(|(_, points)| points.is_empty()) {
+                return Err(format!("{} has no dated games to chart", player).into());
+            }
+            let title = "Elo rating";
+            match png {
+                Some(path) => image::render_chart(title, &series, path)?,
+                None => print(title, &series),
+            }
+        }
+    }
+    Ok(())
+}
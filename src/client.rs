@@ -0,0 +1,92 @@
+//! A typed client for serve mode, for the companion bot, a web UI, or
+//! anything else that talks to a league's server instead of its data file.
+//! The request and response types here are the ones `serve` itself uses.
+
+use crate::http;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+pub use crate::edit::GameFields;
+
+pub type ClientError = Box<dyn Error + Send + Sync>;
+
+/// A game `post_result` recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recorded {
+    pub id: u32,
+    /// The game as reported, e.g. `Grant (Rb Aggro) 2 - 1 Grixis Midrange`
+    pub game: String,
+}
+
+/// One deck's record against another, in games won and lost.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchupRecord {
+    pub deck: String,
+    pub opponent: String,
+    pub won: u32,
+    pub lost: u32,
+}
+
+/// A player's match record and Elo rating.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerRecord {
+    pub player: String,
+    pub won: u32,
+    pub lost: u32,
+    pub rating: f64,
+}
+
+pub struct Client {
+    http: http::Client,
+    base: String,
+    /// The server's shared token, needed to post results
+    token: Option<String>,
+}
+
+impl Client {
+    /// A client of the server at `base`, e.g. `http://127.0.0.1:8080`.
+    pub fn new(base: &str, token: Option<String>) -> Result<Self, ClientError> {
+        Ok(Self {
+            http: http::Client::new().map_err(|err| err.to_string())?,
+            base: base.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    /// Every matchup of the current season, both ways round.
+    pub async fn get_matchups(&self) -> Result<Vec<MatchupRecord>, ClientError> {
+        let body = self.http.get(&self.url("/matchups")).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// `player`'s record this season; an alias finds them too.
+    pub async fn get_player(&self, player: &str) -> Result<PlayerRecord, ClientError> {
+        let path = format!("/players/{}", crate::serve::encode(player));
+        let body = self.http.get(&self.url(&path)).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Records a game, validated like any other new one. It's sent once: if
+    /// the server can't be reached it's tried again, but not after it may have
+    /// got there, so a timeout can leave a game recorded that this reports as
+    /// failed. Check `get_player` before sending it again.
+    pub async fn post_result(&self, result: &GameFields) -> Result<Recorded, ClientError> {
+        let token = self
+            .token
+            .as_deref()
+            .ok_or("posting results needs the server's token")?;
+        let body = self
+            .http
+            .post_json_with_token(
+                &self.url("/results"),
+                &serde_json::to_string(result)?,
+                token,
+            )
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{deck_vs_field, sample, Color, Deck, Matchup};
+use std::collections::{BTreeMap, BTreeSet};
+
+fn record(label: &str, (wins, losses): (u32, u32)) {
+    println!(
+        "{}: {} - {} ({:.1}%{})",
+        label,
+        wins,
+        losses,
+        wins as f64 / (wins + losses).max(1) as f64 * 100.0,
+        sample::marker(wins + losses)
+    );
+}
+
+/// Every deck containing all of `colors`, their combined record vs. the field,
+/// and how they did against decks without those colors.
+pub fn print(matchups: &BTreeMap<(Deck, Deck), Matchup>, colors: &BTreeSet<Color>) {
+    let names: Vec<String> = colors.iter().map(|color| color.to_string()).collect();
+    let name = names.join("/");
+    let decks: BTreeSet<Deck> = matchups
+        .keys()
+        .map(|(deck, _)| *deck)
+        .filter(|deck| deck.0.contains(colors))
+        .collect();
+    if decks.is_empty() {
+        println!("No games with {} decks yet.", name);
+        return;
+    }
+    println!("Decks containing {}:", name);
+    let mut total = (0, 0);
+    decks.iter().for_each(|deck| {
+        let (wins, losses) = deck_vs_field(matchups, *deck);
+        println!("  {} vs. field: {} - {}", deck, wins, losses);
+        total = (total.0 + wins, total.1 + losses);
+    });
+    println!();
+    record(&format!("All {} decks vs. field", name), total);
+    let outside = matchups
+        .values()
+        .filter(|matchup| decks.contains(&matchup.deck) && !decks.contains(&matchup.opponent))
+        .fold((0, 0), |(wins, losses), matchup| {
+            (wins + matchup.win, losses + matchup.loss)
+        });
+    record(&format!("vs. decks without {}", name), outside);
+}
@@ -0,0 +1,195 @@
+use crate::{
+    build_matchups, deck_vs_field, field_shares, player_wins_losses, sample, Deck, GameLog, Matchup,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One stat before and after, as a fraction.
+struct Change<K> {
+    key: K,
+    old: Option<f64>,
+    new: Option<f64>,
+    /// Games behind each side, for win rates
+    games: Option<(u32, u32)>,
+}
+
+impl<K> Change<K> {
+    fn win_rate(key: K, old: (u32, u32), new: (u32, u32)) -> Self {
+        Self {
+            key,
+            old: win_rate(old),
+            new: win_rate(new),
+            games: Some((old.0 + old.1, new.0 + new.1)),
+        }
+    }
+
+    fn delta(&self) -> f64 {
+        self.new.unwrap_or(0.0) - self.old.unwrap_or(0.0)
+    }
+}
+
+fn percent(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{:.1}%", value * 100.0))
+}
+
+/// Prints changes with the biggest first, as percentage points.
+fn print_changes<K: std::fmt::Display>(title: &str, mut changes: Vec<Change<K>>) {
+    changes.sort_by(|a, b| b.delta().abs().total_cmp(&a.delta().abs()));
+    println!("{}:", title);
+    changes.iter().for_each(|change| {
+        let (old, new) = match change.games {
+            Some((old, new)) => (sample::marker(old), sample::marker(new)),
+            None => ("", ""),
+        };
+        println!(
+            "  {}: {}{} -> {}{} ({:+.1})",
+            change.key,
+            percent(change.old),
+            old,
+            percent(change.new),
+            new,
+            change.delta() * 100.0
+        )
+    });
+}
+
+fn win_rate((wins, losses): (u32, u32)) -> Option<f64> {
+    (wins + losses > 0).then(|| wins as f64 / (wins + losses) as f64)
+}
+
+/// How the metagame, deck win rates, and player win rates moved between two sets of games.
+pub fn print(old: &[GameLog], new: &[GameLog], roster: &[String]) {
+    println!("{} games before, {} after\n", old.len(), new.len());
+
+    let (old_shares, new_shares) = (field_shares(old), field_shares(new));
+    let decks: BTreeSet<Deck> = old_shares
+        .keys()
+        .chain(new_shares.keys())
+        .copied()
+        .collect();
+    print_changes(
+        "Metagame share",
+        decks
+            .iter()
+            .map(|deck| Change {
+                key: *deck,
+                old: old_shares.get(deck).copied(),
+                new: new_shares.get(deck).copied(),
+                games: None,
+            })
+            .collect(),
+    );
+
+    // only decks with results on both sides, or every new deck would top the list
+    let (old_matchups, new_matchups) = (build_matchups(old), build_matchups(new));
+    let win_rates = decks
+        .iter()
+        .map(|deck| {
+            Change::win_rate(
+                *deck,
+                deck_vs_field(&old_matchups, *deck),
+                deck_vs_field(&new_matchups, *deck),
+            )
+        })
+        .filter(|change| change.old.is_some() && change.new.is_some())
+        .collect();
+    println!();
+    print_changes("Win rate vs. field", win_rates);
+
+    let players = roster
+        .iter()
+        .map(|player| {
+            Change::win_rate(
+                player,
+                player_wins_losses(old, player),
+                player_wins_losses(new, player),
+            )
+        })
+        .filter(|change| change.old.is_some() && change.new.is_some())
+        .collect();
+    println!();
+    print_changes("Player win rate", players);
+}
+
+fn record((wins, losses): (u32, u32)) -> String {
+    format!(
+        "{}-{} ({}{})",
+        wins,
+        losses,
+        percent(win_rate((wins, losses))),
+        sample::marker(wins + losses)
+    )
+}
+
+/// Both decks' records against one opponent they've each faced.
+struct Shared {
+    opponent: Deck,
+    ours: (u32, u32),
+    theirs: (u32, u32),
+}
+
+impl Shared {
+    fn gap(&self) -> f64 {
+        win_rate(self.ours).unwrap_or(0.0) - win_rate(self.theirs).unwrap_or(0.0)
+    }
+}
+
+fn total(records: impl Iterator<Item = (u32, u32)>) -> (u32, u32) {
+    records.fold((0, 0), |(wins, losses), (w, l)| (wins + w, losses + l))
+}
+
+/// Two decks' records against only the opponents both have faced, so neither
+/// looks better for having met an easier field.
+pub fn decks(matchups: &BTreeMap<(Deck, Deck), Matchup>, deck: Deck, other: Deck) {
+    let opponents = |of: Deck| -> BTreeMap<Deck, (u32, u32)> {
+        matchups
+            .values()
+            .filter(|matchup| matchup.deck == of && matchup.knows_archetypes())
+            .filter(|matchup| ![deck, other].contains(&matchup.opponent))
+            .filter(|matchup| matchup.win + matchup.loss > 0)
+            .map(|matchup| (matchup.opponent, (matchup.win, matchup.loss)))
+            .collect()
+    };
+    let (ours, theirs) = (opponents(deck), opponents(other));
+    let mut shared: Vec<Shared> = ours
+        .iter()
+        .filter_map(|(opponent, record)| {
+            Some(Shared {
+                opponent: *opponent,
+                ours: *record,
+                theirs: *theirs.get(opponent)?,
+            })
+        })
+        .collect();
+    if let Some(head_to_head) = matchups.get(&(deck, other)) {
+        println!("Head to head: {}\n", head_to_head);
+    }
+    if shared.is_empty() {
+        println!("{} and {} have no opponents in common yet.", deck, other);
+        return;
+    }
+    println!("Against the {} opponents both have faced:", shared.len());
+    println!(
+        "  {}: {}",
+        deck,
+        record(total(shared.iter().map(|shared| shared.ours)))
+    );
+    println!(
+        "  {}: {}",
+        other,
+        record(total(shared.iter().map(|shared| shared.theirs)))
+    );
+
+    shared.sort_by(|a, b| b.gap().abs().total_cmp(&a.gap().abs()));
+    println!("\nBy opponent, biggest difference first:");
+    shared.iter().for_each(|shared| {
+        println!(
+            "  vs {}: {} {}, {} {} ({:+.1})",
+            shared.opponent,
+            deck,
+            record(shared.ours),
+            other,
+            record(shared.theirs),
+            shared.gap() * 100.0
+        )
+    });
+}
@@ -0,0 +1,80 @@
+use crate::event::Events;
+use crate::{notes, Cli, Deck, GameLog};
+use clap::builder::PossibleValuesParser;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Names from the data offered when completing arguments.
+struct Suggestions {
+    players: BTreeSet<String>,
+    decks: BTreeSet<String>,
+    events: BTreeSet<String>,
+    tags: BTreeSet<String>,
+}
+
+impl Suggestions {
+    /// What to suggest for an argument, by its name. `opponent` is a player
+    /// next to a `player` argument and a deck anywhere else.
+    fn for_arg(&self, arg: &str, command: &clap::Command) -> Option<&BTreeSet<String>> {
+        let has_player = command.get_arguments().any(|arg| arg.get_id() == "player");
+        match arg {
+            "player" => Some(&self.players),
+            "opponent" if has_player => Some(&self.players),
+            "deck" | "opp_deck" | "opponent" | "other" => Some(&self.decks),
+            "event" => Some(&self.events),
+            "tag" | "not_tag" => Some(&self.tags),
+            _ => None,
+        }
+    }
+
+    /// `command` and its subcommands with every argument that names something
+    /// in the data limited to those names; only used to generate completions.
+    fn apply(&self, mut command: clap::Command) -> clap::Command {
+        let args: Vec<String> = command
+            .get_arguments()
+            .map(|arg| arg.get_id().to_string())
+            .collect();
+        for arg in args {
+            if let Some(values) = self.for_arg(&arg, &command) {
+                let values: Vec<String> = values.iter().cloned().collect();
+                command = command.mut_arg(arg, |arg| {
+                    arg.value_parser(PossibleValuesParser::new(values))
+                });
+            }
+        }
+        let subcommands: Vec<String> = command
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        for subcommand in subcommands {
+            command = command.mut_subcommand(subcommand, |subcommand| self.apply(subcommand));
+        }
+        command
+    }
+}
+
+pub fn print(shell: Shell, games: &[GameLog], roster: &[String], events: &Events) {
+    let suggestions = Suggestions {
+        players: roster
+            .iter()
+            .cloned()
+            .chain(games.iter().map(|game| game.player.to_string()))
+            .collect(),
+        decks: games
+            .iter()
+            .flat_map(|game| [Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck)])
+            .flatten()
+            .map(|deck| deck.to_string())
+            .collect(),
+        events: events.names().cloned().collect(),
+        tags: games
+            .iter()
+            .flat_map(|game| notes::tags(&game.notes))
+            .collect(),
+    };
+    let mut command = suggestions.apply(Cli::command());
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
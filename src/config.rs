@@ -0,0 +1,339 @@
+use crate::{Archetype, Player};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE: &str = "protour.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// CSV file games are read from
+    pub data: PathBuf,
+    /// Players whose records and recommendations are reported
+    pub roster: Vec<String>,
+    pub aliases: Aliases,
+    pub report: ReportOptions,
+    pub league: Option<LeagueConfig>,
+    pub ratings: RatingsConfig,
+    /// Ranked tiers layered on the Elo ratings; unset turns the ladder off
+    pub ladder: Option<LadderConfig>,
+    /// Parent of a deck or archetype in the archetype hierarchy, e.g.
+    /// `"Red Aggro" = "Aggro"` or `Atraxa = "Midrange"`; parents can have
+    /// parents of their own
+    pub archetypes: BTreeMap<String, String>,
+    /// Rules assigning archetypes from attached decklists, tried in order
+    pub classifier: Vec<ClassifierRule>,
+    /// WASM modules whose metrics are added to the report; see `plugin`
+    pub plugins: Vec<PathBuf>,
+    /// Metrics over each deck's record added to the report and exports, e.g.
+    /// `aggro_index = "wins_vs_aggro / games_vs_aggro"`; see `metrics`
+    pub metrics: BTreeMap<String, String>,
+    /// Shares of an event's prize pool paid to each finishing place, best
+    /// first, e.g. `[50, 30, 20]`; they needn't add up to 100
+    pub payouts: Vec<f64>,
+    /// Match points in events, fixed for each event when it's created; a
+    /// league has its own under [league.points]
+    pub points: Points,
+    /// Minutes in an event round, for events that don't set their own
+    pub round_minutes: u32,
+    /// Decks as they're known in another format or source, each linked to
+    /// the deck it's the counterpart of, e.g. `"Rakdos Sligh" = "Rb Aggro"`;
+    /// reports merge linked decks unless run with `--unlinked`, and the data
+    /// file keeps the names they were recorded under
+    pub links: BTreeMap<String, String>,
+    /// Decks `roll decks` hands out at game night
+    pub deck_pool: Vec<String>,
+    /// Separate playgroups, each overriding the settings above when selected
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Settings for one playgroup; anything left out falls back to the top level.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub data: Option<PathBuf>,
+    pub roster: Option<Vec<String>>,
+    pub aliases: Option<Aliases>,
+    pub league: Option<LeagueConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            data: PathBuf::from("data.csv"),
+            roster: [Player::Grant, Player::Noah, Player::Eamonn, Player::Isaac]
+                .iter()
+                .map(|player| player.to_string())
+                .collect(),
+            aliases: Aliases::default(),
+            report: ReportOptions::default(),
+            league: None,
+            ratings: RatingsConfig::default(),
+            ladder: None,
+            archetypes: BTreeMap::new(),
+            classifier: Vec::new(),
+            plugins: Vec::new(),
+            metrics: BTreeMap::new(),
+            payouts: vec![100.0],
+            points: Points::default(),
+            round_minutes: 50,
+            links: BTreeMap::new(),
+            deck_pool: Vec::new(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Alternate spellings mapped to their canonical names when games are loaded,
+/// e.g. `noah = "Noah"` or `"Grixis" = "Grixis Midrange"`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Aliases {
+    pub players: BTreeMap<String, String>,
+    pub decks: BTreeMap<String, String>,
+}
+
+impl Aliases {
+    pub fn player(&self, name: &str) -> String {
+        self.player_name(name).to_string()
+    }
+
+    /// Like `player`, borrowing the name instead of copying it.
+    pub fn player_name<'a>(&'a self, name: &'a str) -> &'a str {
+        let name = name.trim();
+        self.players.get(name).map_or(name, String::as_str)
+    }
+
+    pub fn deck(&self, spec: &str) -> String {
+        self.deck_spec(spec).to_string()
+    }
+
+    /// Like `deck`, borrowing the spec instead of copying it.
+    pub fn deck_spec<'a>(&'a self, spec: &'a str) -> &'a str {
+        let spec = spec.trim();
+        self.decks.get(spec).map_or(spec, String::as_str)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ReportOptions {
+    /// Always annotate matchups with their significance
+    pub significance: bool,
+    /// How many deck recommendations to print per player
+    pub recommendations: usize,
+    /// Fewest games a win rate needs before it's ranked
+    pub min_games: u32,
+    /// Win rates from fewer games than this are marked as a small sample
+    pub small_sample: u32,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            significance: false,
+            recommendations: 5,
+            min_games: 10,
+            small_sample: crate::sample::DEFAULT_THRESHOLD,
+        }
+    }
+}
+
+/// Ratings of players who stop playing drift back toward an unproven rating.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct RatingsConfig {
+    /// Elo points at stake in each match
+    pub k_factor: f64,
+    /// Weeks without a game before decay starts; unset means ratings never decay
+    pub decay_after_weeks: Option<u32>,
+    /// Elo points lost each further idle week, never below the initial rating
+    pub decay_per_week: f64,
+    /// TrueSkill uncertainty added each further idle week
+    pub sigma_per_week: f64,
+}
+
+impl Default for RatingsConfig {
+    fn default() -> Self {
+        Self {
+            k_factor: 32.0,
+            decay_after_weeks: None,
+            decay_per_week: 10.0,
+            sigma_per_week: 0.5,
+        }
+    }
+}
+
+/// Ladder tier with the Elo that promotes a player into it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tier {
+    pub name: String,
+    pub rating: f64,
+}
+
+/// Everyone starts in the lowest tier and moves at most one tier per game:
+/// up on reaching the next tier's rating, down on falling `demotion_buffer`
+/// below their own tier's.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LadderConfig {
+    /// Lowest tier first
+    pub tiers: Vec<Tier>,
+    pub demotion_buffer: f64,
+}
+
+impl Default for LadderConfig {
+    fn default() -> Self {
+        let tier = |name: &str, rating| Tier {
+            name: name.to_string(),
+            rating,
+        };
+        Self {
+            tiers: vec![
+                tier("Bronze", 0.0),
+                tier("Silver", 1450.0),
+                tier("Gold", 1500.0),
+                tier("Platinum", 1550.0),
+                tier("Diamond", 1600.0),
+                tier("Mythic", 1650.0),
+            ],
+            demotion_buffer: 20.0,
+        }
+    }
+}
+
+/// Archetype of any decklist whose main deck plays at least `at_least` copies
+/// of the named cards between them, e.g. four Atraxa makes an Atraxa deck, or
+/// twenty of a group's cheap creatures makes an Aggro one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClassifierRule {
+    pub archetype: Archetype,
+    pub cards: Vec<String>,
+    #[serde(default = "ClassifierRule::default_at_least")]
+    pub at_least: u32,
+}
+
+impl ClassifierRule {
+    fn default_at_least() -> u32 {
+        1
+    }
+}
+
+/// A points season: every dated game from `start` through `weeks` weeks later counts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeagueConfig {
+    pub start: NaiveDate,
+    #[serde(default = "LeagueConfig::default_weeks")]
+    pub weeks: u32,
+    #[serde(default)]
+    pub points: Points,
+}
+
+impl LeagueConfig {
+    fn default_weeks() -> u32 {
+        52
+    }
+}
+
+/// Points awarded per match result.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Points {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+    /// Bonus for winning without dropping a game, e.g. 2-0
+    pub sweep: u32,
+    /// Added for every match played, whatever the result
+    pub participation: u32,
+}
+
+impl Default for Points {
+    fn default() -> Self {
+        Self {
+            win: 3,
+            draw: 1,
+            loss: 0,
+            sweep: 0,
+            participation: 0,
+        }
+    }
+}
+
+impl Points {
+    /// Points for a match that ended `won` games to `lost`.
+    pub fn for_result(&self, won: u32, lost: u32) -> u32 {
+        let result = if won > lost {
+            self.win + if lost == 0 { self.sweep } else { 0 }
+        } else if won < lost {
+            self.loss
+        } else {
+            self.draw
+        };
+        result + self.participation
+    }
+}
+
+/// `protour.toml` in the working directory wins over `~/.config/protour/protour.toml`.
+fn discover() -> Option<PathBuf> {
+    let local = PathBuf::from(CONFIG_FILE);
+    if local.is_file() {
+        return Some(local);
+    }
+    let home = env::var_os("HOME")?;
+    let global = Path::new(&home).join(".config/protour").join(CONFIG_FILE);
+    global.is_file().then_some(global)
+}
+
+impl Config {
+    /// Loads the given config file, or the discovered one, falling back to defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match discover() {
+                Some(path) => path,
+                None => return Ok(Self::default()),
+            },
+        };
+        tracing::info!(path = %path.display(), "loading config");
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        toml::from_str(&contents)
+            .map_err(|err| format!("invalid config {}: {}", path.display(), err).into())
+    }
+
+    /// Applies the named profile's overrides on top of the top-level settings.
+    pub fn with_profile(mut self, name: &str) -> Result<Self, Box<dyn Error>> {
+        let Some(profile) = self.profiles.remove(name) else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(format!(
+                "unknown profile '{}' (configured: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+            .into());
+        };
+        if let Some(data) = profile.data {
+            self.data = data;
+        }
+        if let Some(roster) = profile.roster {
+            self.roster = roster;
+        }
+        if let Some(aliases) = profile.aliases {
+            self.aliases = aliases;
+        }
+        if let Some(league) = profile.league {
+            self.league = Some(league);
+        }
+        Ok(self)
+    }
+}
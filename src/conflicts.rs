@@ -0,0 +1,284 @@
+//! Matches reported twice: once by the player, and once by their opponent
+//! recording it for them. Reports that agree are merged into one game; ones
+//! that contradict each other are flagged to be put right by hand.
+//!
+//! A match is only known by its day, players, and decks, so two matches of
+//! the same pairing on one night can be taken for one reported twice; each
+//! report is paired with at most one other.
+
+use crate::{audit, store, GameLog};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+
+/// How a second report of a match compares with the first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Report {
+    Agrees,
+    Contradicts,
+}
+
+/// Whether `a` and `b` are the same player's side of one match as reported by
+/// two different people: the same day, decks, and opponent, if both name one.
+/// A game already merged from two reports is never taken for another.
+pub fn compare(a: &GameLog, b: &GameLog) -> Option<Report> {
+    let same_match = a.date.is_some()
+        && a.date == b.date
+        && a.player == b.player
+        && a.deck == b.deck
+        && a.opp_deck == b.opp_deck
+        && a.pod.is_none()
+        && b.pod.is_none()
+        && a.confirmed.is_none()
+        && b.confirmed.is_none()
+        && (a.opponent.is_none() || b.opponent.is_none() || a.opponent == b.opponent)
+        && a.reporter() != b.reporter();
+    if !same_match {
+        return None;
+    }
+    if (a.won, a.lost) == (b.won, b.lost) {
+        Some(Report::Agrees)
+    } else {
+        Some(Report::Contradicts)
+    }
+}
+
+/// Fills in whatever `game` left out from `other`, another report of it;
+/// differing notes are kept side by side. Once the player has reported it
+/// themselves, it's theirs.
+pub fn merge(game: &mut GameLog, other: &GameLog) {
+    if other.reported_by.is_none() {
+        game.reported_by = None;
+    }
+    game.confirmed = Some(true);
+    game.opponent = game.opponent.take().or_else(|| other.opponent.clone());
+    game.event = game.event.take().or_else(|| other.event.clone());
+    game.best_of = game.best_of.or(other.best_of);
+    game.mulligans = game.mulligans.or(other.mulligans);
+    game.opp_mulligans = game.opp_mulligans.or(other.opp_mulligans);
+    game.won_roll = game.won_roll.or(other.won_roll);
+    game.on_play = game.on_play.or(other.on_play);
+    if game.notes.is_empty() {
+        game.notes = other.notes.clone();
+    } else if !other.notes.is_empty() && other.notes != game.notes {
+        game.notes = format!("{} / {}", game.notes, other.notes);
+    }
+}
+
+/// Every pair of reports of the same match, by index into `games`; the
+/// player's own report comes first.
+pub fn find(games: &[GameLog]) -> Vec<(usize, usize, Report)> {
+    // only games of one player on one day can be reports of the same match
+    let mut days: BTreeMap<(NaiveDate, &str), Vec<usize>> = BTreeMap::new();
+    games.iter().enumerate().for_each(|(i, game)| {
+        if let Some(date) = game.date {
+            days.entry((date, &game.player)).or_default().push(i);
+        }
+    });
+    let own = |i: usize| games[i].reported_by.is_none();
+    let mut pairs = Vec::new();
+    for day in days.values().filter(|day| day.len() > 1) {
+        let mut paired = vec![false; day.len()];
+        for a in 0..day.len() {
+            for b in a + 1..day.len() {
+                if paired[a] || paired[b] {
+                    continue;
+                }
+                let (i, j) = (day[a], day[b]);
+                if let Some(report) = compare(&games[i], &games[j]) {
+                    paired[a] = true;
+                    paired[b] = true;
+                    let (first, second) = if own(j) && !own(i) { (j, i) } else { (i, j) };
+                    pairs.push((first, second, report));
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The ids of the games already paired up with another report of them.
+pub fn paired(games: &[GameLog]) -> BTreeSet<u32> {
+    find(games)
+        .into_iter()
+        .flat_map(|(first, second, _)| [games[first].id, games[second].id])
+        .collect()
+}
+
+fn describe(game: &GameLog) -> String {
+    format!(
+        "#{} {} - {}, reported by {}",
+        game.id,
+        game.won,
+        game.lost,
+        game.reporter()
+    )
+}
+
+/// Lists every match reported twice and, with `merge`, merges the reports
+/// that agree, leaving the contradictions to be fixed with `edit` or
+/// `delete`.
+pub fn run(data: &Path, mut games: Vec<GameLog>, merge: bool) -> Result<(), Box<dyn Error>> {
+    let pairs = find(&games);
+    if pairs.is_empty() {
+        println!("No match has been reported twice.");
+        return Ok(());
+    }
+    let mut entries = Vec::new();
+    let mut removed = Vec::new();
+    for (first, second, report) in &pairs {
+        let (a, b) = (&games[*first], &games[*second]);
+        let date = a.date.map(|date| date.to_string()).unwrap_or_default();
+        let verdict = match report {
+            Report::Agrees if merge => "merged",
+            Report::Agrees => "agree",
+            Report::Contradicts => "CONTRADICT",
+        };
+        println!(
+            "{} {} ({}) vs. {}: {} and {} {}",
+            date,
+            a.player,
+            a.deck,
+            a.opp_deck,
+            describe(a),
+            describe(b),
+            verdict
+        );
+        if merge && *report == Report::Agrees {
+            let before = games[*first].clone();
+            let other = games[*second].clone();
+            self::merge(&mut games[*first], &other);
+            entries.push(audit::Entry::new(
+                audit::Action::Edit,
+                before.id,
+                Some(before),
+                Some(games[*first].clone()),
+            ));
+            entries.push(audit::Entry::new(
+                audit::Action::Delete,
+                other.id,
+                Some(other.clone()),
+                None,
+            ));
+            removed.push(other.id);
+        }
+    }
+    let contradictions = pairs
+        .iter()
+        .filter(|(_, _, report)| *report == Report::Contradicts)
+        .count();
+    if contradictions > 0 {
+        println!(
+            "{} contradicting reports; fix the wrong one with `edit` or `delete`",
+            contradictions
+        );
+    }
+    if !removed.is_empty() {
+        games.retain(|game| !removed.contains(&game.id));
+        store::save_games(data, &games)?;
+        audit::append(data, &entries)?;
+        println!("Merged {} doubly reported games", removed.len());
+    } else if pairs.len() > contradictions {
+        println!("Run with --merge to merge the reports that agree");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    /// Grant's own report of beating Noah 2 - 1.
+    fn own() -> GameLog {
+        test_game(1)
+    }
+
+    /// The same match as Noah recorded it for Grant.
+    fn for_them() -> GameLog {
+        GameLog {
+            id: 2,
+            reported_by: Some("Noah".into()),
+            ..own()
+        }
+    }
+
+    #[test]
+    fn reports_by_two_people_of_one_match_are_compared() {
+        assert_eq!(compare(&own(), &for_them()), Some(Report::Agrees));
+        let disputed = GameLog {
+            won: 1,
+            lost: 2,
+            ..for_them()
+        };
+        assert_eq!(compare(&own(), &disputed), Some(Report::Contradicts));
+        // one person entering the same result twice is two matches
+        assert_eq!(compare(&own(), &GameLog { id: 2, ..own() }), None);
+        let other_day = GameLog {
+            date: NaiveDate::from_ymd_opt(2026, 10, 14),
+            ..for_them()
+        };
+        assert_eq!(compare(&own(), &other_day), None);
+        let other_deck = GameLog {
+            deck: "Ub Control".into(),
+            ..for_them()
+        };
+        assert_eq!(compare(&own(), &other_deck), None);
+        let merged = GameLog {
+            confirmed: Some(true),
+            ..own()
+        };
+        assert_eq!(compare(&merged, &for_them()), None);
+    }
+
+    #[test]
+    fn the_own_report_comes_first_and_each_is_paired_once() {
+        let games = vec![
+            for_them(),
+            own(),
+            GameLog {
+                id: 3,
+                ..for_them()
+            },
+        ];
+        assert_eq!(find(&games), [(1, 0, Report::Agrees)]);
+        assert_eq!(paired(&games), BTreeSet::from([1, 2]));
+    }
+
+    #[test]
+    fn merging_fills_in_the_gaps_and_keeps_both_notes() {
+        let mut game = GameLog {
+            notes: "flooded g2".to_string(),
+            ..own()
+        };
+        let other = GameLog {
+            event: Some("FNM".to_string()),
+            on_play: Some(true),
+            mulligans: Some(1),
+            notes: "close one".to_string(),
+            ..for_them()
+        };
+        merge(&mut game, &other);
+        assert_eq!(game.confirmed, Some(true));
+        assert_eq!(game.reported_by, None);
+        assert_eq!(game.event.as_deref(), Some("FNM"));
+        assert_eq!((game.on_play, game.mulligans), (Some(true), Some(1)));
+        assert_eq!(game.notes, "flooded g2 / close one");
+
+        // the player's own report makes a game recorded for them theirs
+        let mut game = for_them();
+        merge(&mut game, &own());
+        assert_eq!(game.reported_by, None);
+        let mut game = for_them();
+        merge(
+            &mut game,
+            &GameLog {
+                id: 3,
+                ..for_them()
+            },
+        );
+        assert_eq!(game.reported_by.as_deref(), Some("Noah"));
+    }
+}
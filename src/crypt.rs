@@ -0,0 +1,87 @@
+use age::secrecy::SecretString;
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// Files with this extension are encrypted with the group's passphrase.
+pub const EXTENSION: &str = "age";
+pub const PASSPHRASE_VAR: &str = "PROTOUR_PASSPHRASE";
+// scrypt cost (N = 2^15); every command decrypts and most re-encrypt, so this
+// stays well below age's ~1 second default
+const WORK_FACTOR: u8 = 15;
+
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == EXTENSION)
+}
+
+/// `data.csv.age` for `data.csv`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+/// The path with any `.age` extension removed, for naming files that sit next to it.
+pub fn plain_path(path: &Path) -> PathBuf {
+    if is_encrypted(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn passphrase() -> Result<SecretString, Box<dyn Error>> {
+    let passphrase = std::env::var(PASSPHRASE_VAR)
+        .map_err(|_| format!("{} must be set to read encrypted data", PASSPHRASE_VAR))?;
+    Ok(SecretString::from(passphrase))
+}
+
+pub fn decrypt(encrypted: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let identity = age::scrypt::Identity::new(passphrase()?);
+    let decryptor = age::Decryptor::new(encrypted)?;
+    let mut reader = decryptor
+        .decrypt(iter::once(&identity as &dyn age::Identity))
+        .map_err(|e| format!("couldn't decrypt data, check {}: {}", PASSPHRASE_VAR, e))?;
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+pub fn encrypt(contents: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut recipient = age::scrypt::Recipient::new(passphrase()?);
+    recipient.set_work_factor(WORK_FACTOR);
+    let encryptor = age::Encryptor::with_recipients(iter::once(&recipient as &dyn age::Recipient))?;
+    let mut encrypted = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut encrypted)?;
+    writer.write_all(contents)?;
+    writer.finish()?;
+    Ok(encrypted)
+}
+
+/// Reads a file, decrypting it if it's a `.age` file.
+pub fn read(path: &Path) -> Result<Vec<u8>, Box<dyn Error>> {
+    let contents = fs::read(path)?;
+    if is_encrypted(path) {
+        decrypt(&contents)
+    } else {
+        Ok(contents)
+    }
+}
+
+/// Writes a file through a temporary one, encrypting it if it's a `.age` file.
+pub fn write(path: &Path, contents: &[u8]) -> Result<(), Box<dyn Error>> {
+    let contents = if is_encrypted(path) {
+        encrypt(contents)?
+    } else {
+        contents.to_vec()
+    };
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,453 @@
+use crate::config::ClassifierRule;
+use crate::{audit, crypt, store, Deck, GameLog};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum DecklistCommand {
+    /// Attach the list a game was played with, pasted from Arena's export
+    /// (e.g. `4 Lightning Strike (DMU) 137`)
+    Attach {
+        id: u32,
+        /// File holding the list; read from stdin if left out
+        file: Option<PathBuf>,
+    },
+    /// Print the list attached to a game
+    Show { id: u32 },
+    /// Group attached lists into archetypes by the cards they share, and flag
+    /// games declared as a different deck than their group
+    Cluster {
+        /// How alike two main decks have to be (Jaccard similarity of their
+        /// card names, basic lands aside) to land in the same group
+        #[arg(long, default_value_t = 0.5)]
+        threshold: f64,
+    },
+    /// Check each game's archetype against the one the configured classifier
+    /// rules give its list
+    Classify {
+        /// Rewrite the archetype of games that disagree, keeping their colors
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Card {
+    pub count: u32,
+    pub name: String,
+    /// Set code and collector number of the printing, when the list gave them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Decklist {
+    pub main: Vec<Card>,
+    #[serde(default)]
+    pub sideboard: Vec<Card>,
+}
+
+/// Card names as Oracle spells them, whatever the client or keyboard did to
+/// the punctuation: curly quotes straightened, runs of spaces collapsed, and
+/// split cards joined with ` // `.
+pub fn normalize_name(name: &str) -> String {
+    let name = name.replace(['\u{2018}', '\u{2019}'], "'");
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    name.split('/')
+        .map(str::trim)
+        .filter(|half| !half.is_empty())
+        .collect::<Vec<_>>()
+        .join(" // ")
+}
+
+/// One card line: a count, the name, and optionally `(SET) number`.
+fn parse_card(line: &str) -> Result<Card, String> {
+    let (count, rest) = line
+        .split_once(' ')
+        .ok_or_else(|| format!("'{}' isn't a card line", line))?;
+    let count = count
+        .trim_end_matches('x')
+        .parse()
+        .map_err(|_| format!("'{}' doesn't start with a card count", line))?;
+    let (name, set, number) = match rest.rsplit_once(" (") {
+        Some((name, printing)) => match printing.split_once(')') {
+            Some((set, number)) => {
+                let number = number.trim();
+                (
+                    name,
+                    Some(set.trim().to_uppercase()),
+                    (!number.is_empty()).then(|| number.to_string()),
+                )
+            }
+            None => (rest, None, None),
+        },
+        None => (rest, None, None),
+    };
+    let name = normalize_name(name);
+    if name.is_empty() {
+        return Err(format!("'{}' has no card name", line));
+    }
+    Ok(Card {
+        count,
+        name,
+        set,
+        number,
+    })
+}
+
+/// Parses a list in Arena's export format. Cards go in the main deck until a
+/// `Sideboard` header or, in lists without headers, a blank line after the
+/// main deck; `Deck`, `Commander`, and `Companion` headers and the `About`
+/// block with the deck's name are skipped.
+pub fn parse_arena(text: &str) -> Result<Decklist, String> {
+    let mut list = Decklist::default();
+    let mut sideboard = false;
+    let mut about = false;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            about = false;
+            sideboard |= !list.main.is_empty();
+            continue;
+        }
+        match line.to_lowercase().as_str() {
+            "deck" | "commander" | "companion" => {
+                sideboard = false;
+                continue;
+            }
+            "sideboard" => {
+                sideboard = true;
+                continue;
+            }
+            "about" => {
+                about = true;
+                continue;
+            }
+            _ if about => continue,
+            _ => {}
+        }
+        let card = parse_card(line).map_err(|err| format!("line {}: {}", i + 1, err))?;
+        if sideboard {
+            list.sideboard.push(card);
+        } else {
+            list.main.push(card);
+        }
+    }
+    if list.main.is_empty() {
+        return Err("the list has no main deck cards".to_string());
+    }
+    Ok(list)
+}
+
+/// Lists attached to games, by game id, stored next to the data file:
+/// `data.csv` keeps them in `data.decklists.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Decklists(BTreeMap<u32, Decklist>);
+
+impl Decklists {
+    pub fn path_for(data: &Path) -> PathBuf {
+        crypt::plain_path(data).with_extension("decklists.json")
+    }
+
+    pub fn load(data: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path_for(data);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, data: &Path) -> Result<(), Box<dyn Error>> {
+        let path = Self::path_for(data);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Decklist> {
+        self.0.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&u32, &Decklist)> {
+        self.0.iter()
+    }
+}
+
+const BASIC_LANDS: [&str; 11] = [
+    "Plains",
+    "Island",
+    "Swamp",
+    "Mountain",
+    "Forest",
+    "Wastes",
+    "Snow-Covered Plains",
+    "Snow-Covered Island",
+    "Snow-Covered Swamp",
+    "Snow-Covered Mountain",
+    "Snow-Covered Forest",
+];
+
+/// Names of the main deck's cards, leaving out basic lands, which every list
+/// of a color shares whatever it's playing.
+fn card_names(list: &Decklist) -> BTreeSet<&str> {
+    list.main
+        .iter()
+        .map(|card| card.name.as_str())
+        .filter(|name| !BASIC_LANDS.contains(name))
+        .collect()
+}
+
+pub fn jaccard(a: &BTreeSet<&str>, b: &BTreeSet<&str>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Groups of game ids whose lists are chained together by similarities of at
+/// least `threshold`, largest group first.
+pub fn clusters(lists: &Decklists, threshold: f64) -> Vec<Vec<u32>> {
+    let ids: Vec<u32> = lists.0.keys().copied().collect();
+    let names: Vec<BTreeSet<&str>> = lists.0.values().map(card_names).collect();
+    let mut group: Vec<usize> = (0..ids.len()).collect();
+    fn root(group: &mut [usize], mut i: usize) -> usize {
+        while group[i] != i {
+            group[i] = group[group[i]];
+            i = group[i];
+        }
+        i
+    }
+    for i in 0..ids.len() {
+        for j in i + 1..ids.len() {
+            if jaccard(&names[i], &names[j]) >= threshold {
+                let (a, b) = (root(&mut group, i), root(&mut group, j));
+                group[a] = b;
+            }
+        }
+    }
+    let mut clusters: BTreeMap<usize, Vec<u32>> = BTreeMap::new();
+    (0..ids.len()).for_each(|i| {
+        let root = root(&mut group, i);
+        clusters.entry(root).or_default().push(ids[i]);
+    });
+    let mut clusters: Vec<Vec<u32>> = clusters.into_values().collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    clusters
+}
+
+/// The deck a game was declared as, in its canonical spelling when it parses.
+fn declared(game: &GameLog) -> String {
+    game.deck
+        .parse::<Deck>()
+        .map_or_else(|_| game.deck.trim().to_string(), |deck| deck.to_string())
+}
+
+fn print_clusters(lists: &Decklists, games: &[GameLog], threshold: f64) {
+    let by_id: BTreeMap<u32, &GameLog> = games.iter().map(|game| (game.id, game)).collect();
+    let clusters = clusters(lists, threshold);
+    if clusters.is_empty() {
+        println!("No decklists attached.");
+        return;
+    }
+    let mut alone = 0;
+    let mut number = 0;
+    for cluster in &clusters {
+        if cluster.len() < 2 {
+            alone += 1;
+            continue;
+        }
+        number += 1;
+        let mut labels: BTreeMap<String, usize> = BTreeMap::new();
+        cluster
+            .iter()
+            .filter_map(|id| by_id.get(id))
+            .for_each(|game| *labels.entry(declared(game)).or_default() += 1);
+        let label = labels
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map_or("unlabeled", |(label, _)| label.as_str());
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        cluster
+            .iter()
+            .filter_map(|id| lists.get(*id))
+            .flat_map(card_names)
+            .for_each(|name| *counts.entry(name).or_default() += 1);
+        let mut core: Vec<(&str, usize)> = counts.into_iter().collect();
+        core.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let core: Vec<&str> = core.iter().take(5).map(|(name, _)| *name).collect();
+
+        println!(
+            "Cluster {}: {} lists, suggested label {}",
+            number,
+            cluster.len(),
+            label
+        );
+        println!("  Core cards: {}", core.join(", "));
+        cluster
+            .iter()
+            .filter_map(|id| by_id.get(id))
+            .filter(|game| declared(game) != label)
+            .for_each(|game| {
+                println!(
+                    "  #{} {} declared {}, but the list plays like {}",
+                    game.id,
+                    game.player,
+                    declared(game),
+                    label
+                )
+            });
+    }
+    if alone > 0 {
+        println!("Lists unlike any other: {}", alone);
+    }
+}
+
+/// Archetype of the first rule the list satisfies.
+pub fn classify<'a>(list: &Decklist, rules: &'a [ClassifierRule]) -> Option<&'a ClassifierRule> {
+    rules.iter().find(|rule| {
+        let copies: u32 = list
+            .main
+            .iter()
+            .filter(|card| {
+                rule.cards
+                    .iter()
+                    .any(|name| normalize_name(name).eq_ignore_ascii_case(&card.name))
+            })
+            .map(|card| card.count)
+            .sum();
+        copies >= rule.at_least
+    })
+}
+
+fn classify_games(
+    lists: &Decklists,
+    games: &mut [GameLog],
+    rules: &[ClassifierRule],
+    data: &Path,
+    fix: bool,
+) -> Result<(), Box<dyn Error>> {
+    if rules.is_empty() {
+        return Err("no [[classifier]] rules in the config".into());
+    }
+    let mut entries = Vec::new();
+    let (mut checked, mut unmatched) = (0, 0);
+    for game in games.iter_mut() {
+        let Some(list) = lists.get(game.id) else {
+            continue;
+        };
+        checked += 1;
+        let Some(rule) = classify(list, rules) else {
+            unmatched += 1;
+            continue;
+        };
+        let Ok(deck) = game.deck.parse::<Deck>() else {
+            println!("#{} {}: '{}' isn't a deck", game.id, game.player, game.deck);
+            continue;
+        };
+        if deck.1 == Some(rule.archetype) {
+            continue;
+        }
+        let fixed = Deck(deck.0, Some(rule.archetype));
+        println!(
+            "#{} {} declared {}, but the list is {}",
+            game.id, game.player, deck, fixed
+        );
+        if fix {
+            let before = game.clone();
+            game.deck = fixed.to_string().into();
+            entries.push(audit::Entry::new(
+                audit::Action::Edit,
+                game.id,
+                Some(before),
+                Some(game.clone()),
+            ));
+        }
+    }
+    println!("Checked {} lists; {} matched no rule", checked, unmatched);
+    if !entries.is_empty() {
+        store::save_games(data, games)?;
+        audit::append(data, &entries)?;
+        println!("Fixed the archetype of {} games", entries.len());
+    }
+    Ok(())
+}
+
+fn print_cards(cards: &[Card]) {
+    cards
+        .iter()
+        .for_each(|card| match (&card.set, &card.number) {
+            (Some(set), Some(number)) => {
+                println!("  {} {} ({}) {}", card.count, card.name, set, number)
+            }
+            (Some(set), None) => println!("  {} {} ({})", card.count, card.name, set),
+            _ => println!("  {} {}", card.count, card.name),
+        });
+}
+
+fn total(cards: &[Card]) -> u32 {
+    cards.iter().map(|card| card.count).sum()
+}
+
+pub fn run(
+    command: DecklistCommand,
+    data: &Path,
+    games: Vec<GameLog>,
+    rules: &[ClassifierRule],
+) -> Result<(), Box<dyn Error>> {
+    let mut lists = Decklists::load(data)?;
+    match command {
+        DecklistCommand::Attach { id, file } => {
+            let game = games
+                .iter()
+                .find(|game| game.id == id)
+                .ok_or_else(|| format!("no game with id {}", id))?;
+            let text = match file {
+                Some(path) => fs::read_to_string(path)?,
+                None => {
+                    let mut text = String::new();
+                    std::io::stdin().read_to_string(&mut text)?;
+                    text
+                }
+            };
+            let list = parse_arena(&text)?;
+            println!(
+                "Attached a {}-card list with a {}-card sideboard to #{} ({} on {})",
+                total(&list.main),
+                total(&list.sideboard),
+                id,
+                game.player,
+                game.deck.trim()
+            );
+            lists.0.insert(id, list);
+            lists.save(data)?;
+        }
+        DecklistCommand::Show { id } => {
+            let list = lists
+                .get(id)
+                .ok_or_else(|| format!("game #{} has no list attached", id))?;
+            println!("Deck ({} cards):", total(&list.main));
+            print_cards(&list.main);
+            if !list.sideboard.is_empty() {
+                println!("\nSideboard ({} cards):", total(&list.sideboard));
+                print_cards(&list.sideboard);
+            }
+        }
+        DecklistCommand::Cluster { threshold } => print_clusters(&lists, &games, threshold),
+        DecklistCommand::Classify { fix } => {
+            let mut games = games;
+            classify_games(&lists, &mut games, rules, data, fix)?
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,306 @@
+//! Detail pages: everything recorded about one deck or player, gathered in
+//! one place.
+
+use crate::config::RatingsConfig;
+use crate::prices::{self, DeckCost};
+use crate::season::Seasons;
+use crate::{field_shares, game_line, ratings, rivals, sample, Deck, GameLog, Matchup};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// games listed as recent
+const RECENT: usize = 10;
+
+fn rate(wins: u32, losses: u32) -> String {
+    if wins + losses == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{:.1}%{}",
+        wins as f64 / (wins + losses) as f64 * 100.0,
+        sample::marker(wins + losses)
+    )
+}
+
+/// Wins and losses among `games`, draws aside.
+fn record<'a>(games: impl IntoIterator<Item = &'a GameLog>) -> (u32, u32) {
+    games
+        .into_iter()
+        .filter(|game| !game.is_draw())
+        .fold((0, 0), |(w, l), game| {
+            if game.won > game.lost {
+                (w + 1, l)
+            } else {
+                (w, l + 1)
+            }
+        })
+}
+
+/// The last rating of each month in a rating history.
+fn monthly(history: impl Iterator<Item = (Option<chrono::NaiveDate>, f64)>) -> Vec<(String, f64)> {
+    let mut months: BTreeMap<String, f64> = BTreeMap::new();
+    history
+        .filter_map(|(date, rating)| Some((date?.format("%Y-%m").to_string(), rating)))
+        .for_each(|(month, rating)| {
+            months.insert(month, rating);
+        });
+    months.into_iter().collect()
+}
+
+/// Whether `deck` was played on either side of a game.
+fn involves(game: &GameLog, deck: Deck) -> bool {
+    Deck::from_str(&game.deck).is_ok_and(|d| d == deck)
+        || Deck::from_str(&game.opp_deck).is_ok_and(|o| o == deck)
+}
+
+/// The deck's Elo at the end of each month it played in, rating every deck
+/// as if it were a player against the deck it faced; undated games count
+/// towards the first month.
+fn rating_trend(games: &[GameLog], deck: Deck, config: &RatingsConfig) -> Vec<(String, f64)> {
+    let name = deck.to_string();
+    let seats: Vec<GameLog> = games
+        .iter()
+        .filter(|game| game.pod.is_none() && !game.is_complement())
+        .flat_map(|game| {
+            let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+            let (Ok(deck), Ok(opponent)) = decks else {
+                return Vec::new();
+            };
+            // a mirror says nothing about how the deck stands
+            if deck == opponent {
+                return Vec::new();
+            }
+            let seat = GameLog {
+                player: deck.to_string().into(),
+                opponent: Some(opponent.to_string().into()),
+                reported_by: None,
+                confirmed: None,
+                ..game.clone()
+            };
+            let other = seat.complement();
+            [Some(seat), other].into_iter().flatten().collect()
+        })
+        .collect();
+    monthly(
+        ratings::elo_history(&seats, config)
+            .into_iter()
+            .filter(|(game, _)| game.player == name)
+            .map(|(game, rating)| (game.date, rating)),
+    )
+}
+
+pub fn deck(
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    deck: Deck,
+    cost: Option<&DeckCost>,
+    config: &RatingsConfig,
+) {
+    let played: Vec<&GameLog> = ratings::chronological(games)
+        .into_iter()
+        .filter(|game| involves(game, deck))
+        .collect();
+    if played.is_empty() {
+        println!("No games recorded with {}.", deck);
+        return;
+    }
+
+    let mut opponents: Vec<&Matchup> = matchups
+        .values()
+        .filter(|matchup| matchup.deck == deck && matchup.opponent != deck)
+        .collect();
+    let (wins, losses) = opponents.iter().fold((0, 0), |(w, l), matchup| {
+        (w + matchup.win, l + matchup.loss)
+    });
+    println!("{}", deck);
+    println!(
+        "Overall vs. field: {} - {} ({})",
+        wins,
+        losses,
+        rate(wins, losses)
+    );
+    if let Some(mirror) = matchups.get(&(deck, deck)) {
+        // both sides of a mirror land on the same matchup, a win and a loss each
+        println!("Mirror matches: {}", (mirror.win + mirror.loss) / 2);
+    }
+    if let Some(cost) = cost {
+        println!("Approximate cost: {}", prices::describe(cost));
+    }
+
+    // only the logging side of a game says who piloted the deck
+    let mut pilots: BTreeMap<&str, Vec<&GameLog>> = BTreeMap::new();
+    played
+        .iter()
+        .filter(|game| Deck::from_str(&game.deck).is_ok_and(|d| d == deck))
+        .for_each(|game| pilots.entry(&game.player).or_default().push(game));
+    let pilots: BTreeMap<&str, (u32, u32)> = pilots
+        .into_iter()
+        .map(|(pilot, games)| (pilot, record(games)))
+        .collect();
+    println!("\nPilots:");
+    if pilots.is_empty() {
+        println!("  none recorded; it was only logged as an opponent's deck");
+    }
+    pilots
+        .iter()
+        .for_each(|(pilot, (w, l))| println!("  {:<10} {} - {} ({})", pilot, w, l, rate(*w, *l)));
+
+    // the record above counts opponents known only by their colors, but
+    // matchups need both archetypes
+    opponents.retain(|matchup| matchup.knows_archetypes());
+    opponents.sort_by_key(|matchup| Reverse(matchup.win + matchup.loss));
+    println!("\nMatchups:");
+    opponents.iter().for_each(|matchup| {
+        println!(
+            "  {:<18} {:>3} - {:<3} {}{}",
+            matchup.opponent.to_string(),
+            matchup.win,
+            matchup.loss,
+            rate(matchup.win, matchup.loss),
+            matchup.significance_marker()
+        )
+    });
+
+    println!("\nRecent games:");
+    played
+        .iter()
+        .rev()
+        .filter(|game| !game.is_complement())
+        .take(RECENT)
+        .for_each(|game| println!("  {}", game_line(game)));
+
+    let trend = rating_trend(games, deck, config);
+    if !trend.is_empty() {
+        println!("\nRating by month:");
+        trend
+            .iter()
+            .for_each(|(month, rating)| println!("  {} {:.0}", month, rating));
+    }
+
+    let shares = field_shares(games);
+    let mut ranked: Vec<(&Deck, &f64)> = shares.iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(a.1));
+    if let Some(rank) = ranked.iter().position(|(d, _)| **d == deck) {
+        println!(
+            "\nMetagame share: {:.1}% of decks played, #{} of {}",
+            shares[&deck] * 100.0,
+            rank + 1,
+            ranked.len()
+        );
+    }
+}
+
+/// The longest winning and losing runs in `games`, and the run they're on
+/// now: its length and whether it's a winning one.
+fn streaks(games: &[&GameLog]) -> (u32, u32, Option<(u32, bool)>) {
+    let (mut longest_won, mut longest_lost) = (0, 0);
+    let mut current: Option<(u32, bool)> = None;
+    games
+        .iter()
+        .filter(|game| !game.is_draw())
+        .for_each(|game| {
+            let won = game.won > game.lost;
+            let length = match current {
+                Some((length, streak)) if streak == won => length + 1,
+                _ => 1,
+            };
+            current = Some((length, won));
+            if won {
+                longest_won = longest_won.max(length);
+            } else {
+                longest_lost = longest_lost.max(length);
+            }
+        });
+    (longest_won, longest_lost, current)
+}
+
+pub fn player(games: &[GameLog], player: &str, seasons: &Seasons, config: &RatingsConfig) {
+    let played: Vec<&GameLog> = ratings::chronological(games)
+        .into_iter()
+        .filter(|game| game.player == player)
+        .collect();
+    if played.is_empty() {
+        println!("No games recorded for {}.", player);
+        return;
+    }
+
+    let (wins, losses) = record(played.iter().copied());
+    println!("{}", player);
+    println!("Lifetime: {} - {} ({})", wins, losses, rate(wins, losses));
+    let mut by_season: Vec<(&str, Vec<&GameLog>)> = Vec::new();
+    played.iter().for_each(|game| {
+        let name = seasons
+            .season_of(game)
+            .map_or("Current season", |season| season.name.as_str());
+        match by_season.iter_mut().find(|(season, _)| *season == name) {
+            Some((_, games)) => games.push(game),
+            None => by_season.push((name, vec![game])),
+        }
+    });
+    by_season.iter().for_each(|(season, games)| {
+        let (w, l) = record(games.iter().copied());
+        println!("  {}: {} - {} ({})", season, w, l, rate(w, l))
+    });
+
+    let mut decks: BTreeMap<&str, Vec<&GameLog>> = BTreeMap::new();
+    played
+        .iter()
+        .for_each(|game| decks.entry(game.deck.trim()).or_default().push(game));
+    let mut decks: Vec<(&str, usize, (u32, u32))> = decks
+        .into_iter()
+        .map(|(deck, games)| (deck, games.len(), record(games)))
+        .collect();
+    decks.sort_by_key(|(_, count, _)| Reverse(*count));
+    println!("\nDecks:");
+    decks.iter().for_each(|(deck, _, (w, l))| {
+        println!("  {:<18} {:>3} - {:<3} {}", deck, w, l, rate(*w, *l))
+    });
+
+    let head_to_head: Vec<(String, Vec<bool>)> = rivals::head_to_head(games)
+        .into_iter()
+        .filter(|((p, _), _)| p == player)
+        .map(|((_, opponent), results)| (opponent, results))
+        .collect();
+    if !head_to_head.is_empty() {
+        println!("\nHead to head:");
+        head_to_head.iter().for_each(|(opponent, results)| {
+            let w = results.iter().filter(|won| **won).count() as u32;
+            let l = results.len() as u32 - w;
+            println!("  {:<10} {} - {} ({})", opponent, w, l, rate(w, l))
+        });
+    }
+
+    let history = monthly(
+        ratings::elo_history(games, config)
+            .into_iter()
+            .filter(|(game, _)| game.player == player)
+            .map(|(game, rating)| (game.date, rating)),
+    );
+    if !history.is_empty() {
+        println!("\nRating by month:");
+        history
+            .iter()
+            .for_each(|(month, rating)| println!("  {} {:.0}", month, rating));
+    }
+
+    let (longest_won, longest_lost, current) = streaks(&played);
+    println!("\nLongest win streak: {}", longest_won);
+    println!("Longest losing streak: {}", longest_lost);
+    if let Some((length, won)) = current {
+        let kind = match (won, length) {
+            (true, 1) => "win",
+            (true, _) => "wins",
+            (false, 1) => "loss",
+            (false, _) => "losses",
+        };
+        println!("Current streak: {} {}", length, kind);
+    }
+
+    println!("\nRecent games:");
+    played
+        .iter()
+        .rev()
+        .take(RECENT)
+        .for_each(|game| println!("  {}", game_line(game)));
+}
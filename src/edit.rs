@@ -0,0 +1,171 @@
+use crate::config::Aliases;
+use crate::intern::Name;
+use crate::{GameLog, DEFAULT_BEST_OF};
+use chrono::{Local, NaiveDate};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+fn prompt<T: FromStr>(label: &str) -> Result<T, Box<dyn Error>>
+where
+    T::Err: Error + 'static,
+{
+    print!("{}: ", label);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(format!("no value given for {}", label).into());
+    }
+    Ok(line.trim().parse()?)
+}
+
+/// Game fields that can be set from the command line or a submitted result;
+/// anything left out is kept as it was.
+#[derive(Debug, Default, Args, Deserialize, Serialize)]
+pub struct GameFields {
+    #[arg(long)]
+    pub date: Option<NaiveDate>,
+    #[arg(long)]
+    pub player: Option<String>,
+    #[arg(long)]
+    pub deck: Option<String>,
+    #[arg(long)]
+    pub won: Option<u32>,
+    #[arg(long)]
+    pub lost: Option<u32>,
+    #[arg(long)]
+    pub opp_deck: Option<String>,
+    /// Who you played, if they're on the roster; the game is recorded for
+    /// them too
+    #[arg(long)]
+    pub opponent: Option<String>,
+    #[arg(long)]
+    pub notes: Option<String>,
+    /// Event the game was played in
+    #[arg(long)]
+    pub event: Option<String>,
+    /// Match length, e.g. 1 for Arena ladder or 5 for finals (default 3)
+    #[arg(long)]
+    pub best_of: Option<u32>,
+    /// Multiplayer game this is one seat of; record a row for every seat
+    #[arg(long)]
+    pub pod: Option<String>,
+    /// Mulligans you took across the match
+    #[arg(long)]
+    pub mulligans: Option<u32>,
+    /// Mulligans your opponent took across the match
+    #[arg(long)]
+    pub opp_mulligans: Option<u32>,
+    /// Whether you won the die roll (true or false)
+    #[arg(long)]
+    pub won_roll: Option<bool>,
+    /// Whether you were on the play in the first game (true or false)
+    #[arg(long)]
+    pub on_play: Option<bool>,
+    /// Team you were on, for a team game recorded as a pod
+    #[arg(long)]
+    pub team: Option<String>,
+}
+
+impl GameFields {
+    pub fn apply(&self, game: &mut GameLog, aliases: &Aliases) {
+        if let Some(date) = self.date {
+            game.date = Some(date);
+        }
+        if let Some(player) = &self.player {
+            game.player = aliases.player(player).into();
+        }
+        if let Some(deck) = &self.deck {
+            game.deck = aliases.deck(deck).into();
+        }
+        if let Some(won) = self.won {
+            game.won = won;
+        }
+        if let Some(lost) = self.lost {
+            game.lost = lost;
+        }
+        if let Some(opp_deck) = &self.opp_deck {
+            game.opp_deck = aliases.deck(opp_deck).into();
+        }
+        if let Some(opponent) = &self.opponent {
+            game.opponent = Some(opponent)
+                .filter(|opponent| !opponent.is_empty())
+                .map(|opponent| aliases.player(opponent).into());
+        }
+        if let Some(notes) = &self.notes {
+            game.notes = notes.clone();
+        }
+        if let Some(event) = &self.event {
+            game.event = Some(event.clone()).filter(|event| !event.is_empty());
+        }
+        if let Some(pod) = &self.pod {
+            game.pod = Some(pod.clone()).filter(|pod| !pod.is_empty());
+        }
+        if let Some(mulligans) = self.mulligans {
+            game.mulligans = Some(mulligans);
+        }
+        if let Some(opp_mulligans) = self.opp_mulligans {
+            game.opp_mulligans = Some(opp_mulligans);
+        }
+        if let Some(won_roll) = self.won_roll {
+            game.won_roll = Some(won_roll);
+        }
+        if let Some(on_play) = self.on_play {
+            game.on_play = Some(on_play);
+        }
+        if let Some(team) = &self.team {
+            game.team = Some(team.clone()).filter(|team| !team.is_empty());
+        }
+        if let Some(best_of) = self.best_of {
+            game.best_of = Some(best_of).filter(|best_of| *best_of != DEFAULT_BEST_OF);
+        }
+    }
+}
+
+impl GameFields {
+    /// Asks on stdin for every required field that wasn't passed as a flag.
+    pub fn prompt_missing(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.player.is_none() {
+            self.player = Some(prompt("Player")?);
+        }
+        if self.deck.is_none() {
+            self.deck = Some(prompt("Deck")?);
+        }
+        if self.won.is_none() {
+            self.won = Some(prompt("Games won")?);
+        }
+        if self.lost.is_none() {
+            self.lost = Some(prompt("Games lost")?);
+        }
+        if self.opp_deck.is_none() && self.pod.is_none() {
+            self.opp_deck = Some(prompt("Opponent's deck")?);
+        }
+        if self.notes.is_none() {
+            self.notes = Some(prompt("Notes")?);
+        }
+        Ok(())
+    }
+
+    /// Builds a new game from these fields, dated today unless a date was given.
+    pub fn into_game(self, aliases: &Aliases) -> Result<GameLog, Box<dyn Error>> {
+        let missing = |field: &str| format!("missing --{}", field);
+        let mut game = GameLog {
+            date: Some(self.date.unwrap_or_else(|| Local::now().date_naive())),
+            player: self.player.clone().ok_or_else(|| missing("player"))?.into(),
+            deck: self.deck.clone().ok_or_else(|| missing("deck"))?.into(),
+            won: self.won.ok_or_else(|| missing("won"))?,
+            lost: self.lost.ok_or_else(|| missing("lost"))?,
+            opp_deck: match (&self.opp_deck, &self.pod) {
+                (Some(opp_deck), _) => opp_deck.clone().into(),
+                (None, Some(_)) => Name::default(),
+                (None, None) => return Err(missing("opp-deck").into()),
+            },
+            notes: self.notes.clone().unwrap_or_default(),
+            ..GameLog::default()
+        };
+        self.apply(&mut game, aliases);
+        Ok(game)
+    }
+}
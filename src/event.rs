@@ -0,0 +1,1486 @@
+use crate::bracket::Bracket;
+use crate::config::{Config, Points};
+use crate::roll::PlayBalance;
+use crate::{audit, crypt, image, pdf, ratings, serve, ColorIdentity, Deck, GameLog};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Subcommand)]
+pub enum EventCommand {
+    /// Start a new event
+    Create {
+        name: String,
+        /// Pair round one by current ratings, top half against bottom half,
+        /// instead of in name order
+        #[arg(long)]
+        seeded: bool,
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+        /// What each player pays to enter, paid out by the configured payouts
+        #[arg(long)]
+        entry_fee: Option<f64>,
+        /// Minutes in a round, instead of the configured round_minutes
+        #[arg(long)]
+        round_minutes: Option<u32>,
+    },
+    /// Lock in the deck a player will play for the whole event; in a draft
+    /// or sealed event, the colors of the deck they built, e.g. Ub
+    Register {
+        event: String,
+        player: String,
+        deck: String,
+        /// ID of the sealed pool the player opened
+        #[arg(long)]
+        pool: Option<String>,
+    },
+    /// Record who sat where in a draft, in passing order
+    Seat { event: String, players: Vec<String> },
+    /// Pair the next Swiss round by points, avoiding rematches; an odd field
+    /// gives the lowest-ranked player without one a bye
+    Pair {
+        event: String,
+        /// Say who is on the play in each match's first game, evening out
+        /// play and draw over the season, for formats that let players choose
+        /// instead of rolling for it
+        #[arg(long)]
+        balance_play: bool,
+    },
+    /// Drop a player from the rest of the event; their results still count
+    Drop { event: String, player: String },
+    /// Record an intentional draw between two players
+    Id {
+        event: String,
+        player: String,
+        opponent: String,
+    },
+    /// Record an infraction and its penalty; a game or match loss tied to a
+    /// game changes that game's result in the standings, and a match loss
+    /// without one counts as a lost match
+    Penalize {
+        event: String,
+        player: String,
+        #[arg(value_enum)]
+        penalty: Penalty,
+        /// ID of the game the penalty was applied to
+        #[arg(long)]
+        game: Option<u32>,
+        /// What happened, e.g. "tardiness"
+        #[arg(long, default_value = "")]
+        reason: String,
+    },
+    /// Cut to a single- or double-elimination playoff of the top players in
+    /// the standings, or show the bracket once it's been made
+    Bracket {
+        event: String,
+        /// Players making the cut, a power of two; 8 if not given
+        #[arg(long)]
+        top: Option<usize>,
+        /// Losing once drops a player to the losers' bracket instead of out
+        #[arg(long)]
+        double: bool,
+        /// Render the bracket as a PNG for sharing instead of printing it
+        #[arg(long)]
+        png: Option<PathBuf>,
+    },
+    /// Record the winner of a playoff match
+    Advance { event: String, winner: String },
+    /// Close the event and pay out its prize pool by the final standings
+    Finish { event: String },
+    /// Time left in the current round and the matches still being played, in
+    /// one event or every running one
+    Status { event: Option<String> },
+    /// Standings and the field by registered deck
+    Report {
+        event: String,
+        /// Render the standings as a PNG for sharing instead of printing the report
+        #[arg(long)]
+        png: Option<PathBuf>,
+        /// Write the latest round's pairings by table and the standings as a
+        /// PDF for printing
+        #[arg(long, conflicts_with = "png")]
+        pdf: Option<PathBuf>,
+        /// Where `protour serve` is reachable from players' phones, e.g.
+        /// http://192.168.1.20:8080; each table in the PDF gets a QR code
+        /// opening its result form
+        #[arg(long, requires = "pdf")]
+        results_url: Option<String>,
+    },
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Constructed,
+    /// Booster draft; players register the colors they drafted, and the
+    /// games are kept out of the constructed stats
+    Draft,
+    /// Sealed deck; like a draft, with each player's pool registered too
+    Sealed,
+}
+
+impl Format {
+    pub fn is_limited(&self) -> bool {
+        *self != Format::Constructed
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Penalty {
+    #[strum(serialize = "warning")]
+    Warning,
+    #[strum(serialize = "game loss")]
+    GameLoss,
+    #[strum(serialize = "match loss")]
+    MatchLoss,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Infraction {
+    pub date: NaiveDate,
+    pub player: String,
+    pub penalty: Penalty,
+    #[serde(default)]
+    pub game: Option<u32>,
+    #[serde(default)]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Event {
+    pub created: NaiveDate,
+    #[serde(default)]
+    pub format: Format,
+    /// Registered deck spec for each player, or drafted colors in a limited event
+    pub registrations: BTreeMap<String, String>,
+    /// Draft seating in passing order
+    #[serde(default)]
+    pub pod: Vec<String>,
+    /// Sealed pool each player opened
+    #[serde(default)]
+    pub pools: BTreeMap<String, String>,
+    #[serde(default)]
+    pub entry_fee: Option<f64>,
+    #[serde(default)]
+    pub round_minutes: Option<u32>,
+    /// Match points, copied from the config when the event was created
+    #[serde(default)]
+    pub points: Points,
+    /// When the event was closed; no more games can be recorded in it
+    #[serde(default)]
+    pub finished: Option<NaiveDate>,
+    /// Prize money each player won, settled when the event finished
+    #[serde(default)]
+    pub payouts: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub rounds: Vec<Round>,
+    /// Pairs of players who agreed to draw instead of playing
+    #[serde(default)]
+    pub intentional_draws: Vec<(String, String)>,
+    /// Players who left the event and aren't paired any more
+    #[serde(default)]
+    pub dropped: BTreeSet<String>,
+    #[serde(default)]
+    pub infractions: Vec<Infraction>,
+    /// The playoff after the Swiss rounds
+    #[serde(default)]
+    pub bracket: Option<Bracket>,
+    #[serde(default)]
+    pub seeded: bool,
+    /// Players and their ratings when round one was paired, best first
+    #[serde(default)]
+    pub seeding: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Round {
+    pub pairings: Vec<(String, String)>,
+    pub bye: Option<String>,
+    /// When the round was paired and its clock started
+    #[serde(default)]
+    pub started: Option<DateTime<Local>>,
+    /// Whether the first player of each pairing was given the play
+    #[serde(default)]
+    pub play_assigned: bool,
+}
+
+// match points for a win and a draw in the match-win percentages used as
+// tiebreakers, whatever the event's own scoring
+const WIN_POINTS: u32 = 3;
+const DRAW_POINTS: u32 = 1;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Standing {
+    /// Match points under the event's scoring
+    points: u32,
+    wins: u32,
+    losses: u32,
+    draws: u32,
+    intentional_draws: u32,
+    byes: u32,
+    /// Average match-win percentage of everyone paired against this player
+    opponents_match_win: f64,
+}
+
+// floor on a match-win percentage when it's used as a tiebreaker, as in the MTR
+const MIN_MATCH_WIN: f64 = 1.0 / 3.0;
+
+// pairings tried while looking for a round without rematches, which in a big
+// pod with few ways round them could otherwise take forever
+const PAIRING_ATTEMPTS: usize = 10_000;
+
+impl Standing {
+    fn match_win(&self) -> f64 {
+        let matches = self.wins + self.losses + self.draws + self.intentional_draws + self.byes;
+        if matches == 0 {
+            return MIN_MATCH_WIN;
+        }
+        let points = WIN_POINTS * (self.wins + self.byes)
+            + DRAW_POINTS * (self.draws + self.intentional_draws);
+        (points as f64 / (WIN_POINTS * matches) as f64).max(MIN_MATCH_WIN)
+    }
+}
+
+/// Every event, stored next to the data file: `data.csv` keeps them in `data.events.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Events(BTreeMap<String, Event>);
+
+impl Events {
+    pub fn path_for(data: &Path) -> PathBuf {
+        crypt::plain_path(data).with_extension("events.json")
+    }
+
+    pub fn load(data: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path_for(data);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    pub fn save(&self, data: &Path) -> Result<(), Box<dyn Error>> {
+        let path = Self::path_for(data);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Event)> {
+        self.0.iter()
+    }
+
+    /// Format of the event the game was played in; games outside events are
+    /// constructed.
+    pub fn format(&self, game: &GameLog) -> Format {
+        game.event
+            .as_ref()
+            .and_then(|name| self.0.get(name))
+            .map_or(Format::Constructed, |event| event.format)
+    }
+
+    /// Whether the game was played in a draft or other limited event.
+    pub fn is_limited(&self, game: &GameLog) -> bool {
+        self.format(game).is_limited()
+    }
+
+    /// Prize money each player won in events that finished between `from` and
+    /// `to`, inclusive.
+    pub fn winnings(&self, from: NaiveDate, to: NaiveDate) -> BTreeMap<&str, f64> {
+        let mut winnings: BTreeMap<&str, f64> = BTreeMap::new();
+        self.0
+            .values()
+            .filter(|event| {
+                event
+                    .finished
+                    .is_some_and(|date| from <= date && date <= to)
+            })
+            .flat_map(|event| &event.payouts)
+            .for_each(|(player, amount)| *winnings.entry(player).or_default() += amount);
+        winnings
+    }
+
+    fn get(&self, name: &str) -> Result<&Event, String> {
+        self.0
+            .get(name)
+            .ok_or_else(|| format!("no event named '{}'", name))
+    }
+
+    fn get_mut(&mut self, name: &str) -> Result<&mut Event, String> {
+        self.0
+            .get_mut(name)
+            .ok_or_else(|| format!("no event named '{}'", name))
+    }
+
+    /// Events that have been paired and haven't finished, which can run side
+    /// by side, e.g. a main event and a side draft.
+    pub fn running(&self) -> impl Iterator<Item = (&String, &Event)> {
+        self.0
+            .iter()
+            .filter(|(_, event)| event.finished.is_none() && !event.rounds.is_empty())
+    }
+
+    /// The running event a game recorded without one belongs to: the only one
+    /// pairing the player this round on the deck they registered.
+    pub fn infer(&self, game: &GameLog) -> Option<&str> {
+        let mut candidates = self.running().filter(|(name, event)| {
+            let paired = event.rounds.last().is_some_and(|round| {
+                round
+                    .pairings
+                    .iter()
+                    .any(|(a, b)| *a == game.player || *b == game.player)
+            });
+            let on_deck = self
+                .validate(&GameLog {
+                    event: Some(name.to_string()),
+                    ..game.clone()
+                })
+                .is_ok();
+            paired && on_deck
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some((name, _)), None) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Games played in an event must be by a registered player on their registered deck.
+    pub fn validate(&self, game: &GameLog) -> Result<(), String> {
+        let Some(name) = &game.event else {
+            return Ok(());
+        };
+        let event = self.get(name)?;
+        if let Some(finished) = event.finished {
+            return Err(format!("{} finished on {}", name, finished));
+        }
+        let registered = event
+            .registrations
+            .get(game.player.as_str())
+            .ok_or_else(|| format!("{} isn't registered for {}", game.player, name))?;
+        // a limited deck is only its colors
+        let same_deck = match (Deck::from_str(registered), Deck::from_str(&game.deck)) {
+            (Ok(registered), Ok(played)) if event.format.is_limited() => registered.0 == played.0,
+            (Ok(registered), Ok(played)) => registered == played,
+            _ => registered.eq_ignore_ascii_case(&game.deck),
+        };
+        if same_deck {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} registered {} for {}, not {}",
+                game.player, registered, name, game.deck
+            ))
+        }
+    }
+}
+
+fn event_games<'a>(games: &'a [GameLog], name: &'a str) -> impl Iterator<Item = &'a GameLog> {
+    games
+        .iter()
+        .filter(move |game| game.event.as_deref() == Some(name))
+}
+
+impl Event {
+    fn played(&self, a: &str, b: &str) -> bool {
+        self.rounds
+            .iter()
+            .flat_map(|round| &round.pairings)
+            .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+    }
+
+    /// Players ordered by match points, best first. Byes and intentional draws
+    /// only live here, so they never reach the matchup stats.
+    fn standings<'a>(&'a self, games: &'a [GameLog], name: &'a str) -> Vec<(&'a str, Standing)> {
+        let mut standings: BTreeMap<&str, Standing> = self
+            .registrations
+            .keys()
+            .map(|player| (player.as_str(), Standing::default()))
+            .collect();
+        event_games(games, name).for_each(|game| {
+            let standing = standings.entry(&game.player).or_default();
+            let (won, lost) = self.result(games, game);
+            standing.points += self.points.for_result(won, lost);
+            if won > lost {
+                standing.wins += 1;
+            } else if won < lost {
+                standing.losses += 1;
+            } else {
+                standing.draws += 1;
+            }
+        });
+        self.infractions
+            .iter()
+            .filter(|infraction| {
+                infraction.penalty == Penalty::MatchLoss && infraction.game.is_none()
+            })
+            .for_each(|infraction| {
+                let standing = standings.entry(&infraction.player).or_default();
+                standing.losses += 1;
+                standing.points += self.points.loss + self.points.participation;
+            });
+        self.rounds
+            .iter()
+            .filter_map(|round| round.bye.as_deref())
+            .for_each(|player| {
+                let standing = standings.entry(player).or_default();
+                standing.byes += 1;
+                standing.points += self.points.win + self.points.participation;
+            });
+        self.intentional_draws
+            .iter()
+            .flat_map(|(a, b)| [a, b])
+            .for_each(|player| {
+                let standing = standings.entry(player).or_default();
+                standing.intentional_draws += 1;
+                standing.points += self.points.draw + self.points.participation;
+            });
+
+        // dropped players' results keep counting toward their opponents' tiebreakers
+        let match_wins: BTreeMap<&str, f64> = standings
+            .iter()
+            .map(|(player, standing)| (*player, standing.match_win()))
+            .collect();
+        standings.iter_mut().for_each(|(player, standing)| {
+            let opponents: Vec<f64> = self
+                .rounds
+                .iter()
+                .flat_map(|round| &round.pairings)
+                .filter_map(|(a, b)| match (a == player, b == player) {
+                    (true, _) => Some(b.as_str()),
+                    (_, true) => Some(a.as_str()),
+                    _ => None,
+                })
+                .map(|opponent| match_wins.get(opponent).copied().unwrap_or(MIN_MATCH_WIN))
+                .collect();
+            if !opponents.is_empty() {
+                standing.opponents_match_win =
+                    opponents.iter().sum::<f64>() / opponents.len() as f64;
+            }
+        });
+
+        let mut standings: Vec<(&str, Standing)> = standings.into_iter().collect();
+        standings.sort_by(|(_, a), (_, b)| {
+            b.points
+                .cmp(&a.points)
+                .then(b.opponents_match_win.total_cmp(&a.opponents_match_win))
+                .then(a.losses.cmp(&b.losses))
+        });
+        standings
+    }
+
+    /// Next round's pairings: the bye goes to the lowest-ranked player who
+    /// hasn't had one, and everyone else is paired down the standings. A
+    /// seeded round one pairs the top half of the seeds against the bottom half.
+    fn pair(&self, games: &[GameLog], name: &str) -> Round {
+        let seeded = self.rounds.is_empty() && !self.seeding.is_empty();
+        let mut order: Vec<&str> = if seeded {
+            self.seeding
+                .iter()
+                .map(|(player, _)| player.as_str())
+                .collect()
+        } else {
+            self.standings(games, name)
+                .into_iter()
+                .map(|(player, _)| player)
+                .collect()
+        };
+        order.retain(|player| !self.dropped.contains(*player));
+        let mut bye = None;
+        if order.len() % 2 == 1 {
+            let had_bye = |player: &str| {
+                self.rounds
+                    .iter()
+                    .any(|round| round.bye.as_deref() == Some(player))
+            };
+            let position = order
+                .iter()
+                .rposition(|player| !had_bye(player))
+                .unwrap_or(order.len() - 1);
+            bye = Some(order.remove(position).to_string());
+        }
+        if seeded {
+            let (top, bottom) = order.split_at(order.len() / 2);
+            let pairings = top
+                .iter()
+                .zip(bottom)
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect();
+            return Round {
+                pairings,
+                bye,
+                started: Some(Local::now()),
+                play_assigned: false,
+            };
+        }
+        // fall back to as few rematches as a pass down the standings finds
+        // when they can't be avoided, or the search for a way round them
+        // runs too long
+        let mut attempts = PAIRING_ATTEMPTS;
+        let pairings = self
+            .pair_without_rematches(&order, &mut attempts)
+            .unwrap_or_else(|| self.pair_fewest_rematches(&order));
+        Round {
+            pairings,
+            bye,
+            started: Some(Local::now()),
+            play_assigned: false,
+        }
+    }
+
+    /// A game's result after the penalties applied to its match: each game
+    /// loss adds a lost game, and a match loss makes it a clean loss. The
+    /// opponent's side of a penalized game gets the mirrored result.
+    fn result(&self, games: &[GameLog], game: &GameLog) -> (u32, u32) {
+        // `other` is the same match recorded for the opponent of `penalized`
+        let mirrors = |penalized: &GameLog, other: &GameLog| {
+            other.opponent.as_ref() == Some(&penalized.player)
+                && penalized
+                    .opponent
+                    .as_ref()
+                    .is_none_or(|opponent| *opponent == other.player)
+                && other.date == penalized.date
+                && other.event == penalized.event
+                && other.deck == penalized.opp_deck
+                && other.opp_deck == penalized.deck
+        };
+        let clean = game.best_of() / 2 + 1;
+        self.infractions
+            .iter()
+            .filter_map(|infraction| {
+                let penalized = games.iter().find(|game| Some(game.id) == infraction.game)?;
+                if penalized.id == game.id {
+                    Some((infraction.penalty, false))
+                } else {
+                    mirrors(penalized, game).then_some((infraction.penalty, true))
+                }
+            })
+            .fold(
+                (game.won, game.lost),
+                |(won, lost), (penalty, mirrored)| match (penalty, mirrored) {
+                    (Penalty::Warning, _) => (won, lost),
+                    (Penalty::GameLoss, false) => (won, lost + 1),
+                    (Penalty::GameLoss, true) => (won + 1, lost),
+                    (Penalty::MatchLoss, false) => (0, clean),
+                    (Penalty::MatchLoss, true) => (clean, 0),
+                },
+            )
+    }
+
+    fn round_length(&self, config: &Config) -> Duration {
+        Duration::minutes(self.round_minutes.unwrap_or(config.round_minutes) as i64)
+    }
+
+    /// Index of the round a result recorded at `at` was played in: the last
+    /// one started before it.
+    fn round_at(&self, at: DateTime<Local>) -> Option<usize> {
+        self.rounds
+            .iter()
+            .rposition(|round| round.started.is_some_and(|started| started <= at))
+    }
+
+    /// Results recorded after their round's time was up, with the round and
+    /// how long after time they came in; those matches went to turns.
+    fn over_time<'a>(
+        &self,
+        games: &'a [GameLog],
+        name: &'a str,
+        recorded: &BTreeMap<u32, DateTime<Local>>,
+        length: Duration,
+    ) -> Vec<(usize, &'a GameLog, Duration)> {
+        event_games(games, name)
+            .filter_map(|game| {
+                let at = *recorded.get(&game.id)?;
+                let round = self.round_at(at)?;
+                let over = at - (self.rounds[round].started? + length);
+                (over > Duration::zero()).then_some((round, game, over))
+            })
+            .collect()
+    }
+
+    /// The prize pool split between the top finishers by `shares`, to the cent.
+    fn payouts(&self, games: &[GameLog], name: &str, shares: &[f64]) -> BTreeMap<String, f64> {
+        let Some(fee) = self.entry_fee else {
+            return BTreeMap::new();
+        };
+        let pool = fee * self.registrations.len() as f64;
+        let total: f64 = shares.iter().sum();
+        if total <= 0.0 {
+            return BTreeMap::new();
+        }
+        self.standings(games, name)
+            .into_iter()
+            .zip(shares)
+            .map(|((player, _), share)| {
+                let amount = (pool * share / total * 100.0).round() / 100.0;
+                (player.to_string(), amount)
+            })
+            .filter(|(_, amount)| *amount > 0.0)
+            .collect()
+    }
+
+    /// Pairs `order` off with no rematches, trying the partners down the
+    /// standings first. Gives up with `None` once no such pairing exists or
+    /// `attempts` runs out, rather than searching every pairing of a big pod.
+    fn pair_without_rematches(
+        &self,
+        order: &[&str],
+        attempts: &mut usize,
+    ) -> Option<Vec<(String, String)>> {
+        let Some((first, rest)) = order.split_first() else {
+            return Some(Vec::new());
+        };
+        rest.iter().enumerate().find_map(|(i, opponent)| {
+            if *attempts == 0 || self.played(first, opponent) {
+                return None;
+            }
+            *attempts -= 1;
+            let remaining: Vec<&str> = rest
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, player)| *player)
+                .collect();
+            let mut pairings = self.pair_without_rematches(&remaining, attempts)?;
+            pairings.insert(0, (first.to_string(), opponent.to_string()));
+            Some(pairings)
+        })
+    }
+
+    /// Pairs `order` off in one pass down the standings, each player meeting
+    /// the highest one left they haven't played, or the next one down when
+    /// they've played everyone left.
+    fn pair_fewest_rematches(&self, order: &[&str]) -> Vec<(String, String)> {
+        let mut left = order.to_vec();
+        let mut pairings = Vec::new();
+        while left.len() >= 2 {
+            let first = left.remove(0);
+            let i = left
+                .iter()
+                .position(|opponent| !self.played(first, opponent))
+                .unwrap_or(0);
+            pairings.push((first.to_string(), left.remove(i).to_string()));
+        }
+        pairings
+    }
+}
+
+fn report(
+    events: &Events,
+    games: &[GameLog],
+    name: &str,
+    png: Option<PathBuf>,
+    pdf: Option<PathBuf>,
+    results_url: Option<String>,
+    over_time: &[(usize, &GameLog, Duration)],
+) -> Result<(), Box<dyn Error>> {
+    let event = events.get(name)?;
+
+    let mut deck_records: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    event_games(games, name).for_each(|game| {
+        let registered = event.registrations.get(game.player.as_str());
+        let deck = deck_records
+            .entry(registered.map_or(game.deck.as_str(), String::as_str))
+            .or_insert((0, 0));
+        if game.won > game.lost {
+            deck.0 += 1;
+        } else if game.won < game.lost {
+            deck.1 += 1;
+        }
+    });
+
+    let standings = event.standings(games, name);
+    let lines: Vec<String> = standings
+        .iter()
+        .enumerate()
+        .map(|(i, (player, standing))| {
+            let deck = event.registrations.get(*player).map_or("?", String::as_str);
+            let mut extras = Vec::new();
+            if standing.byes > 0 {
+                extras.push(format!("{} bye", standing.byes));
+            }
+            if standing.intentional_draws > 0 {
+                extras.push(format!("{} ID", standing.intentional_draws));
+            }
+            if event.dropped.contains(*player) {
+                extras.push("dropped".to_string());
+            }
+            if let Some(amount) = event.payouts.get(*player) {
+                extras.push(format!("won {:.2}", amount));
+            }
+            let extras = if extras.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", extras.join(", "))
+            };
+            format!(
+                "  {}. {} ({}) {} - {} - {}{}, {} points, OMW {:.1}%",
+                i + 1,
+                player,
+                deck,
+                standing.wins,
+                standing.losses,
+                standing.draws,
+                extras,
+                standing.points,
+                standing.opponents_match_win * 100.0
+            )
+        })
+        .collect();
+    if let Some(path) = png {
+        let mut image_lines = vec![format!("{} standings", name)];
+        image_lines.extend(lines);
+        return image::render_text(&image_lines, path);
+    }
+    if let Some(path) = pdf {
+        let mut pages = Vec::new();
+        if let Some(round) = event.rounds.last() {
+            let points: BTreeMap<&str, u32> = standings
+                .iter()
+                .map(|(player, standing)| (*player, standing.points))
+                .collect();
+            let points = |player: &str| points.get(player).copied().unwrap_or(0);
+            let on_play = if round.play_assigned {
+                ", on the play"
+            } else {
+                ""
+            };
+            let mut tables: Vec<(String, Option<String>)> = round
+                .pairings
+                .iter()
+                .enumerate()
+                .map(|(i, (player, opponent))| {
+                    let line = format!(
+                        "Table {:>3}  {} ({}{}) vs. {} ({})",
+                        i + 1,
+                        player,
+                        points(player),
+                        on_play,
+                        opponent,
+                        points(opponent)
+                    );
+                    let deck =
+                        |player: &str| event.registrations.get(player).map_or("", String::as_str);
+                    let link = results_url.as_ref().map(|url| {
+                        serve::form_link(
+                            url,
+                            &[
+                                ("event", name),
+                                ("table", &(i + 1).to_string()),
+                                ("player", player),
+                                ("deck", deck(player)),
+                                ("opponent", opponent),
+                                ("opp_deck", deck(opponent)),
+                            ],
+                        )
+                    });
+                    (line, link)
+                })
+                .collect();
+            if let Some(player) = &round.bye {
+                tables.push((format!("Bye        {} ({})", player, points(player)), None));
+            }
+            let title = format!("{} round {} pairings", name, event.rounds.len());
+            if results_url.is_some() {
+                pages.extend(pdf::coded_pages(&title, &tables)?);
+            } else {
+                let lines: Vec<String> = tables.into_iter().map(|(line, _)| line).collect();
+                pages.extend(pdf::text_pages(&title, &lines));
+            }
+        }
+        pages.extend(pdf::text_pages(&format!("{} standings", name), &lines));
+        return pdf::write(&pages, path);
+    }
+    println!("{} ({})", name, event.created);
+    if let Some(fee) = event.entry_fee {
+        println!(
+            "Entry fee {:.2}, prize pool {:.2}",
+            fee,
+            fee * event.registrations.len() as f64
+        );
+    }
+    if let Some(finished) = event.finished {
+        println!("Finished {}", finished);
+    }
+    if !event.pod.is_empty() {
+        println!("Seating: {}", event.pod.join(", "));
+    }
+    println!("\nStandings:");
+    lines.iter().for_each(|line| println!("{}", line));
+    if !over_time.is_empty() {
+        println!("\nFinished after time (turns):");
+        over_time.iter().for_each(|(round, game, over)| {
+            println!(
+                "  Round {}: {} {} - {}, recorded {} minutes after time",
+                round + 1,
+                game.player,
+                game.won,
+                game.lost,
+                // a match a few seconds over still went to turns
+                over.num_minutes() + 1
+            )
+        });
+    }
+    if !event.infractions.is_empty() {
+        println!("\nInfractions:");
+        event.infractions.iter().for_each(|infraction| {
+            let mut line = format!(
+                "  {} {}: {}",
+                infraction.date, infraction.player, infraction.penalty
+            );
+            if !infraction.reason.is_empty() {
+                line += &format!(" for {}", infraction.reason);
+            }
+            if let Some(id) = infraction.game {
+                line += &format!(", game #{}", id);
+            }
+            println!("{}", line);
+        });
+    }
+
+    let mut field: BTreeMap<&str, u32> = BTreeMap::new();
+    event
+        .registrations
+        .values()
+        .for_each(|deck| *field.entry(deck).or_insert(0) += 1);
+    let mut field: Vec<(&str, u32)> = field.into_iter().collect();
+    field.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let players = event.registrations.len().max(1) as f64;
+    println!("\nField:");
+    field.iter().for_each(|(deck, count)| {
+        let (wins, losses) = deck_records.get(deck).copied().unwrap_or((0, 0));
+        println!(
+            "  {}: {} players ({:.0}%), {} - {}",
+            deck,
+            count,
+            *count as f64 / players * 100.0,
+            wins,
+            losses
+        );
+    });
+    Ok(())
+}
+
+/// The clock and the unreported matches of an event's current round.
+fn status(
+    event: &Event,
+    name: &str,
+    games: &[GameLog],
+    recorded: &BTreeMap<u32, DateTime<Local>>,
+    config: &Config,
+) {
+    let Some(round) = event.rounds.last() else {
+        return;
+    };
+    let number = event.rounds.len();
+    let Some(started) = round.started else {
+        println!("{} round {} has no clock", name, number);
+        return;
+    };
+    let left = started + event.round_length(config) - Local::now();
+    if left > Duration::zero() {
+        println!(
+            "{} round {}: {} minutes left (started {})",
+            name,
+            number,
+            left.num_minutes() + 1,
+            started.format("%H:%M")
+        );
+    } else {
+        println!(
+            "{} round {}: time was called {} minutes ago; unfinished matches go to turns",
+            name,
+            number,
+            -left.num_minutes()
+        );
+    }
+    // anyone with a result since the round started has finished
+    let finished: BTreeSet<&str> = event_games(games, name)
+        .filter(|game| recorded.get(&game.id).is_some_and(|at| *at >= started))
+        .map(|game| game.player.as_str())
+        .collect();
+    let playing: Vec<&(String, String)> = round
+        .pairings
+        .iter()
+        .filter(|(a, b)| !finished.contains(a.as_str()) && !finished.contains(b.as_str()))
+        .collect();
+    if playing.is_empty() {
+        println!("  Every match has reported");
+    } else {
+        playing
+            .iter()
+            .for_each(|(a, b)| println!("  Still playing: {} vs. {}", a, b));
+    }
+}
+
+/// When each game was first recorded, from the audit log.
+fn recorded_at(data: &Path) -> Result<BTreeMap<u32, DateTime<Local>>, Box<dyn Error>> {
+    let mut recorded = BTreeMap::new();
+    audit::read(data)?
+        .iter()
+        .filter(|entry| entry.action == audit::Action::Add)
+        .for_each(|entry| {
+            recorded.entry(entry.id).or_insert(entry.at);
+        });
+    Ok(recorded)
+}
+
+pub fn run(
+    command: EventCommand,
+    data: &Path,
+    games: &[GameLog],
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let aliases = &config.aliases;
+    let mut events = Events::load(data)?;
+    match command {
+        EventCommand::Create {
+            name,
+            seeded,
+            format,
+            entry_fee,
+            round_minutes,
+        } => {
+            if events.0.contains_key(&name) {
+                return Err(format!("event '{}' already exists", name).into());
+            }
+            events.0.insert(
+                name.clone(),
+                Event {
+                    created: Local::now().date_naive(),
+                    format,
+                    registrations: BTreeMap::new(),
+                    pod: Vec::new(),
+                    pools: BTreeMap::new(),
+                    entry_fee,
+                    round_minutes,
+                    points: config.points,
+                    finished: None,
+                    payouts: BTreeMap::new(),
+                    rounds: Vec::new(),
+                    intentional_draws: Vec::new(),
+                    dropped: BTreeSet::new(),
+                    infractions: Vec::new(),
+                    bracket: None,
+                    seeded,
+                    seeding: Vec::new(),
+                },
+            );
+            events.save(data)?;
+            println!("Created event {}", name);
+        }
+        EventCommand::Register {
+            event: name,
+            player,
+            deck,
+            pool,
+        } => {
+            // registration closes once round 1 results start coming in
+            if event_games(games, &name).next().is_some() {
+                return Err(format!("{} has already started, registration is closed", name).into());
+            }
+            let event = events.get_mut(&name)?;
+            let deck = if event.format.is_limited() {
+                ColorIdentity::from_str(&deck).map_err(|_| format!("unknown colors '{}'", deck))?;
+                deck
+            } else {
+                let deck = aliases.deck(&deck);
+                Deck::from_str(&deck).map_err(|_| format!("unknown deck '{}'", deck))?;
+                deck
+            };
+            let player = aliases.player(&player);
+            match (event.format, pool) {
+                (Format::Sealed, Some(pool)) => {
+                    event.pools.insert(player.clone(), pool);
+                }
+                (Format::Sealed, None) => {
+                    return Err(format!("{} is sealed, give the player's --pool", name).into())
+                }
+                (_, Some(_)) => return Err(format!("{} isn't a sealed event", name).into()),
+                (_, None) => {}
+            }
+            event.registrations.insert(player.clone(), deck.clone());
+            events.save(data)?;
+            println!("Registered {} on {} for {}", player, deck, name);
+        }
+        EventCommand::Pair {
+            event: name,
+            balance_play,
+        } => {
+            let event = events.get_mut(&name)?;
+            if event.registrations.len() < 2 {
+                return Err(format!("{} needs at least two registered players", name).into());
+            }
+            if event.seeded && event.rounds.is_empty() {
+                let players: Vec<String> = event.registrations.keys().cloned().collect();
+                event.seeding = ratings::ranked(&ratings::elo(games, &config.ratings), &players);
+                println!("{} seeding:", name);
+                event
+                    .seeding
+                    .iter()
+                    .enumerate()
+                    .for_each(|(i, (player, rating))| {
+                        println!("  {}. {} ({:.0})", i + 1, player, rating)
+                    });
+            }
+            let mut round = event.pair(games, &name);
+            if balance_play {
+                let mut balance = PlayBalance::new(games);
+                round.pairings = round
+                    .pairings
+                    .into_iter()
+                    .map(|(player, opponent)| balance.assign(player, opponent))
+                    .collect();
+                round.play_assigned = true;
+            }
+            println!("{} round {}:", name, event.rounds.len() + 1);
+            let on_play = if round.play_assigned {
+                " (on the play)"
+            } else {
+                ""
+            };
+            round.pairings.iter().for_each(|(player, opponent)| {
+                println!("  {}{} vs. {}", player, on_play, opponent)
+            });
+            if let Some(player) = &round.bye {
+                println!("  {} has a bye", player);
+            }
+            event.rounds.push(round);
+            events.save(data)?;
+        }
+        EventCommand::Seat {
+            event: name,
+            players,
+        } => {
+            let event = events.get_mut(&name)?;
+            if event.format != Format::Draft {
+                return Err(format!("{} isn't a draft", name).into());
+            }
+            let players: Vec<String> = players
+                .iter()
+                .map(|player| aliases.player(player))
+                .collect();
+            if let Some(unregistered) = players
+                .iter()
+                .find(|player| !event.registrations.contains_key(*player))
+            {
+                return Err(format!("{} isn't registered for {}", unregistered, name).into());
+            }
+            println!("{} seating: {}", name, players.join(", "));
+            event.pod = players;
+            events.save(data)?;
+        }
+        EventCommand::Drop {
+            event: name,
+            player,
+        } => {
+            let player = aliases.player(&player);
+            let event = events.get_mut(&name)?;
+            if !event.registrations.contains_key(&player) {
+                return Err(format!("{} isn't registered for {}", player, name).into());
+            }
+            if !event.dropped.insert(player.clone()) {
+                return Err(format!("{} already dropped from {}", player, name).into());
+            }
+            events.save(data)?;
+            println!("Dropped {} from {}", player, name);
+        }
+        EventCommand::Id {
+            event: name,
+            player,
+            opponent,
+        } => {
+            let (player, opponent) = (aliases.player(&player), aliases.player(&opponent));
+            let event = events.get_mut(&name)?;
+            if let Some(unregistered) = [&player, &opponent]
+                .into_iter()
+                .find(|player| !event.registrations.contains_key(*player))
+            {
+                return Err(format!("{} isn't registered for {}", unregistered, name).into());
+            }
+            event
+                .intentional_draws
+                .push((player.clone(), opponent.clone()));
+            events.save(data)?;
+            println!(
+                "Recorded an intentional draw between {} and {}",
+                player, opponent
+            );
+        }
+        EventCommand::Penalize {
+            event: name,
+            player,
+            penalty,
+            game,
+            reason,
+        } => {
+            let player = aliases.player(&player);
+            if let Some(id) = game {
+                event_games(games, &name)
+                    .find(|game| game.id == id && game.player == player)
+                    .ok_or_else(|| {
+                        format!("#{} isn't one of {}'s games in {}", id, player, name)
+                    })?;
+            }
+            let event = events.get_mut(&name)?;
+            if !event.registrations.contains_key(&player) {
+                return Err(format!("{} isn't registered for {}", player, name).into());
+            }
+            event.infractions.push(Infraction {
+                date: Local::now().date_naive(),
+                player: player.clone(),
+                penalty,
+                game,
+                reason,
+            });
+            events.save(data)?;
+            println!("Recorded a {} for {} in {}", penalty, player, name);
+        }
+        EventCommand::Bracket {
+            event: name,
+            top,
+            double,
+            png,
+        } => {
+            let event = events.get_mut(&name)?;
+            if event.bracket.is_none() {
+                let seeds: Vec<String> = event
+                    .standings(games, &name)
+                    .into_iter()
+                    .map(|(player, _)| player.to_string())
+                    .filter(|player| !event.dropped.contains(player))
+                    .take(top.unwrap_or(8))
+                    .collect();
+                event.bracket = Some(Bracket::new(seeds, double)?);
+                events.save(data)?;
+            } else if top.is_some() || double {
+                return Err(format!("{} already has a bracket", name).into());
+            }
+            let lines = events
+                .get(&name)?
+                .bracket
+                .as_ref()
+                .map_or(Vec::new(), Bracket::lines);
+            if let Some(path) = png {
+                let mut image_lines = vec![format!("{} playoff", name)];
+                image_lines.extend(lines);
+                return image::render_text(&image_lines, path);
+            }
+            println!("{} playoff:", name);
+            lines.iter().for_each(|line| println!("  {}", line));
+        }
+        EventCommand::Advance {
+            event: name,
+            winner,
+        } => {
+            let winner = aliases.player(&winner);
+            let bracket = events
+                .get_mut(&name)?
+                .bracket
+                .as_mut()
+                .ok_or_else(|| format!("{} has no bracket yet", name))?;
+            let played = bracket.advance(&winner)?;
+            println!("{} won playoff match {} in {}", winner, played, name);
+            if let Some(champion) = bracket.champion() {
+                println!("{} wins {}", champion, name);
+            }
+            events.save(data)?;
+        }
+        EventCommand::Finish { event: name } => {
+            let event = events.get_mut(&name)?;
+            if let Some(finished) = event.finished {
+                return Err(format!("{} already finished on {}", name, finished).into());
+            }
+            event.payouts = event.payouts(games, &name, &config.payouts);
+            event.finished = Some(Local::now().date_naive());
+            println!("Finished {}", name);
+            event
+                .payouts
+                .iter()
+                .for_each(|(player, amount)| println!("  {} wins {:.2}", player, amount));
+            events.save(data)?;
+        }
+        EventCommand::Status { event: None } => {
+            let running: Vec<&String> = events.running().map(|(name, _)| name).collect();
+            if running.is_empty() {
+                println!("No events are running");
+            }
+            let recorded = recorded_at(data)?;
+            for name in running {
+                status(events.get(name)?, name, games, &recorded, config);
+            }
+        }
+        EventCommand::Status { event: Some(name) } => {
+            let event = events.get(&name)?;
+            if event.rounds.is_empty() {
+                return Err(format!("{} hasn't been paired yet", name).into());
+            }
+            status(event, &name, games, &recorded_at(data)?, config);
+        }
+        EventCommand::Report {
+            event,
+            png,
+            pdf,
+            results_url,
+        } => {
+            let length = events.get(&event)?.round_length(config);
+            let recorded = recorded_at(data)?;
+            let over_time = events
+                .get(&event)?
+                .over_time(games, &event, &recorded, length);
+            report(&events, games, &event, png, pdf, results_url, &over_time)?
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    fn event(players: &[&str]) -> Event {
+        Event {
+            created: NaiveDate::from_ymd_opt(2026, 10, 13).unwrap(),
+            format: Format::default(),
+            registrations: players
+                .iter()
+                .map(|player| (player.to_string(), "Rb Aggro".to_string()))
+                .collect(),
+            pod: Vec::new(),
+            pools: BTreeMap::new(),
+            entry_fee: None,
+            round_minutes: None,
+            points: Points::default(),
+            finished: None,
+            payouts: BTreeMap::new(),
+            rounds: Vec::new(),
+            intentional_draws: Vec::new(),
+            dropped: BTreeSet::new(),
+            infractions: Vec::new(),
+            bracket: None,
+            seeded: false,
+            seeding: Vec::new(),
+        }
+    }
+
+    fn round(pairings: &[(&str, &str)]) -> Round {
+        Round {
+            pairings: pairings
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// `winner` beating `loser` 2 - 0 at FNM, recorded for both of them; the
+    /// loser's side is numbered `id + 100`.
+    fn beat(id: u32, winner: &str, loser: &str) -> [GameLog; 2] {
+        let game = GameLog {
+            player: winner.into(),
+            won: 2,
+            lost: 0,
+            opp_deck: "Rb Aggro".into(),
+            opponent: Some(loser.into()),
+            event: Some("FNM".to_string()),
+            ..test_game(id)
+        };
+        let other = GameLog {
+            id: id + 100,
+            ..game.complement().unwrap()
+        };
+        [game, other]
+    }
+
+    /// Two rounds after which Bea and Cal are both 1 - 1, Bea having played
+    /// the stronger opponents.
+    fn two_rounds() -> (Event, Vec<GameLog>) {
+        let mut event = event(&["Ana", "Bea", "Cal", "Dan"]);
+        event.rounds = vec![
+            round(&[("Ana", "Bea"), ("Cal", "Dan")]),
+            round(&[("Ana", "Dan"), ("Bea", "Cal")]),
+        ];
+        let games = [
+            beat(1, "Ana", "Bea"),
+            beat(2, "Cal", "Dan"),
+            beat(3, "Ana", "Dan"),
+            beat(4, "Bea", "Cal"),
+        ]
+        .concat();
+        (event, games)
+    }
+
+    #[test]
+    fn ties_on_points_go_to_the_stronger_opponents() {
+        let (event, games) = two_rounds();
+        let standings = event.standings(&games, "FNM");
+        let order: Vec<&str> = standings.iter().map(|(player, _)| *player).collect();
+        assert_eq!(order, ["Ana", "Bea", "Cal", "Dan"]);
+        let standing = |player| standings.iter().find(|(p, _)| *p == player).unwrap().1;
+        assert_eq!(standing("Ana").points, 6);
+        // Ana won everything and Cal half; Dan's 0% counts as a third
+        assert!((standing("Bea").opponents_match_win - 0.75).abs() < 1e-9);
+        assert!((standing("Cal").opponents_match_win - (1.0 / 3.0 + 0.5) / 2.0).abs() < 1e-9);
+        assert!((standing("Dan").match_win() - MIN_MATCH_WIN).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_penalty_applies_to_both_sides_of_the_match() {
+        let (mut event, games) = two_rounds();
+        let penalize = |player: &str, penalty, game| Infraction {
+            date: event.created,
+            player: player.to_string(),
+            penalty,
+            game: Some(game),
+            reason: String::new(),
+        };
+        event.infractions = vec![
+            penalize("Ana", Penalty::MatchLoss, 3),
+            penalize("Cal", Penalty::GameLoss, 2),
+        ];
+        let row = |id| games.iter().find(|game| game.id == id).unwrap();
+        // Ana's win over Dan becomes Dan's, on both their rows
+        assert_eq!(event.result(&games, row(3)), (0, 2));
+        assert_eq!(event.result(&games, row(103)), (2, 0));
+        // Cal still beats Dan, a game closer
+        assert_eq!(event.result(&games, row(2)), (2, 1));
+        assert_eq!(event.result(&games, row(102)), (1, 2));
+
+        let standings = event.standings(&games, "FNM");
+        let standing = |player| standings.iter().find(|(p, _)| *p == player).unwrap().1;
+        assert_eq!((standing("Ana").wins, standing("Ana").losses), (1, 1));
+        assert_eq!((standing("Dan").wins, standing("Dan").losses), (1, 1));
+        let (wins, losses) = standings.iter().fold((0, 0), |(wins, losses), (_, s)| {
+            (wins + s.wins, losses + s.losses)
+        });
+        assert_eq!(wins, losses);
+    }
+
+    #[test]
+    fn pairs_down_the_standings_without_rematches() {
+        let (event, games) = two_rounds();
+        let round = event.pair(&games, "FNM");
+        // Ana has played Bea and Dan already, so meets Cal
+        assert_eq!(
+            round.pairings,
+            [
+                ("Ana".to_string(), "Cal".to_string()),
+                ("Bea".to_string(), "Dan".to_string())
+            ]
+        );
+        assert!(round.bye.is_none());
+    }
+
+    #[test]
+    fn the_bye_goes_to_the_lowest_player_without_one() {
+        let mut event = event(&["Ana", "Bea", "Cal", "Dan", "Eve"]);
+        assert_eq!(event.pair(&[], "FNM").bye.as_deref(), Some("Eve"));
+        event.rounds = vec![Round {
+            bye: Some("Eve".to_string()),
+            ..round(&[("Ana", "Bea"), ("Cal", "Dan")])
+        }];
+        let games = [beat(1, "Ana", "Bea"), beat(2, "Cal", "Dan")].concat();
+        // the bye put Eve level with the winners, leaving Dan at the bottom
+        assert_eq!(event.pair(&games, "FNM").bye.as_deref(), Some("Dan"));
+    }
+
+    #[test]
+    fn dropped_players_sit_out_but_still_count_for_their_opponents() {
+        let (mut event, games) = two_rounds();
+        let tiebreak = |event: &Event| {
+            event
+                .standings(&games, "FNM")
+                .into_iter()
+                .find(|(player, _)| *player == "Cal")
+                .unwrap()
+                .1
+                .opponents_match_win
+        };
+        let before = tiebreak(&event);
+        event.dropped.insert("Dan".to_string());
+        assert_eq!(tiebreak(&event), before);
+        let round = event.pair(&games, "FNM");
+        assert_eq!(round.bye.as_deref(), Some("Cal"));
+        assert!(round.pairings.iter().all(|(a, b)| a != "Dan" && b != "Dan"));
+    }
+
+    #[test]
+    fn gives_up_on_avoiding_rematches_in_a_big_pod() {
+        let mut players: Vec<String> = (1..20).map(|i| format!("P{:02}", i)).collect();
+        players.push("Zed".to_string());
+        let players: Vec<&str> = players.iter().map(String::as_str).collect();
+        let mut event = event(&players);
+        // Zed has played everyone, which only turns up at the bottom of the
+        // standings after every way of pairing the rest has been tried
+        event.rounds = players[..19]
+            .iter()
+            .map(|player| round(&[(player, "Zed")]))
+            .collect();
+        let round = event.pair(&[], "FNM");
+        assert_eq!(round.pairings.len(), 10);
+        let mut paired: Vec<&str> = round
+            .pairings
+            .iter()
+            .flat_map(|(a, b)| [a.as_str(), b.as_str()])
+            .collect();
+        paired.sort();
+        assert_eq!(paired, players);
+        let rematches = round
+            .pairings
+            .iter()
+            .filter(|(a, b)| event.played(a, b))
+            .count();
+        assert_eq!(rematches, 1);
+    }
+
+    #[test]
+    fn seeded_round_one_pairs_the_top_half_against_the_bottom() {
+        let mut event = event(&["Ana", "Bea", "Cal", "Dan"]);
+        event.seeding = ["Dan", "Cal", "Bea", "Ana"]
+            .iter()
+            .zip([1700.0, 1600.0, 1500.0, 1400.0])
+            .map(|(player, rating)| (player.to_string(), rating))
+            .collect();
+        assert_eq!(
+            event.pair(&[], "FNM").pairings,
+            [
+                ("Dan".to_string(), "Bea".to_string()),
+                ("Cal".to_string(), "Ana".to_string())
+            ]
+        );
+    }
+}
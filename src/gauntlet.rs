@@ -0,0 +1,229 @@
+//! Testing gauntlets: the benchmark decks a deck is being tested against and
+//! the sessions played against each. Kept in their own file so practice games
+//! never reach the league's stats.
+
+use crate::config::Aliases;
+use crate::{crypt, Deck};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const DEFAULT_GAMES: u32 = 20;
+
+#[derive(Debug, Subcommand)]
+pub enum GauntletCommand {
+    /// Add benchmark decks to the gauntlet
+    Add {
+        opponents: Vec<String>,
+        /// Games to play against each benchmark deck before the testing is
+        /// done; 20 if the gauntlet doesn't have a goal yet
+        #[arg(long)]
+        games: Option<u32>,
+    },
+    /// Take a benchmark deck out of the gauntlet; its sessions are kept
+    Remove { opponent: String },
+    /// Record a testing session against a benchmark deck
+    Log {
+        opponent: String,
+        won: u32,
+        lost: u32,
+        #[arg(long, default_value = "")]
+        notes: String,
+    },
+    /// Progress and win rate against each benchmark deck
+    Report,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub date: NaiveDate,
+    pub opponent: String,
+    pub won: u32,
+    pub lost: u32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Gauntlet {
+    pub opponents: Vec<String>,
+    /// Games to play against each opponent
+    pub games: u32,
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+}
+
+impl Default for Gauntlet {
+    fn default() -> Self {
+        Self {
+            opponents: Vec::new(),
+            games: DEFAULT_GAMES,
+            sessions: Vec::new(),
+        }
+    }
+}
+
+/// Each tested deck's gauntlet.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Gauntlets(BTreeMap<String, Gauntlet>);
+
+impl Gauntlets {
+    pub fn path_for(data: &Path) -> PathBuf {
+        crypt::plain_path(data).with_extension("gauntlets.json")
+    }
+
+    pub fn load(data: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path_for(data);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, data: &Path) -> Result<(), Box<dyn Error>> {
+        let path = Self::path_for(data);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// A deck spec with aliases resolved, in the data's `Rb Midrange` spelling.
+fn deck(spec: &str, aliases: &Aliases) -> Result<String, Box<dyn Error>> {
+    let spec = aliases.deck(spec);
+    let deck = Deck::from_str(&spec).map_err(|_| format!("unknown deck '{}'", spec))?;
+    Ok(deck.to_string())
+}
+
+fn report(name: &str, gauntlet: &Gauntlet) {
+    println!(
+        "{} gauntlet, {} games against each deck:",
+        name, gauntlet.games
+    );
+    let mut records: BTreeMap<&str, (u32, u32, usize)> = BTreeMap::new();
+    gauntlet.sessions.iter().for_each(|session| {
+        let record = records.entry(&session.opponent).or_default();
+        record.0 += session.won;
+        record.1 += session.lost;
+        record.2 += 1;
+    });
+    let (mut won, mut lost) = (0, 0);
+    gauntlet.opponents.iter().for_each(|opponent| {
+        let (w, l, sessions) = records.get(opponent.as_str()).copied().unwrap_or_default();
+        won += w;
+        lost += l;
+        let rate = if w + l == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.1}%", w as f64 / (w + l) as f64 * 100.0)
+        };
+        let done = if w + l >= gauntlet.games {
+            ", done"
+        } else {
+            ""
+        };
+        println!(
+            "  {}: {} - {} ({}) in {} sessions, {}/{} games{}",
+            opponent,
+            w,
+            l,
+            rate,
+            sessions,
+            w + l,
+            gauntlet.games,
+            done
+        );
+    });
+    if won + lost > 0 {
+        println!(
+            "Overall: {} - {} ({:.1}%)",
+            won,
+            lost,
+            won as f64 / (won + lost) as f64 * 100.0
+        );
+    }
+}
+
+pub fn run(
+    name: &str,
+    command: Option<GauntletCommand>,
+    data: &Path,
+    aliases: &Aliases,
+) -> Result<(), Box<dyn Error>> {
+    let name = deck(name, aliases)?;
+    let mut gauntlets = Gauntlets::load(data)?;
+    match command.unwrap_or(GauntletCommand::Report) {
+        GauntletCommand::Add { opponents, games } => {
+            let gauntlet = gauntlets.0.entry(name.clone()).or_default();
+            if let Some(games) = games {
+                gauntlet.games = games;
+            }
+            for opponent in &opponents {
+                let opponent = deck(opponent, aliases)?;
+                if gauntlet.opponents.contains(&opponent) {
+                    println!("{} is already in the gauntlet", opponent);
+                } else {
+                    println!("Added {} to the {} gauntlet", opponent, name);
+                    gauntlet.opponents.push(opponent);
+                }
+            }
+            gauntlets.save(data)?;
+        }
+        GauntletCommand::Remove { opponent } => {
+            let opponent = deck(&opponent, aliases)?;
+            let gauntlet = gauntlets
+                .0
+                .get_mut(&name)
+                .ok_or_else(|| format!("{} has no gauntlet", name))?;
+            let before = gauntlet.opponents.len();
+            gauntlet.opponents.retain(|deck| *deck != opponent);
+            if gauntlet.opponents.len() == before {
+                return Err(format!("{} isn't in the {} gauntlet", opponent, name).into());
+            }
+            gauntlets.save(data)?;
+            println!("Removed {} from the {} gauntlet", opponent, name);
+        }
+        GauntletCommand::Log {
+            opponent,
+            won,
+            lost,
+            notes,
+        } => {
+            let opponent = deck(&opponent, aliases)?;
+            let gauntlet = gauntlets
+                .0
+                .get_mut(&name)
+                .ok_or_else(|| format!("{} has no gauntlet", name))?;
+            if !gauntlet.opponents.contains(&opponent) {
+                return Err(format!("{} isn't in the {} gauntlet", opponent, name).into());
+            }
+            gauntlet.sessions.push(Session {
+                date: Local::now().date_naive(),
+                opponent: opponent.clone(),
+                won,
+                lost,
+                notes,
+            });
+            gauntlets.save(data)?;
+            println!(
+                "Logged {} - {} against {} for {}",
+                won, lost, opponent, name
+            );
+        }
+        GauntletCommand::Report => {
+            let gauntlet = gauntlets
+                .0
+                .get(&name)
+                .ok_or_else(|| format!("{} has no gauntlet", name))?;
+            report(&name, gauntlet);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! The HTTP client every integration fetches through. Requests to each host
+//! are spaced out to stay within its rate limit, and ones that fail for
+//! reasons that pass (timeouts, dropped connections, 429s, and 5xx
+//! responses) are retried with exponential backoff. Requests that mustn't be
+//! made twice are only retried when they never reached the server.
+
+use reqwest::{Method, StatusCode};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+// tries per request, the first included
+const ATTEMPTS: u32 = 4;
+// wait before the first retry, doubled for each one after
+const BACKOFF: Duration = Duration::from_millis(500);
+// longest a Retry-After header is waited for
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+// spacing between requests to hosts without a limit of their own
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The least time between two requests to a host.
+fn interval(host: &str) -> Duration {
+    match host {
+        // Scryfall asks for 50-100ms between requests
+        "api.scryfall.com" => Duration::from_millis(100),
+        _ => DEFAULT_INTERVAL,
+    }
+}
+
+pub struct Client {
+    client: reqwest::Client,
+    /// When each host may next be sent a request
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl Client {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("protour/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        Ok(Self {
+            client,
+            next: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Waits for the host's turn, and takes it.
+    async fn wait_turn(&self, url: &reqwest::Url) {
+        let host = url.host_str().unwrap_or_default();
+        let turn = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let turn = next.get(host).map_or(now, |at| (*at).max(now));
+            next.insert(host.to_string(), turn + interval(host));
+            turn
+        };
+        tokio::time::sleep_until(turn.into()).await;
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&str>,
+        token: Option<&str>,
+        // whether the request can be sent again after it may have reached the server
+        resend: bool,
+    ) -> Result<Vec<u8>, String> {
+        let url = reqwest::Url::parse(url).map_err(|err| format!("{}: {}", url, err))?;
+        let mut attempt = 1;
+        loop {
+            self.wait_turn(&url).await;
+            let mut request = self
+                .client
+                .request(method.clone(), url.clone())
+                .header("Accept", "application/json, text/csv, */*");
+            if let Some(body) = body {
+                request = request
+                    .header("Content-Type", "application/json")
+                    .body(body.to_string());
+            }
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            let (failure, wait) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    return match response.bytes().await {
+                        Ok(body) => Ok(body.to_vec()),
+                        Err(err) => Err(err.to_string()),
+                    };
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.parse().ok())
+                        .map(|seconds| Duration::from_secs(seconds).min(MAX_RETRY_AFTER));
+                    let passing =
+                        status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                    if !(passing && resend) {
+                        // APIs like serve mode's say what was wrong as `{"error": ...}`
+                        let reason = response
+                            .bytes()
+                            .await
+                            .ok()
+                            .and_then(|body| {
+                                serde_json::from_slice::<serde_json::Value>(&body).ok()
+                            })
+                            .and_then(|body| Some(body.get("error")?.as_str()?.to_string()));
+                        return Err(match reason {
+                            Some(reason) => format!("{} from {}: {}", status, url, reason),
+                            None => format!("{} from {}", status, url),
+                        });
+                    }
+                    (status.to_string(), retry_after)
+                }
+                // a failed connection never sent anything, so can always be retried
+                Err(err)
+                    if err.is_connect() || (resend && (err.is_timeout() || err.is_request())) =>
+                {
+                    (err.to_string(), None)
+                }
+                Err(err) => return Err(err.to_string()),
+            };
+            if attempt == ATTEMPTS {
+                return Err(format!("{} after {} tries: {}", url, ATTEMPTS, failure));
+            }
+            let wait = wait.unwrap_or(BACKOFF * 2u32.pow(attempt - 1));
+            debug!("retrying {} in {:.1?}: {}", url, wait, failure);
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    pub async fn get(&self, url: &str) -> Result<Vec<u8>, String> {
+        self.send(Method::GET, url, None, None, true).await
+    }
+
+    /// POSTs `body` as JSON.
+    pub async fn post_json(&self, url: &str, body: &str) -> Result<Vec<u8>, String> {
+        self.send(Method::POST, url, Some(body), None, true).await
+    }
+
+    /// POSTs `body` as JSON, with `token` as a bearer token, for a request
+    /// that changes something on the server: it's never resent once it may
+    /// have got there, so it can't happen twice.
+    pub async fn post_json_with_token(
+        &self,
+        url: &str,
+        body: &str,
+        token: &str,
+    ) -> Result<Vec<u8>, String> {
+        self.send(Method::POST, url, Some(body), Some(token), false)
+            .await
+    }
+}
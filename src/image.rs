@@ -0,0 +1,223 @@
+//! Renders plain text reports and line charts as PNG images sized for phones,
+//! using the public-domain 8x8 bitmap glyphs from `font8x8`.
+
+use chrono::NaiveDate;
+use font8x8::{UnicodeFonts, BASIC_FONTS};
+use png::{BitDepth, ColorType, Encoder};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const BACKGROUND: [u8; 3] = [0x2b, 0x2d, 0x31];
+const TEXT: [u8; 3] = [0xdb, 0xde, 0xe1];
+const TITLE: [u8; 3] = [0xff, 0xc8, 0x57];
+// images wider than this get shrunk on most phone screens anyway
+const TARGET_WIDTH: usize = 1080;
+const PADDING_CHARS: usize = 2;
+const LINE_SPACING: usize = 4;
+const AXIS: [u8; 3] = [0x72, 0x76, 0x7d];
+// one color per charted series, repeating after the last
+const SERIES: [[u8; 3]; 6] = [
+    [0x57, 0xa8, 0xff],
+    [0xff, 0x6b, 0x6b],
+    [0x6b, 0xd9, 0x8a],
+    [0xff, 0xc8, 0x57],
+    [0xc5, 0x8a, 0xf9],
+    [0x4e, 0xd6, 0xd6],
+];
+const CHART_HEIGHT: usize = 720;
+const CHART_SCALE: usize = 2;
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: BACKGROUND.repeat(width * height),
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, size: usize, color: [u8; 3]) {
+        for dy in 0..size {
+            for dx in 0..size {
+                let offset = ((y + dy) * self.width + x + dx) * 3;
+                self.pixels[offset..offset + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn write(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let mut encoder = Encoder::new(
+            BufWriter::new(File::create(path)?),
+            self.width as u32,
+            self.height as u32,
+        );
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    /// A line two pixels thick from one point to another, clipped to the canvas.
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), color: [u8; 3]) {
+        let steps = (to.0 - from.0)
+            .abs()
+            .max((to.1 - from.1).abs())
+            .ceil()
+            .max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            let x = (from.0 + (to.0 - from.0) * t).round() as usize;
+            let y = (from.1 + (to.1 - from.1) * t).round() as usize;
+            if x + 2 <= self.width && y + 2 <= self.height {
+                self.fill(x, y, 2, color);
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str, x: usize, y: usize, scale: usize, color: [u8; 3]) {
+        for (i, c) in text.chars().enumerate() {
+            self.glyph(c, x + i * 8 * scale, y, scale, color);
+        }
+    }
+
+    fn glyph(&mut self, c: char, x: usize, y: usize, scale: usize, color: [u8; 3]) {
+        let Some(rows) = BASIC_FONTS.get(c).or_else(|| BASIC_FONTS.get('?')) else {
+            return;
+        };
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..8 {
+                if bits & (1 << col) != 0 {
+                    self.fill(x + col * scale, y + row * scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// Draws `lines` in a monospace grid, highlighting the first line as a title.
+pub fn render_text(lines: &[String], path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2 * PADDING_CHARS;
+    let scale = (TARGET_WIDTH / (columns * 8)).clamp(2, 4);
+    let cell = 8 * scale;
+    let row_height = cell + LINE_SPACING * scale;
+    let width = columns * cell;
+    let height = (lines.len() + 2) * row_height;
+
+    let mut canvas = Canvas::new(width, height);
+    for (i, line) in lines.iter().enumerate() {
+        let color = if i == 0 { TITLE } else { TEXT };
+        let y = (i + 1) * row_height;
+        for (j, c) in line.chars().enumerate() {
+            canvas.glyph(c, (j + PADDING_CHARS) * cell, y, scale, color);
+        }
+    }
+
+    canvas.write(path)
+}
+
+/// Axis range with some headroom, rounded out to whole `step`s.
+fn padded_range(min: f64, max: f64, step: f64) -> (f64, f64) {
+    let low = (min / step).floor() * step;
+    let high = (max / step).ceil() * step;
+    if high > low {
+        (low, high)
+    } else {
+        (low - step, high + step)
+    }
+}
+
+/// Draws each named series as a line over dates, with a legend under the title.
+pub fn render_chart(
+    title: &str,
+    series: &[(String, Vec<(NaiveDate, f64)>)],
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let points = || series.iter().flat_map(|(_, points)| points);
+    let (Some(first), Some(last)) = (
+        points().map(|(date, _)| *date).min(),
+        points().map(|(date, _)| *date).max(),
+    ) else {
+        return Err("nothing to chart".into());
+    };
+    let values = || points().map(|(_, value)| *value);
+    let (low, high) = padded_range(
+        values().fold(f64::MAX, f64::min),
+        values().fold(f64::MIN, f64::max),
+        50.0,
+    );
+
+    let cell = 8 * CHART_SCALE;
+    let (width, height) = (TARGET_WIDTH, CHART_HEIGHT);
+    let mut canvas = Canvas::new(width, height);
+    canvas.text(title, cell, cell, CHART_SCALE, TITLE);
+    let mut x = cell;
+    for (i, (name, _)) in series.iter().enumerate() {
+        canvas.text(name, x, 3 * cell, CHART_SCALE, SERIES[i % SERIES.len()]);
+        x += (name.chars().count() + 2) * cell;
+    }
+
+    // plot area, leaving room for the labels on the left and bottom
+    let (left, right) = (6.0 * cell as f64, (width - 2 * cell) as f64);
+    let (top, bottom) = (5.0 * cell as f64, (height - 3 * cell) as f64);
+    let days = (last - first).num_days().max(1) as f64;
+    let to_x = |date: NaiveDate| left + (date - first).num_days() as f64 / days * (right - left);
+    let to_y = |value: f64| bottom - (value - low) / (high - low) * (bottom - top);
+
+    canvas.line((left, top), (left, bottom), AXIS);
+    canvas.line((left, bottom), (right, bottom), AXIS);
+    let ticks = 4;
+    for tick in 0..=ticks {
+        let value = low + (high - low) * tick as f64 / ticks as f64;
+        let y = to_y(value);
+        canvas.line((left - cell as f64 / 2.0, y), (left, y), AXIS);
+        canvas.text(
+            &format!("{:>5.0}", value),
+            0,
+            (y as usize).saturating_sub(cell / 2),
+            CHART_SCALE,
+            TEXT,
+        );
+    }
+    let label_y = height - 2 * cell;
+    canvas.text(
+        &first.to_string(),
+        left as usize,
+        label_y,
+        CHART_SCALE,
+        TEXT,
+    );
+    let last_label = last.to_string();
+    canvas.text(
+        &last_label,
+        right as usize - last_label.len() * cell,
+        label_y,
+        CHART_SCALE,
+        TEXT,
+    );
+
+    for (i, (_, points)) in series.iter().enumerate() {
+        let color = SERIES[i % SERIES.len()];
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            canvas.line((to_x(a.0), to_y(a.1)), (to_x(b.0), to_y(b.1)), color);
+        }
+        if let [(date, value)] = points[..] {
+            canvas.fill(to_x(date) as usize, to_y(value) as usize, 4, color);
+        }
+    }
+
+    canvas.write(path)
+}
@@ -0,0 +1,196 @@
+use crate::conflicts::{self, Report};
+use crate::event::Events;
+use crate::{progress, store, Deck, GameLog};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::warn;
+
+pub struct ImportPlan {
+    pub source: String,
+    pub added: Vec<GameLog>,
+    pub duplicates: Vec<GameLog>,
+    /// Source line and reason for every row that failed validation
+    pub invalid: Vec<(u64, String)>,
+}
+
+pub fn validate(game: &GameLog, roster: &[String], events: &Events) -> Result<(), String> {
+    if !roster.iter().any(|player| *player == game.player) {
+        return Err(format!("player '{}' is not on the roster", game.player));
+    }
+    Deck::from_str(&game.deck).map_err(|_| format!("unknown deck '{}'", game.deck))?;
+    // a pod seat had several opponents, so its opposing deck is optional
+    if game.pod.is_none() || !game.opp_deck.trim().is_empty() {
+        Deck::from_str(&game.opp_deck).map_err(|_| format!("unknown deck '{}'", game.opp_deck))?;
+    }
+    if let Some(opponent) = &game.opponent {
+        if !roster.iter().any(|player| *player == *opponent) {
+            return Err(format!("opponent '{}' is not on the roster", opponent));
+        }
+        if *opponent == game.player {
+            return Err(format!("{} can't play themselves", opponent));
+        }
+        if game.pod.is_some() {
+            return Err("a pod seat's opponents are the other seats".to_string());
+        }
+    }
+    if game.team.is_some() && game.pod.is_none() {
+        return Err("a team game has to be recorded as a pod".to_string());
+    }
+    if game.won + game.lost == 0 {
+        return Err("no games were played".to_string());
+    }
+    let best_of = game.best_of();
+    if best_of.is_multiple_of(2) {
+        return Err(format!("best of {} can't be decided", best_of));
+    }
+    // the match ends as soon as someone has won a majority of the games
+    let needed = best_of / 2 + 1;
+    if game.won > needed || game.lost > needed || game.won + game.lost > best_of {
+        return Err(format!(
+            "{} - {} isn't a best of {} result",
+            game.won, game.lost, best_of
+        ));
+    }
+    events.validate(game)
+}
+
+/// Where an imported row came from, for `GameLog::source`: the importer, then
+/// the file and, when the source has them, the line.
+pub fn provenance(importer: &str, source: &Path, line: u64) -> String {
+    if line == 0 {
+        format!("{} {}", importer, source.display())
+    } else {
+        format!("{} {}:{}", importer, source.display(), line)
+    }
+}
+
+/// Sorts `rows` read from `source` by `importer` into games to add,
+/// duplicates, and invalid rows. A row naming its opponent brings the
+/// opponent's side of the match along with it.
+///
+/// Identical rows are legitimate (the same matchup and result with no date or
+/// notes), so a row only counts as a duplicate while the existing data still
+/// has an unmatched copy of it. A row the other player already reported counts
+/// as a duplicate too if the reports agree; one that contradicts their report
+/// is added with a warning, to be sorted out through `conflicts`.
+pub fn plan(
+    existing: &[GameLog],
+    importer: &str,
+    source: &Path,
+    rows: Vec<store::Row>,
+    roster: &[String],
+    events: &Events,
+) -> ImportPlan {
+    let mut unmatched: BTreeMap<_, u32> = BTreeMap::new();
+    existing
+        .iter()
+        .for_each(|game| *unmatched.entry(game.key()).or_insert(0) += 1);
+    let mut days: BTreeMap<(NaiveDate, &str), Vec<&GameLog>> = BTreeMap::new();
+    existing.iter().for_each(|game| {
+        if let Some(date) = game.date {
+            days.entry((date, &game.player)).or_default().push(game);
+        }
+    });
+    let paired = conflicts::paired(existing);
+    let reported = |game: &GameLog| {
+        let day = days.get(&(game.date?, game.player.as_str()))?;
+        let (report, existing) = day
+            .iter()
+            .filter(|existing| !paired.contains(&existing.id))
+            .find_map(|existing| Some((conflicts::compare(existing, game)?, *existing)))?;
+        if report == Report::Contradicts {
+            warn!(
+                line = game.line,
+                "{} reported this match as {} - {} in game #{}; see `protour conflicts`",
+                existing.reporter(),
+                existing.won,
+                existing.lost,
+                existing.id
+            );
+        }
+        Some((report, existing))
+    };
+
+    let mut plan = ImportPlan {
+        source: source.display().to_string(),
+        added: Vec::new(),
+        duplicates: Vec::new(),
+        invalid: Vec::new(),
+    };
+    let bar = progress::rows(rows.len() as u64, "Checking");
+    for row in rows {
+        bar.inc(1);
+        let game = match row {
+            Ok(game) => game,
+            Err(invalid) => {
+                plan.invalid.push(invalid);
+                progress::errors(&bar, plan.invalid.len());
+                continue;
+            }
+        };
+        let complement = game.complement();
+        let checked = validate(&game, roster, events).and_then(|()| match &complement {
+            Some(complement) => validate(complement, roster, events)
+                .map_err(|reason| format!("the opponent's side: {}", reason)),
+            None => Ok(()),
+        });
+        if let Err(reason) = checked {
+            plan.invalid.push((game.line, reason));
+            progress::errors(&bar, plan.invalid.len());
+            continue;
+        }
+        for game in [Some(game), complement].into_iter().flatten() {
+            match unmatched.get_mut(&game.key()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    plan.duplicates.push(game);
+                }
+                _ if reported(&game).is_some_and(|(report, _)| report == Report::Agrees) => {
+                    plan.duplicates.push(game)
+                }
+                // ids are the source file's own; the game is numbered afresh
+                // when added, and a row another protour already traced keeps
+                // its source
+                _ => plan.added.push(GameLog {
+                    id: 0,
+                    source: game
+                        .source
+                        .clone()
+                        .or_else(|| Some(provenance(importer, source, game.line))),
+                    ..game
+                }),
+            }
+        }
+    }
+    plan
+}
+
+impl ImportPlan {
+    pub fn print(&self, dry_run: bool) {
+        let verb = if dry_run { "Would add" } else { "Added" };
+        println!("{} {} games from {}", verb, self.added.len(), self.source);
+        self.added.iter().for_each(|game| {
+            println!(
+                "  #{} (line {}): {} ({}) {} - {} {}",
+                game.id, game.line, game.player, game.deck, game.won, game.lost, game.opp_deck
+            )
+        });
+        if !self.duplicates.is_empty() {
+            println!("{} duplicates already recorded:", self.duplicates.len());
+            self.duplicates.iter().for_each(|game| {
+                println!(
+                    "  line {}: {} ({}) {} - {} {}",
+                    game.line, game.player, game.deck, game.won, game.lost, game.opp_deck
+                )
+            });
+        }
+        if !self.invalid.is_empty() {
+            println!("{} rows failed validation:", self.invalid.len());
+            self.invalid
+                .iter()
+                .for_each(|(line, reason)| println!("  line {}: {}", line, reason));
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Ingesting games from several sources in one run. Sources are fetched
+//! concurrently, then merged in the order given, each one checked against the
+//! data and the sources merged before it. URLs are always fetched anew, but
+//! their last response is cached for when they can't be, or `--offline`.
+
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::event::Events;
+use crate::{audit, crypt, http, import, store, GameLog};
+use std::error::Error;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Where a source's CSV comes from: a local file, or a URL such as a Google
+/// Sheet's CSV export link.
+#[derive(Debug, Clone)]
+pub enum Source {
+    File(PathBuf),
+    Url(String),
+}
+
+impl Source {
+    pub fn parse(spec: &str) -> Self {
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            Source::Url(spec.to_string())
+        } else {
+            Source::File(PathBuf::from(spec))
+        }
+    }
+
+    /// The source as games ingested from it record it: a file by its absolute
+    /// path, so it can be found again from anywhere.
+    fn path(&self) -> PathBuf {
+        match self {
+            Source::File(path) => std::path::absolute(path).unwrap_or_else(|_| path.clone()),
+            Source::Url(url) => PathBuf::from(url),
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+async fn fetch(client: Arc<http::Client>, source: Source) -> Result<Vec<u8>, String> {
+    match source {
+        Source::File(path) => {
+            tokio::task::spawn_blocking(move || crypt::read(&path).map_err(|err| err.to_string()))
+                .await
+                .map_err(|err| err.to_string())?
+        }
+        Source::Url(url) => client.get(&url).await,
+    }
+}
+
+/// A source's contents, or why they couldn't be read, and how long it took.
+type Fetched = (Result<Vec<u8>, String>, Duration);
+
+/// Every source's contents, in the order given, falling back to the cached
+/// response for URLs that can't be fetched.
+fn fetch_all(sources: &[Source], cache: &Cache) -> Result<Vec<Fetched>, Box<dyn Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let client = Arc::new(http::Client::new()?);
+    let fetched = runtime.block_on(async {
+        let tasks: Vec<_> = sources
+            .iter()
+            .filter(|source| !(cache.offline && matches!(source, Source::Url(_))))
+            .map(|source| {
+                let fetching = fetch(client.clone(), source.clone());
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    (fetching.await, started.elapsed())
+                })
+            })
+            .collect();
+        let mut fetched = Vec::new();
+        for task in tasks {
+            fetched.push(
+                task.await
+                    .unwrap_or_else(|err| (Err(err.to_string()), Duration::ZERO)),
+            );
+        }
+        fetched
+    });
+
+    let mut fetched = fetched.into_iter();
+    let mut all = Vec::new();
+    for source in sources {
+        let Source::Url(url) = source else {
+            all.extend(fetched.next());
+            continue;
+        };
+        if cache.offline {
+            let cached = cache.get(url).map(|(body, _)| body);
+            all.push((
+                cached.ok_or_else(|| format!("offline, and {} isn't cached", url)),
+                Duration::ZERO,
+            ));
+            continue;
+        }
+        let Some((contents, elapsed)) = fetched.next() else {
+            continue;
+        };
+        let contents = match contents {
+            Ok(body) => {
+                cache.put(url, &body)?;
+                Ok(body)
+            }
+            Err(err) => match cache.get(url) {
+                Some((body, age)) => {
+                    warn!(
+                        "couldn't fetch {}, using its copy from {} hours ago: {}",
+                        url,
+                        age.as_secs() / 3600,
+                        err
+                    );
+                    Ok(body)
+                }
+                None => Err(err),
+            },
+        };
+        all.push((contents, elapsed));
+    }
+    Ok(all)
+}
+
+pub fn run(
+    sources: &[String],
+    dry_run: bool,
+    data: &Path,
+    games: Vec<GameLog>,
+    events: &Events,
+    config: &Config,
+    offline: bool,
+) -> Result<(), Box<dyn Error>> {
+    let sources: Vec<Source> = sources.iter().map(|spec| Source::parse(spec)).collect();
+    let fetched = fetch_all(&sources, &Cache::new(data, offline))?;
+
+    let mut merged = games;
+    let mut entries = Vec::new();
+    let mut statuses = Vec::new();
+    let mut failed = 0;
+    for (source, (contents, elapsed)) in sources.iter().zip(fetched) {
+        let rows = contents.and_then(|contents| {
+            store::parse_rows(&contents, &config.aliases).map_err(|err| err.to_string())
+        });
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(err) => {
+                failed += 1;
+                statuses.push(format!(
+                    "  {}: failed after {:.1?}: {}",
+                    source, elapsed, err
+                ));
+                continue;
+            }
+        };
+        let mut plan = import::plan(
+            &merged,
+            "ingest",
+            &source.path(),
+            rows,
+            &config.roster,
+            events,
+        );
+        store::assign_ids(&merged, &mut plan.added);
+        statuses.push(format!(
+            "  {}: {} new, {} duplicates, {} invalid, fetched in {:.1?}",
+            source,
+            plan.added.len(),
+            plan.duplicates.len(),
+            plan.invalid.len(),
+            elapsed
+        ));
+        plan.invalid
+            .iter()
+            .for_each(|(line, reason)| statuses.push(format!("    line {}: {}", line, reason)));
+        entries.extend(
+            plan.added.iter().map(|game| {
+                audit::Entry::new(audit::Action::Add, game.id, None, Some(game.clone()))
+            }),
+        );
+        merged.extend(plan.added);
+    }
+
+    let verb = if dry_run { "Would add" } else { "Added" };
+    println!(
+        "{} {} games from {} sources:",
+        verb,
+        entries.len(),
+        sources.len()
+    );
+    statuses.iter().for_each(|status| println!("{}", status));
+    if !dry_run && !entries.is_empty() {
+        store::save_games(data, &merged)?;
+        audit::append(data, &entries)?;
+    }
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} sources couldn't be ingested",
+            failed,
+            sources.len()
+        )
+        .into());
+    }
+    Ok(())
+}
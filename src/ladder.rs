@@ -0,0 +1,111 @@
+use crate::config::{LadderConfig, RatingsConfig};
+use crate::{ratings, GameLog};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+/// A player moving between tiers, and the game that moved them.
+pub struct Move {
+    pub id: u32,
+    pub date: Option<NaiveDate>,
+    pub from: usize,
+    pub to: usize,
+    pub rating: f64,
+}
+
+/// Tier each player ends in and the moves that got them there, replaying the
+/// Elo history game by game.
+pub fn replay(
+    games: &[GameLog],
+    ladder: &LadderConfig,
+    config: &RatingsConfig,
+) -> BTreeMap<String, (usize, Vec<Move>)> {
+    let mut players: BTreeMap<String, (usize, Vec<Move>)> = BTreeMap::new();
+    let floor = |tier: usize| ladder.tiers.get(tier).map_or(f64::MIN, |tier| tier.rating);
+    ratings::elo_history(games, config)
+        .into_iter()
+        .for_each(|(game, rating)| {
+            let (tier, moves) = players.entry(game.player.to_string()).or_default();
+            let to = if *tier + 1 < ladder.tiers.len() && rating >= floor(*tier + 1) {
+                *tier + 1
+            } else if *tier > 0 && rating < floor(*tier) - ladder.demotion_buffer {
+                *tier - 1
+            } else {
+                return;
+            };
+            moves.push(Move {
+                id: game.id,
+                date: game.date,
+                from: *tier,
+                to,
+                rating,
+            });
+            *tier = to;
+        });
+    players
+}
+
+fn describe(ladder: &LadderConfig, step: &Move) -> String {
+    let verb = if step.to > step.from {
+        "Promoted"
+    } else {
+        "Demoted"
+    };
+    format!(
+        "{} to {} at {:.0}",
+        verb, ladder.tiers[step.to].name, step.rating
+    )
+}
+
+/// Who's in each tier, highest first and best rated first within it, or one
+/// player's tier history.
+pub fn print(
+    games: &[GameLog],
+    ladder: &LadderConfig,
+    config: &RatingsConfig,
+    roster: &[String],
+    player: Option<&str>,
+) {
+    if ladder.tiers.is_empty() {
+        println!("The ladder has no tiers configured.");
+        return;
+    }
+    let players = replay(games, ladder, config);
+    if let Some(player) = player {
+        let (tier, moves) = players
+            .get(player)
+            .map_or((0, &[][..]), |(tier, moves)| (*tier, moves.as_slice()));
+        println!("{}: {}", player, ladder.tiers[tier].name);
+        moves.iter().for_each(|step| {
+            let date = step
+                .date
+                .map_or_else(|| "----------".to_string(), |date| date.to_string());
+            println!("  {} #{} {}", date, step.id, describe(ladder, step))
+        });
+        return;
+    }
+    let ratings = ratings::elo(games, config);
+    let ranked = ratings::ranked(&ratings, roster);
+    (0..ladder.tiers.len()).rev().for_each(|tier| {
+        let members: Vec<&(String, f64)> = ranked
+            .iter()
+            .filter(|(player, _)| players.get(player).map_or(0, |(tier, _)| *tier) == tier)
+            .collect();
+        if members.is_empty() {
+            return;
+        }
+        println!("{}:", ladder.tiers[tier].name);
+        members
+            .iter()
+            .enumerate()
+            .for_each(|(i, (player, rating))| println!("  {}. {} ({:.0})", i + 1, player, rating));
+    });
+}
+
+/// Tells the player if game `id` moved them to another tier.
+pub fn announce(games: &[GameLog], ladder: &LadderConfig, config: &RatingsConfig, id: u32) {
+    replay(games, ladder, config)
+        .iter()
+        .flat_map(|(player, (_, moves))| moves.iter().map(move |step| (player, step)))
+        .filter(|(_, step)| step.id == id)
+        .for_each(|(player, step)| println!("{}: {}", player, describe(ladder, step)));
+}
@@ -0,0 +1,103 @@
+use crate::{sample, Deck, GameLog};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+struct Board {
+    title: String,
+    /// Player, displayed value, and the value ranked by (highest first)
+    rows: Vec<(String, String, f64)>,
+}
+
+impl Board {
+    fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, player: &str, shown: impl ToString, value: f64) {
+        self.rows
+            .push((player.to_string(), shown.to_string(), value));
+    }
+
+    fn print(mut self) {
+        self.rows.sort_by(|a, b| b.2.total_cmp(&a.2));
+        println!("{}:", self.title);
+        if self.rows.is_empty() {
+            println!("  nobody qualifies yet");
+        }
+        let mut rank = 0;
+        let mut previous = None;
+        for (i, (player, shown, value)) in self.rows.iter().enumerate() {
+            // tied players share a rank
+            if previous != Some(*value) {
+                rank = i + 1;
+                previous = Some(*value);
+            }
+            println!("  {:>2}. {:<10} {}", rank, player, shown);
+        }
+        println!();
+    }
+}
+
+fn longest_streak(games: &[&GameLog]) -> u32 {
+    games
+        .iter()
+        .filter(|game| !game.is_draw())
+        .fold((0, 0), |(longest, current), game| {
+            let current = if game.won > game.lost { current + 1 } else { 0 };
+            (longest.max(current), current)
+        })
+        .0
+}
+
+pub fn print(games: &[GameLog], roster: &[String], min_games: u32) {
+    let mut wins = Board::new("Most wins");
+    let mut win_rate = Board::new(format!("Best win rate (at least {} games)", min_games));
+    let mut decks = Board::new("Most distinct decks played");
+    let mut mirrors = Board::new("Most mirror matches");
+    let mut streaks = Board::new("Longest win streak");
+
+    roster.iter().for_each(|player| {
+        let played: Vec<&GameLog> = games.iter().filter(|game| game.player == *player).collect();
+        let won = played.iter().filter(|game| game.won > game.lost).count();
+        let decided = played.iter().filter(|game| !game.is_draw()).count();
+        wins.push(player, won, won as f64);
+        if decided as u32 >= min_games && decided > 0 {
+            let rate = won as f64 / decided as f64;
+            win_rate.push(
+                player,
+                format!(
+                    "{:.1}%{} ({} - {})",
+                    rate * 100.0,
+                    sample::marker(decided as u32),
+                    won,
+                    decided - won
+                ),
+                rate,
+            );
+        }
+
+        let parsed: Vec<(Deck, Deck)> = played
+            .iter()
+            .filter_map(|game| {
+                Some((
+                    Deck::from_str(&game.deck).ok()?,
+                    Deck::from_str(&game.opp_deck).ok()?,
+                ))
+            })
+            .collect();
+        let distinct: BTreeSet<Deck> = parsed.iter().map(|(deck, _)| *deck).collect();
+        decks.push(player, distinct.len(), distinct.len() as f64);
+        let mirror_count = parsed.iter().filter(|(deck, opp)| deck == opp).count();
+        mirrors.push(player, mirror_count, mirror_count as f64);
+
+        let streak = longest_streak(&played);
+        streaks.push(player, streak, streak as f64);
+    });
+
+    [wins, win_rate, decks, mirrors, streaks]
+        .into_iter()
+        .for_each(Board::print);
+}
@@ -0,0 +1,319 @@
+use crate::config::LeagueConfig;
+use crate::event::Events;
+use crate::roll::PlayBalance;
+use crate::{image, GameLog};
+use chrono::{Duration, Local, NaiveDate};
+use clap::Subcommand;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum LeagueCommand {
+    /// Points table for the season so far
+    Standings {
+        /// Only count games up to the end of this week
+        #[arg(long)]
+        week: Option<u32>,
+        /// Render the table as a PNG for sharing instead of printing it
+        #[arg(long)]
+        png: Option<PathBuf>,
+    },
+    /// Round-robin pairings for each week of the season
+    Fixtures {
+        /// Only show this week's pairings
+        #[arg(long)]
+        week: Option<u32>,
+        /// Render the pairings as a PNG for sharing instead of printing them
+        #[arg(long)]
+        png: Option<PathBuf>,
+        /// Say who is on the play in the first game of each match still to
+        /// come, evening out play and draw over the season, for formats that
+        /// let players choose instead of rolling for it
+        #[arg(long)]
+        balance_play: bool,
+    },
+    /// Final standings, weekly winners, each player's deck of the season, and
+    /// the prize money won in events that finished during it
+    Report,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Line {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    points: u32,
+}
+
+impl Line {
+    fn played(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// 1-based season week a date falls in, if it's part of the season at all.
+fn week_of(league: &LeagueConfig, date: NaiveDate) -> Option<u32> {
+    let days = (date - league.start).num_days();
+    let week = u32::try_from(days.div_euclid(7)).ok()? + 1;
+    (week <= league.weeks).then_some(week)
+}
+
+pub fn in_season(league: &LeagueConfig, date: NaiveDate) -> bool {
+    week_of(league, date).is_some()
+}
+
+fn current_week(league: &LeagueConfig) -> u32 {
+    week_of(league, Local::now().date_naive()).unwrap_or_else(|| {
+        if Local::now().date_naive() < league.start {
+            0
+        } else {
+            league.weeks
+        }
+    })
+}
+
+fn season_games<'a>(
+    league: &'a LeagueConfig,
+    games: &'a [GameLog],
+) -> impl Iterator<Item = (u32, &'a GameLog)> {
+    games
+        .iter()
+        .filter_map(move |game| Some((week_of(league, game.date?)?, game)))
+}
+
+fn table<'a>(
+    league: &LeagueConfig,
+    games: impl Iterator<Item = &'a GameLog>,
+    roster: &[String],
+) -> Vec<(String, Line)> {
+    let mut lines: BTreeMap<String, Line> = roster
+        .iter()
+        .map(|player| (player.clone(), Line::default()))
+        .collect();
+    games.for_each(|game| {
+        let line = lines.entry(game.player.to_string()).or_default();
+        if game.won > game.lost {
+            line.wins += 1;
+        } else if game.won < game.lost {
+            line.losses += 1;
+        } else {
+            line.draws += 1;
+        }
+        line.points += league.points.for_result(game.won, game.lost);
+    });
+    let mut table: Vec<(String, Line)> = lines.into_iter().collect();
+    table.sort_by(|(_, a), (_, b)| b.points.cmp(&a.points).then(b.wins.cmp(&a.wins)));
+    table
+}
+
+fn table_lines(table: &[(String, Line)]) -> Vec<String> {
+    let width = table
+        .iter()
+        .map(|(player, _)| player.len())
+        .max()
+        .unwrap_or(6)
+        .max(6);
+    let mut lines = vec![format!(
+        "     {:width$}  {:>3} {:>3} {:>3} {:>3} {:>4}",
+        "Player", "P", "W", "D", "L", "Pts"
+    )];
+    table.iter().enumerate().for_each(|(i, (player, line))| {
+        lines.push(format!(
+            "  {:>2} {:width$}  {:>3} {:>3} {:>3} {:>3} {:>4}",
+            i + 1,
+            player,
+            line.played(),
+            line.wins,
+            line.draws,
+            line.losses,
+            line.points
+        ))
+    });
+    lines
+}
+
+fn print_table(table: &[(String, Line)]) {
+    table_lines(table)
+        .iter()
+        .for_each(|line| println!("{}", line));
+}
+
+fn standings_table(
+    league: &LeagueConfig,
+    games: &[GameLog],
+    roster: &[String],
+    week: Option<u32>,
+) -> Vec<(String, Line)> {
+    let week = week.unwrap_or(league.weeks).min(league.weeks);
+    table(
+        league,
+        season_games(league, games)
+            .filter(|(game_week, _)| *game_week <= week)
+            .map(|(_, game)| game),
+        roster,
+    )
+}
+
+/// Points table up to the end of `week` (the whole season by default), with a title line.
+pub fn standings(
+    league: &LeagueConfig,
+    games: &[GameLog],
+    roster: &[String],
+    week: Option<u32>,
+) -> Vec<String> {
+    let through = week.unwrap_or(league.weeks).min(current_week(league));
+    let mut lines = vec![format!("Standings through week {}", through)];
+    lines.extend(table_lines(&standings_table(league, games, roster, week)));
+    lines
+}
+
+/// Round-robin pairings for one week using the circle method; an odd roster
+/// gives one player a bye each week.
+fn fixtures(roster: &[String], week: u32) -> Vec<(String, Option<String>)> {
+    let mut players: Vec<Option<&String>> = roster.iter().map(Some).collect();
+    if players.len() % 2 == 1 {
+        players.push(None);
+    }
+    if players.len() < 2 {
+        return Vec::new();
+    }
+    let rounds = players.len() - 1;
+    let rotation = (week as usize).saturating_sub(1) % rounds;
+    let (fixed, rest) = players.split_first_mut().unwrap();
+    rest.rotate_right(rotation);
+    let order: Vec<Option<&String>> = std::iter::once(*fixed)
+        .chain(rest.iter().copied())
+        .collect();
+    let half = order.len() / 2;
+    (0..half)
+        .filter_map(|i| match (order[i], order[order.len() - 1 - i]) {
+            (Some(a), Some(b)) => Some((a.clone(), Some(b.clone()))),
+            (Some(a), None) | (None, Some(a)) => Some((a.clone(), None)),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+/// A week's fixtures; with a `balance`, the player on the play in each
+/// match's first game is named first.
+fn fixture_lines(
+    league: &LeagueConfig,
+    roster: &[String],
+    week: u32,
+    mut balance: Option<&mut PlayBalance>,
+) -> Vec<String> {
+    let start = league.start + Duration::weeks(week as i64 - 1);
+    let mut lines = vec![format!("Week {} ({}):", week, start)];
+    fixtures(roster, week).into_iter().for_each(|(home, away)| {
+        match (away, balance.as_deref_mut()) {
+            (Some(away), Some(balance)) => {
+                let (first, second) = balance.assign(home, away);
+                lines.push(format!("  {} (on the play) vs. {}", first, second))
+            }
+            (Some(away), None) => lines.push(format!("  {} vs. {}", home, away)),
+            (None, _) => lines.push(format!("  {} has a bye", home)),
+        }
+    });
+    lines
+}
+
+fn report(league: &LeagueConfig, games: &[GameLog], roster: &[String], events: &Events) {
+    let week = current_week(league);
+    let finished = week >= league.weeks;
+    if !finished {
+        println!(
+            "Season in progress (week {} of {}), standings so far:",
+            week, league.weeks
+        );
+    } else {
+        println!("Final standings:");
+    }
+    let standings = table(
+        league,
+        season_games(league, games).map(|(_, game)| game),
+        roster,
+    );
+    print_table(&standings);
+    if let Some((champion, line)) = standings.first().filter(|(_, line)| line.played() > 0) {
+        let title = if finished { "Champion" } else { "Leader" };
+        println!("\n{}: {} with {} points", title, champion, line.points);
+    }
+
+    println!("\nWeekly winners:");
+    (1..=week.min(league.weeks)).for_each(|week| {
+        let weekly = table(
+            league,
+            season_games(league, games)
+                .filter(|(game_week, _)| *game_week == week)
+                .map(|(_, game)| game),
+            roster,
+        );
+        if let Some((winner, line)) = weekly.first().filter(|(_, line)| line.played() > 0) {
+            println!("  Week {}: {} ({} points)", week, winner, line.points);
+        }
+    });
+
+    println!("\nDeck of the season:");
+    roster.iter().for_each(|player| {
+        let mut decks: BTreeMap<&str, u32> = BTreeMap::new();
+        season_games(league, games)
+            .filter(|(_, game)| game.player == *player)
+            .for_each(|(_, game)| *decks.entry(&game.deck).or_insert(0) += 1);
+        if let Some((deck, count)) = decks.into_iter().max_by_key(|(_, count)| *count) {
+            println!("  {}: {} ({} matches)", player, deck, count);
+        }
+    });
+
+    let end = league.start + Duration::weeks(league.weeks as i64) - Duration::days(1);
+    let mut winnings: Vec<(&str, f64)> = events.winnings(league.start, end).into_iter().collect();
+    if !winnings.is_empty() {
+        winnings.sort_by(|a, b| b.1.total_cmp(&a.1));
+        println!("\nPrize winnings:");
+        winnings
+            .iter()
+            .for_each(|(player, amount)| println!("  {}: {:.2}", player, amount));
+    }
+}
+
+pub fn run(
+    command: LeagueCommand,
+    league: &LeagueConfig,
+    games: &[GameLog],
+    roster: &[String],
+    events: &Events,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        LeagueCommand::Standings { week, png } => match png {
+            Some(path) => image::render_text(&standings(league, games, roster, week), path)?,
+            None => print_table(&standings_table(league, games, roster, week)),
+        },
+        LeagueCommand::Fixtures {
+            week,
+            png,
+            balance_play,
+        } => {
+            let weeks = match week {
+                Some(week) => week..=week,
+                None => 1..=league.weeks,
+            };
+            // weeks already played keep whoever went first; the ones to come
+            // are assigned in order, each counting towards the next
+            let now = current_week(league);
+            let mut balance = balance_play
+                .then(|| PlayBalance::new(season_games(league, games).map(|(_, game)| game)));
+            let lines: Vec<String> = weeks
+                .flat_map(|week| {
+                    let balance = balance.as_mut().filter(|_| week >= now);
+                    fixture_lines(league, roster, week, balance)
+                })
+                .collect();
+            match png {
+                Some(path) => image::render_text(&lines, path)?,
+                None => lines.iter().for_each(|line| println!("{}", line)),
+            }
+        }
+        LeagueCommand::Report => report(league, games, roster, events),
+    }
+    Ok(())
+}
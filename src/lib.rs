@@ -0,0 +1,2477 @@
+#![allow(unused)]
+mod achievements;
+mod arena;
+mod audit;
+mod backtest;
+mod backup;
+pub mod bench;
+mod bot;
+mod bracket;
+mod bundle;
+mod cache;
+mod chart;
+pub mod client;
+mod colors;
+mod compare;
+mod completions;
+mod config;
+mod conflicts;
+mod crypt;
+mod decklist;
+mod detail;
+mod edit;
+mod event;
+mod gauntlet;
+mod http;
+mod image;
+mod import;
+mod ingest;
+mod intern;
+mod ladder;
+mod leaderboard;
+mod league;
+mod limited;
+mod matrix;
+mod metrics;
+mod mtgo;
+mod mulligans;
+mod notes;
+mod pdf;
+mod plugin;
+mod prices;
+mod progress;
+mod ratings;
+mod report;
+mod rivals;
+mod roll;
+mod sample;
+mod schema;
+mod script;
+mod season;
+mod serve;
+mod shorthand;
+mod snapshot;
+mod store;
+mod summary;
+mod sync;
+mod teams;
+mod trends;
+mod underplayed;
+mod xlsx;
+
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use config::{Aliases, Config};
+use csv::{Reader, StringRecord};
+use intern::{Decks, Name};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Parser)]
+#[command(about = "Matchup and player stats for our playgroup")]
+struct Cli {
+    /// Print more diagnostics to stderr (-v for progress, -vv for debugging)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print errors to stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Config file to use instead of the discovered protour.toml
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Playgroup profile from the config file to use
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Games CSV to read, overriding the configured data file
+    #[arg(long, global = true)]
+    data: Option<PathBuf>,
+
+    #[command(flatten)]
+    tags: notes::TagFilter,
+
+    /// Include the games of closed seasons, not just the current one
+    #[arg(long, global = true)]
+    all_time: bool,
+
+    /// Keep decks linked across formats or sources apart, under the names
+    /// they were recorded with
+    #[arg(long, global = true)]
+    unlinked: bool,
+
+    /// Don't go online: use responses cached from Scryfall and other
+    /// services, however old, and leave out whatever isn't cached
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Annotate matchups whose record deviates significantly from 50%
+    #[arg(long)]
+    significance: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Show a single matchup from both perspectives, with the games behind it
+    Vs { deck: Deck, opponent: Deck },
+    /// Combined record of every deck whose colors include all of the given
+    /// ones, e.g. `colors red` for Rb, Rg, Grixis, Naya, ... together
+    Colors {
+        /// Colors by name or letter (W, U, B, R, G)
+        #[arg(required = true)]
+        colors: Vec<Color>,
+    },
+    /// Show the archetype-vs-archetype matrix rolled up from every matchup
+    Archetypes {
+        /// How far up the archetype hierarchy to roll decks up: 0 for decks,
+        /// 1 for their archetype (or configured parent), and each level above
+        /// for the configured parents of those
+        #[arg(long, default_value_t = 1)]
+        level: u32,
+        /// Also write the matrix to this CSV file
+        #[arg(long)]
+        csv: Option<PathBuf>,
+    },
+    /// Write computed stats out to files
+    #[command(arg_required_else_help = true)]
+    Export {
+        /// Write the deck-by-deck matchup grid to this CSV file
+        #[arg(long)]
+        matrix: Option<PathBuf>,
+        /// Write an Excel workbook with games, matchups, player records, deck
+        /// records, and ratings
+        #[arg(long)]
+        xlsx: Option<PathBuf>,
+        /// Write an anonymized bundle of the games for sharing with other
+        /// playgroups: no player names, notes, or event names
+        #[arg(long)]
+        bundle: Option<PathBuf>,
+        /// Write the JSON Schema bundles are checked against, for tools that
+        /// write them
+        #[arg(long)]
+        bundle_schema: Option<PathBuf>,
+        /// Write what was known before each head-to-head game (decks,
+        /// ratings, play/draw, and the matchup's record so far) and how it
+        /// went, as CSV for training prediction models elsewhere
+        #[arg(long)]
+        predictions: Option<PathBuf>,
+    },
+    /// Merge games from another CSV into the data file
+    Import {
+        file: PathBuf,
+        /// Report what would be added without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Read the file as a Magic Online match log (.dat) instead of a CSV
+        #[arg(long, requires_all = ["deck", "opp_deck"])]
+        mtgo: bool,
+        /// Read the file as another playgroup's exported bundle; its players
+        /// are kept apart from ours under the file's name, so use --data to
+        /// merge it into a data file of its own for comparisons
+        #[arg(long, conflicts_with = "mtgo")]
+        bundle: bool,
+        /// Deck you played in the MTGO match
+        #[arg(long, requires = "mtgo")]
+        deck: Option<String>,
+        /// Deck your MTGO opponent played
+        #[arg(long, requires = "mtgo")]
+        opp_deck: Option<String>,
+        /// Your MTGO username, when it isn't aliased to someone on the roster
+        #[arg(long = "as", requires = "mtgo")]
+        username: Option<String>,
+    },
+    /// Merge games from several CSV files or URLs (e.g. a Google Sheet's CSV
+    /// export link) at once, fetching them concurrently
+    Ingest {
+        #[arg(required = true)]
+        sources: Vec<String>,
+        /// Report what would be added without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Make up data files of any size, for the benchmarks in `benches/`
+    BenchData {
+        #[command(subcommand)]
+        command: bench::BenchDataCommand,
+    },
+    /// Record MTG Arena matches from its Player.log
+    Arena {
+        #[command(subcommand)]
+        command: arena::ArenaCommand,
+    },
+    /// Record a game, prompting for anything not given as a flag
+    Add {
+        /// The game in one line, e.g. "Grant RbAggro 2-1 Noah GrixisMid,
+        /// flooded g2": player, deck, score, the opponent if they're on the
+        /// roster, their deck, and notes after a comma; flags override it
+        shorthand: Option<String>,
+        #[command(flatten)]
+        fields: edit::GameFields,
+    },
+    /// Who changed what in the data file, newest first
+    Audit {
+        /// Only changes to this game
+        id: Option<u32>,
+        /// Only changes made by this person
+        #[arg(long)]
+        by: Option<String>,
+        /// Changes to list
+        #[arg(long, default_value_t = 20)]
+        count: usize,
+    },
+    /// Revert the most recent command's changes: an add with its opponent's
+    /// side, an edit, a delete, an import, or a merge of two reports
+    Undo {
+        /// Show the changes that can be undone instead of reverting one
+        #[arg(long)]
+        list: bool,
+    },
+    /// Correct a mis-entered game
+    #[command(arg_required_else_help = true)]
+    Edit {
+        id: u32,
+        #[command(flatten)]
+        fields: edit::GameFields,
+    },
+    /// Remove a game from the data file
+    Delete { id: u32 },
+    /// Matches both players reported, whose reports would otherwise count
+    /// twice: the ones that agree, and the ones that contradict each other
+    Conflicts {
+        /// Merge each pair of reports that agree into one game
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Everything recorded about a game, where it came from, and every change
+    /// made to it since
+    Show { id: u32 },
+    /// Roll the data file back to an automatic backup
+    Restore {
+        /// Show the available backups
+        #[arg(long, conflicts_with = "to", required_unless_present = "to")]
+        list: bool,
+        /// Timestamp (or unique prefix of one) of the backup to restore
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Run events: register decks and report on them
+    Event {
+        #[command(subcommand)]
+        command: event::EventCommand,
+    },
+    /// Attach decklists to games and print them
+    Decklist {
+        #[command(subcommand)]
+        command: decklist::DecklistCommand,
+    },
+    /// Test a deck against a gauntlet of benchmark decks, kept apart from
+    /// the league's games
+    Gauntlet {
+        /// The deck being tested, e.g. "Rb Aggro"
+        #[arg(long)]
+        deck: String,
+        /// What to do with the gauntlet; reports on it if left out
+        #[command(subcommand)]
+        command: Option<gauntlet::GauntletCommand>,
+    },
+    /// Close the season, or look back at closed ones
+    Season {
+        #[command(subcommand)]
+        command: season::SeasonCommand,
+    },
+    /// Season standings, fixtures, and the season-end report
+    League {
+        #[command(subcommand)]
+        command: league::LeagueCommand,
+    },
+    /// The latest games, newest first
+    Recent {
+        /// How many games to list
+        #[arg(short = 'n', default_value_t = 20)]
+        count: usize,
+        #[command(flatten)]
+        filter: ratings::Filter,
+    },
+    /// A game night's recap for the group chat: matches played, records,
+    /// the biggest upset, achievements, and rating changes
+    Summary {
+        /// Tonight's games
+        #[arg(long, conflicts_with = "date")]
+        today: bool,
+        /// Another night's games; the latest night with games if neither is
+        /// given
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Achievements each player has earned over the game history
+    Achievements {
+        /// Only show this player's achievements
+        #[arg(long)]
+        player: Option<String>,
+    },
+    /// Current rating of everyone on the roster
+    Ratings {
+        #[arg(long, value_enum, default_value_t)]
+        system: ratings::System,
+        #[command(flatten)]
+        filter: ratings::Filter,
+        #[command(subcommand)]
+        command: Option<ratings::RatingsCommand>,
+    },
+    /// Ranked ladder tiers built on the Elo ratings, each with its own
+    /// leaderboard; needs a [ladder] section in the config
+    Ladder {
+        /// Show this player's promotions and demotions instead
+        #[arg(long)]
+        player: Option<String>,
+        #[command(flatten)]
+        filter: ratings::Filter,
+    },
+    /// Charts of how stats moved over time
+    Chart {
+        #[command(subcommand)]
+        command: chart::ChartCommand,
+    },
+    /// All-time ranked tables of the roster
+    Leaderboard {
+        /// Games needed to be ranked by win rate, overriding the config
+        #[arg(long)]
+        min_games: Option<u32>,
+    },
+    /// Share the data file through its git repo: commit local games, pull,
+    /// merge games recorded elsewhere, and push
+    Sync,
+    /// Write encrypted copies of the data file and audit log, for sharing them
+    /// without the notes being readable; the passphrase is read from
+    /// PROTOUR_PASSPHRASE
+    Encrypt,
+    /// Run the Discord bot so games can be recorded and looked up from the server
+    ///
+    /// Reads the bot token from DISCORD_TOKEN. Discord usernames are mapped to
+    /// the roster through the player aliases.
+    Bot,
+    /// Accept match results over HTTP, so they can be entered at the table
+    ///
+    /// Results are POSTed as JSON to /results with the token from
+    /// PROTOUR_SERVE_TOKEN as a bearer token, or entered in the form served
+    /// at /results; the season's matchups and players' records are at
+    /// /matchups and /players/NAME. See `serve`, and `client` for a typed
+    /// client.
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// Show how win rates and the metagame moved between two datasets, e.g.
+    /// before and after a set release
+    Compare {
+        /// Games before the change
+        #[arg(requires = "new", required_unless_present = "split")]
+        old: Option<PathBuf>,
+        /// Games after the change
+        new: Option<PathBuf>,
+        /// Compare games before this date with games from it on, instead of two files
+        #[arg(long, conflicts_with = "old")]
+        split: Option<NaiveDate>,
+    },
+    /// A deck's record against the field
+    Deck {
+        deck: Deck,
+        /// Everything about the deck: its pilots, matchups, recent games,
+        /// rating over time, and share of the metagame
+        #[arg(long)]
+        detail: bool,
+    },
+    /// Decks ranked by win rate against the field per dollar their list
+    /// costs, priced from Scryfall by the list last attached to one of
+    /// their games
+    Budget {
+        /// Games needed to be ranked, overriding the config
+        #[arg(long)]
+        min_games: Option<u32>,
+    },
+    /// A player's record
+    Player {
+        player: String,
+        /// Everything about the player: their record by season, decks,
+        /// head-to-heads, rating over time, streaks, and recent games
+        #[arg(long)]
+        detail: bool,
+    },
+    /// A deck's most favorable and most unfavorable matchups
+    Matchups {
+        #[arg(long)]
+        deck: Deck,
+        /// How many of the best matchups to list
+        #[arg(long, default_value_t = 10)]
+        best: usize,
+        /// How many of the worst matchups to list
+        #[arg(long, default_value_t = 10)]
+        worst: usize,
+        /// Games a matchup needs to be ranked, overriding the config
+        #[arg(long)]
+        min_games: Option<u32>,
+    },
+    /// Compare two decks on only the opponents both have faced, and where
+    /// their records differ most
+    CompareDecks { deck: Deck, other: Deck },
+    /// The matchups with the fewest games behind them, and who should play
+    /// them next; pairs up the configured deck_pool, or every deck with at
+    /// least the configured min_games
+    Underplayed {
+        /// How many matchups to suggest
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Each player's nemesis and customer: the opponents they do worst and best
+    /// against head to head, with their records and recent form
+    Rivals,
+    /// Win rates of each color combination in our drafts, month by month
+    Drafts,
+    /// Win rates of each color combination in our sealed events, and the
+    /// record of every pool
+    Sealed,
+    /// Records of the teams in team games like Two-Headed Giant, and how they
+    /// did against each other
+    Teams,
+    /// Backtest the matchup matrix: predict every match from the games before
+    /// it and compare the predictions with what happened
+    Calibration,
+    /// Win rates by the annotations in game notes: `t:5` for the turn the
+    /// match ended on, `flood` and `screw` for mana trouble, and `mull:2`
+    Notes {
+        /// Instead, the words in each deck's notes that go most with its wins
+        /// and with its losses, scored by log-odds
+        #[arg(long)]
+        terms: bool,
+        /// Only this deck's terms
+        #[arg(long, requires = "terms")]
+        deck: Option<Deck>,
+        /// How many terms to list each way
+        #[arg(long, default_value_t = 5, requires = "terms")]
+        count: usize,
+    },
+    /// Win rate of each deck by how many times its pilot mulliganed
+    Mulligans,
+    /// How often the player who won the die roll went on to win the match,
+    /// overall and per matchup; or hand out random decks for game night
+    Roll {
+        #[command(subcommand)]
+        command: Option<roll::RollCommand>,
+    },
+    /// Save the computed stats to compare with later, or diff two saved ones
+    Snapshot {
+        #[command(subcommand)]
+        command: snapshot::SnapshotCommand,
+    },
+    /// Each deck's win rate over rolling windows of matches, flagging decks
+    /// whose recent results have moved away from their lifetime rate
+    Trends {
+        /// Matches in each window
+        #[arg(long, default_value_t = 20)]
+        window: usize,
+    },
+    /// Run a rhai script with the games, matchups, and ratings; see `script`
+    Script { path: PathBuf },
+    /// Print a shell completion script, suggesting the players, decks, events,
+    /// and tags in the current data; regenerate it to pick up new ones
+    Completions { shell: clap_complete::Shell },
+    /// Render a custom report from a tera template
+    Report {
+        /// Template rendered with the computed stats; see `report::ReportContext`
+        #[arg(long)]
+        template: PathBuf,
+    },
+}
+
+impl Command {
+    /// Whether the command works on every game, closed seasons and all.
+    fn sees_all_seasons(&self) -> bool {
+        self.writes_games()
+            || matches!(
+                self,
+                Command::Show { .. }
+                    | Command::Season { .. }
+                    | Command::Event { .. }
+                    | Command::Player { detail: true, .. }
+            )
+    }
+
+    /// Commands that see draft and other limited games, which are otherwise
+    /// kept out of the constructed stats.
+    fn sees_limited_games(&self) -> bool {
+        self.writes_games()
+            || self
+                .rating_filter()
+                .is_some_and(|filter| !filter.format.is_empty())
+            || matches!(
+                self,
+                Command::Event { .. }
+                    | Command::Decklist { .. }
+                    | Command::Show { .. }
+                    | Command::Drafts
+                    | Command::Sealed
+            )
+    }
+
+    /// How a command that computes ratings, or lists games, narrows the games
+    /// it works on.
+    fn rating_filter(&self) -> Option<&ratings::Filter> {
+        match self {
+            Command::Ratings { filter, .. }
+            | Command::Recent { filter, .. }
+            | Command::Ladder { filter, .. }
+            | Command::Chart {
+                command: chart::ChartCommand::Rating { filter, .. },
+            } => Some(filter),
+            _ => None,
+        }
+    }
+
+    /// Commands that save the data file, and so have to see every game.
+    fn writes_games(&self) -> bool {
+        matches!(
+            self,
+            Command::Import { .. }
+                | Command::Ingest { .. }
+                | Command::Arena { .. }
+                | Command::Add { .. }
+                | Command::Undo { .. }
+                | Command::Edit { .. }
+                | Command::Delete { .. }
+                | Command::Conflicts { merge: true }
+                | Command::Restore { .. }
+                | Command::Sync
+                | Command::Encrypt
+                | Command::Bot
+                | Command::Serve { .. }
+                | Command::Decklist {
+                    command: decklist::DecklistCommand::Classify { fix: true }
+                }
+        )
+    }
+}
+
+#[derive(Debug)]
+enum GameParseError {
+    Color(String),
+    Archetype(String),
+    Other,
+    StrumError(strum::ParseError),
+}
+
+impl Error for GameParseError {}
+impl From<strum::ParseError> for GameParseError {
+    fn from(value: strum::ParseError) -> Self {
+        Self::StrumError(value)
+    }
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, PartialOrd, strum_macros::Display, strum_macros::EnumString,
+)]
+enum Player {
+    Grant,
+    Isaac,
+    Eamonn,
+    Noah,
+    Random,
+}
+
+impl Display for GameParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GameParseError, check data")
+    }
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum_macros::Display,
+    strum_macros::EnumString,
+    strum_macros::EnumIter,
+)]
+enum ColorIdentity {
+    White,
+    Black,
+    Red,
+    Green,
+    Blue,
+    Uw,
+    Ub,
+    Ur,
+    Ug,
+    Rg,
+    Rw,
+    Rb,
+    Gw,
+    Gb,
+    Bw,
+    Naya,
+    Grixis,
+    Esper,
+    Bant,
+    Jund,
+    Abzan,
+    Jeskai,
+    Sultai,
+    Mardu,
+    Temur,
+    #[strum(serialize = "4c")]
+    FourColor,
+    #[strum(serialize = "5c")]
+    FiveColor,
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[strum(ascii_case_insensitive)]
+enum Color {
+    #[strum(serialize = "White", serialize = "W")]
+    White,
+    #[strum(serialize = "Blue", serialize = "U")]
+    Blue,
+    #[strum(serialize = "Black", serialize = "B")]
+    Black,
+    #[strum(serialize = "Red", serialize = "R")]
+    Red,
+    #[strum(serialize = "Green", serialize = "G")]
+    Green,
+}
+
+impl ColorIdentity {
+    /// The colors of the identity. A four-color deck doesn't say which color
+    /// it leaves out, so it only has the colors every four-color deck shares:
+    /// none.
+    fn colors(&self) -> BTreeSet<Color> {
+        use Color::*;
+        let colors: &[Color] = match self {
+            ColorIdentity::White => &[White],
+            ColorIdentity::Black => &[Black],
+            ColorIdentity::Red => &[Red],
+            ColorIdentity::Green => &[Green],
+            ColorIdentity::Blue => &[Blue],
+            ColorIdentity::Uw => &[Blue, White],
+            ColorIdentity::Ub => &[Blue, Black],
+            ColorIdentity::Ur => &[Blue, Red],
+            ColorIdentity::Ug => &[Blue, Green],
+            ColorIdentity::Rg => &[Red, Green],
+            ColorIdentity::Rw => &[Red, White],
+            ColorIdentity::Rb => &[Red, Black],
+            ColorIdentity::Gw => &[Green, White],
+            ColorIdentity::Gb => &[Green, Black],
+            ColorIdentity::Bw => &[Black, White],
+            ColorIdentity::Naya => &[Red, Green, White],
+            ColorIdentity::Grixis => &[Blue, Black, Red],
+            ColorIdentity::Esper => &[White, Blue, Black],
+            ColorIdentity::Bant => &[Green, White, Blue],
+            ColorIdentity::Jund => &[Black, Red, Green],
+            ColorIdentity::Abzan => &[White, Black, Green],
+            ColorIdentity::Jeskai => &[Blue, Red, White],
+            ColorIdentity::Sultai => &[Black, Green, Blue],
+            ColorIdentity::Mardu => &[Red, White, Black],
+            ColorIdentity::Temur => &[Green, Blue, Red],
+            ColorIdentity::FourColor => &[],
+            ColorIdentity::FiveColor => &[White, Blue, Black, Red, Green],
+        };
+        colors.iter().copied().collect()
+    }
+
+    fn contains(&self, colors: &BTreeSet<Color>) -> bool {
+        self.colors().is_superset(colors)
+    }
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum_macros::Display,
+    strum_macros::EnumIter,
+)]
+enum Archetype {
+    Aggro,
+    Control,
+    Anvil,
+    Midrange,
+    Combo,
+    Legends,
+    Toxic,
+    Atraxa,
+    Tempo,
+    Vehicles,
+    Domain,
+    Reanimator,
+    Enchantments,
+}
+
+impl FromStr for Archetype {
+    type Err = GameParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_ref() {
+            "AGGRO" => Ok(Archetype::Aggro),
+            "ANVIL" => Ok(Archetype::Anvil),
+            "MIDRANGE" => Ok(Archetype::Midrange),
+            "COMBO" => Ok(Archetype::Combo),
+            "CONTROL" => Ok(Archetype::Control),
+            "LEGENDS" => Ok(Archetype::Legends),
+            "TOXIC" => Ok(Archetype::Toxic),
+            "ATRAXA" => Ok(Archetype::Atraxa),
+            "TEMPO" => Ok(Archetype::Tempo),
+            "DOMAIN" => Ok(Archetype::Domain),
+            "REANIMATOR" => Ok(Archetype::Reanimator),
+            "VEHICLES" => Ok(Archetype::Vehicles),
+            "ENCHANTMENTS" => Ok(Archetype::Enchantments),
+            _ => Err(GameParseError::Archetype(s.to_string())),
+        }
+    }
+}
+
+/// A deck by its color identity and archetype. Games sometimes only say what
+/// colors a deck was (`Rb ?`, or just `Rb`); those decks have no archetype,
+/// and count toward color-level records but not archetype-level matchups.
+#[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Deck(ColorIdentity, Option<Archetype>);
+
+/// The name `levels` steps up from `deck` in the archetype hierarchy. A deck's
+/// parent is its configured one or else its archetype; anything without a
+/// configured parent is the top of its hierarchy.
+fn rolled_up(deck: Deck, parents: &BTreeMap<String, String>, levels: u32) -> String {
+    let mut name = deck.to_string();
+    for level in 0..levels {
+        match parents.get(&name) {
+            Some(parent) => name = parent.clone(),
+            None if level == 0 => match deck.1 {
+                Some(archetype) => name = archetype.to_string(),
+                None => break,
+            },
+            None => break,
+        }
+    }
+    name
+}
+
+impl Deck {
+    fn new(color_id: ColorIdentity, archetype: Option<Archetype>) -> Self {
+        Deck(color_id, archetype)
+    }
+}
+
+impl Display for Deck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.1 {
+            Some(archetype) => write!(f, "{} {}", self.0, archetype),
+            None => write!(f, "{} ?", self.0),
+        }
+    }
+}
+
+impl FromStr for Deck {
+    type Err = GameParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(' ');
+        let color_id = ColorIdentity::from_str(parts.next().unwrap_or(""))?;
+        // an archetype left out, marked unknown with `?`, or not one we know
+        // of leaves only the colors known
+        let archetype = Archetype::from_str(parts.next().unwrap_or("")).ok();
+        Ok(Deck(color_id, archetype))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GameLog {
+    /// Stable identifier, assigned when the game is first loaded or imported
+    #[serde(default)]
+    id: u32,
+    /// Line of the source CSV this game was read from
+    #[serde(skip)]
+    line: u64,
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    player: Name,
+    deck: Name,
+    won: u32,
+    lost: u32,
+    opp_deck: Name,
+    /// Who played `opp_deck`, when they're on the roster; their side of the
+    /// match is recorded for them as a game of its own when this one is added,
+    /// and the two are edited and deleted separately after that
+    #[serde(default)]
+    opponent: Option<Name>,
+    /// Who reported the game, when it wasn't the player: the opponent whose
+    /// report it's the other side of
+    #[serde(default)]
+    reported_by: Option<Name>,
+    /// Whether both players reported the game and their reports were merged
+    #[serde(default)]
+    confirmed: Option<bool>,
+    notes: String,
+    /// Event the game was played in, checked against its deck registrations
+    #[serde(default)]
+    event: Option<String>,
+    /// Match length; left empty for the usual best of three
+    #[serde(default)]
+    best_of: Option<u32>,
+    /// Multiplayer game (e.g. a Commander pod) this row is one seat of; every
+    /// seat in the game shares the pod name
+    #[serde(default)]
+    pod: Option<String>,
+    /// Mulligans the player took across the match
+    #[serde(default)]
+    mulligans: Option<u32>,
+    /// Mulligans their opponent took across the match
+    #[serde(default)]
+    opp_mulligans: Option<u32>,
+    /// Whether the player won the die roll for the first game
+    #[serde(default)]
+    won_roll: Option<bool>,
+    /// Whether the player was on the play in the first game
+    #[serde(default)]
+    on_play: Option<bool>,
+    /// Team the player was on in a team game like Two-Headed Giant; every
+    /// seat of the game shares a pod, and teammates share a result
+    #[serde(default)]
+    team: Option<String>,
+    /// Where the game was first recorded: the importer, file, and line it
+    /// came from, or the command that added it; empty for games recorded
+    /// before sources were kept
+    #[serde(default)]
+    source: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Matchup {
+    deck: Deck,
+    opponent: Deck,
+    win: u32,
+    loss: u32,
+}
+
+impl Matchup {
+    fn new(deck: Deck, opponent: Deck) -> Self {
+        Self {
+            deck,
+            opponent,
+            win: 0,
+            loss: 0,
+        }
+    }
+
+    fn key(&self) -> (Deck, Deck) {
+        (self.deck, self.opponent)
+    }
+
+    /// Whether both decks' archetypes are known, as archetype-level
+    /// matchups need.
+    fn knows_archetypes(&self) -> bool {
+        self.deck.1.is_some() && self.opponent.1.is_some()
+    }
+
+    fn complement(&self) -> Self {
+        Self {
+            deck: self.opponent,
+            opponent: self.deck,
+            win: self.loss,
+            loss: self.win,
+        }
+    }
+
+    fn add(&mut self, other: Self) -> Result<&mut Self, GameParseError> {
+        if self.key() == other.key() {
+            self.win += other.win;
+            self.loss += other.loss;
+            Ok(self)
+        } else {
+            Err(GameParseError::Other)
+        }
+    }
+
+    /// Two-sided exact binomial test of this record against a 50% win rate.
+    fn p_value(&self) -> f64 {
+        let n = self.win + self.loss;
+        let extreme = self.win.min(self.loss);
+        // P(X = k) for X ~ Binomial(n, 0.5), built up incrementally from P(X = 0)
+        let mut probability = 0.5f64.powi(n as i32);
+        let mut tail = 0.0;
+        for k in 0..=extreme {
+            tail += probability;
+            probability *= (n - k) as f64 / (k + 1) as f64;
+        }
+        (2.0 * tail).min(1.0)
+    }
+
+    fn significance_marker(&self) -> &'static str {
+        match self.p_value() {
+            p if p < 0.01 => "**",
+            p if p < 0.05 => "*",
+            _ => "",
+        }
+    }
+}
+
+impl Display for Matchup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} - {} {}",
+            self.deck, self.win, self.loss, self.opponent
+        )
+    }
+}
+
+const DEFAULT_BEST_OF: u32 = 3;
+
+impl GameLog {
+    /// Everything that was recorded about the game, used to spot duplicates.
+    fn key(&self) -> String {
+        let recorded = GameLog {
+            id: 0,
+            source: None,
+            ..self.clone()
+        };
+        serde_json::to_string(&recorded).unwrap_or_default()
+    }
+
+    /// The match from the opponent's side, as a game of their own, when the
+    /// opponent was recorded.
+    fn complement(&self) -> Option<GameLog> {
+        let opponent = self.opponent.clone()?;
+        let reporter = self.reporter().clone();
+        Some(GameLog {
+            reported_by: (reporter != opponent).then_some(reporter),
+            id: 0,
+            player: opponent,
+            deck: self.opp_deck.clone(),
+            won: self.lost,
+            lost: self.won,
+            opp_deck: self.deck.clone(),
+            opponent: Some(self.player.clone()),
+            mulligans: self.opp_mulligans,
+            opp_mulligans: self.mulligans,
+            won_roll: self.won_roll.map(|won| !won),
+            on_play: self.on_play.map(|on_play| !on_play),
+            ..self.clone()
+        })
+    }
+
+    /// Whoever entered the game.
+    fn reporter(&self) -> &Name {
+        self.reported_by.as_ref().unwrap_or(&self.player)
+    }
+
+    /// Whether this is the second row of a match recorded for both players,
+    /// the one its decks' results aren't counted from: the side entered for
+    /// the opponent or, once both players' reports of it were merged, the
+    /// side of whichever player's name sorts later. Each player's own record
+    /// still counts it.
+    fn is_complement(&self) -> bool {
+        self.reported_by.is_some()
+            || (self.confirmed == Some(true)
+                && self
+                    .opponent
+                    .as_ref()
+                    .is_some_and(|opponent| *opponent < self.player))
+    }
+
+    fn best_of(&self) -> u32 {
+        self.best_of.unwrap_or(DEFAULT_BEST_OF)
+    }
+
+    /// Mulligans the player took, falling back to a `mull:` annotation in the
+    /// notes for games recorded before the column existed.
+    fn mulligans(&self) -> Option<u32> {
+        self.mulligans
+            .or_else(|| notes::Annotations::parse(&self.notes).mulligans)
+    }
+
+    fn is_draw(&self) -> bool {
+        self.won == self.lost
+    }
+
+    fn matchups(&self) -> Vec<Matchup> {
+        self.parsed_matchups(&mut Decks::default())
+    }
+
+    /// Like `matchups`, parsing the decks through `decks`, so a history only
+    /// parses each distinct deck spec once.
+    fn parsed_matchups<'a>(&'a self, decks: &mut Decks<'a>) -> Vec<Matchup> {
+        let mut matchups = Vec::new();
+        // a drawn match says nothing about which deck is favored, a pod seat
+        // wasn't a head-to-head match, and the opponent's side of a match is
+        // already counted from the player's
+        if self.is_draw() || self.pod.is_some() || self.is_complement() {
+            return matchups;
+        }
+        let deck = decks.parse(&self.deck);
+        let opponent = decks.parse(&self.opp_deck);
+        let player_won = self.won > self.lost;
+
+        match (deck, opponent) {
+            (Some(player_deck), Some(opp_deck)) => {
+                let matchup = Matchup {
+                    deck: player_deck,
+                    opponent: opp_deck,
+                    win: if player_won { 1 } else { 0 },
+                    loss: if player_won { 0 } else { 1 },
+                };
+                matchups.push(matchup.complement());
+                matchups.push(matchup);
+            }
+            (deck, opponent) => {
+                let side = if deck.is_none() { "deck" } else { "opp_deck" };
+                warn!(
+                    line = self.line,
+                    field = side,
+                    deck = %self.deck,
+                    opp_deck = %self.opp_deck,
+                    source = self.source,
+                    "bad game log record"
+                );
+            }
+        }
+        matchups
+    }
+}
+
+fn deck_vs_field(matchups: &BTreeMap<(Deck, Deck), Matchup>, deck: Deck) -> (u32, u32) {
+    matchups
+        .iter()
+        .filter(|((d, _), _)| *d == deck)
+        // filter out mirror matchups
+        .filter(|((d, o), _)| *d != *o)
+        .fold((0, 0), |(wins, losses), (_, matchup)| {
+            (wins + matchup.win, losses + matchup.loss)
+        })
+}
+
+// furthest a deck's adjusted strength is taken from even, in Elo points, so a
+// perfect or winless record still comes out short of 100% or 0%
+const ADJUSTED_SPREAD: f64 = 800.0;
+
+/// The rating, relative to an average pilot, at which `results` (whether each
+/// match was won, and the opposing pilot's Elo) are as many wins as expected.
+fn performance(results: &[(bool, f64)]) -> f64 {
+    let wins = results.iter().filter(|(won, _)| *won).count() as f64;
+    let expected_wins = |shift: f64| -> f64 {
+        results
+            .iter()
+            .map(|(_, opponent)| ratings::expected(ratings::INITIAL_RATING + shift, *opponent))
+            .sum()
+    };
+    // expected wins only grow with the shift, so it's found by bisection
+    let (mut low, mut high) = (-ADJUSTED_SPREAD, ADJUSTED_SPREAD);
+    for _ in 0..50 {
+        let mid = (low + high) / 2.0;
+        if expected_wins(mid) < wins {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Each deck's win rate vs. the field with its results weighed by the Elo of
+/// the pilots it faced, so beating a weak player counts for less than beating
+/// a strong one: the rate an average pilot would score against an average
+/// opponent, given the rating its results are worth.
+///
+/// A deck logged as `opp_deck` faced whoever logged the game, and one logged
+/// as `deck` faced the recorded opponent; a match against someone off the
+/// roster counts as against an average opponent.
+fn adjusted_vs_field(games: &[GameLog], ratings: &BTreeMap<String, f64>) -> BTreeMap<Deck, f64> {
+    let mut results: BTreeMap<Deck, Vec<(bool, f64)>> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw() && game.pod.is_none() && !game.is_complement())
+        .for_each(|game| {
+            let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+            let (Ok(deck), Ok(opponent)) = decks else {
+                return;
+            };
+            if deck == opponent {
+                return;
+            }
+            let won = game.won > game.lost;
+            let rating = |player: Option<&str>| {
+                player
+                    .and_then(|player| ratings.get(player))
+                    .copied()
+                    .unwrap_or(ratings::INITIAL_RATING)
+            };
+            let (player, pilot) = (rating(Some(&game.player)), rating(game.opponent.as_deref()));
+            results.entry(deck).or_default().push((won, pilot));
+            results.entry(opponent).or_default().push((!won, player));
+        });
+    results
+        .into_iter()
+        .map(|(deck, results)| {
+            let strength = ratings::INITIAL_RATING + performance(&results);
+            (deck, ratings::expected(strength, ratings::INITIAL_RATING))
+        })
+        .collect()
+}
+
+fn deck_record(
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    adjusted: &BTreeMap<Deck, f64>,
+    custom: &metrics::Metrics,
+    deck: Deck,
+) -> Result<(), Box<dyn Error>> {
+    let (wins, losses) = deck_vs_field(matchups, deck);
+    let custom: String = custom
+        .names()
+        .zip(custom.deck(matchups, deck)?)
+        .map(|(name, value)| format!(", {} {}", name, metrics::display(value)))
+        .collect();
+    match adjusted.get(&deck) {
+        Some(adjusted) => println!(
+            "{} vs. field: {} - {} (opponent-adjusted {:.1}%{}){}",
+            deck,
+            wins,
+            losses,
+            adjusted * 100.0,
+            sample::marker(wins + losses),
+            custom
+        ),
+        None => println!("{} vs. field: {} - {}{}", deck, wins, losses, custom),
+    }
+    Ok(())
+}
+
+fn matchup_lookup(
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    deck: Deck,
+    opponent: Deck,
+) {
+    let matchup = matchups
+        .get(&(deck, opponent))
+        .copied()
+        .unwrap_or(Matchup::new(deck, opponent));
+    println!("{}", matchup);
+    println!("{}", matchup.complement());
+
+    let involved: Vec<&GameLog> = games
+        .iter()
+        .filter(|game| !game.is_complement())
+        .filter(|game| {
+            let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+            match decks {
+                (Ok(d), Ok(o)) => (d, o) == (deck, opponent) || (d, o) == (opponent, deck),
+                _ => false,
+            }
+        })
+        .collect();
+    if involved.is_empty() {
+        println!("\nNo games recorded for this matchup.");
+        return;
+    }
+
+    let pilots: BTreeSet<&str> = involved.iter().map(|game| game.player.as_str()).collect();
+    println!(
+        "\nPilots: {}",
+        pilots.into_iter().collect::<Vec<_>>().join(", ")
+    );
+    println!("Games:");
+    involved
+        .iter()
+        .for_each(|game| println!("  {}", game_line(game)));
+}
+
+/// A game on one line: its id, date, pilot and deck, result, opposing deck,
+/// and notes.
+fn game_line(game: &GameLog) -> String {
+    let date = game
+        .date
+        .map(|date| date.to_string())
+        .unwrap_or_else(|| "----------".to_string());
+    let length = match game.best_of {
+        Some(best_of) => format!(" (bo{})", best_of),
+        None => String::new(),
+    };
+    let line = format!(
+        "#{} {} {} ({}) {} - {}{} {}  {}",
+        game.id,
+        date,
+        game.player,
+        game.deck.trim(),
+        game.won,
+        game.lost,
+        length,
+        game.opp_deck.trim(),
+        game.notes
+    );
+    line.trim_end().to_string()
+}
+
+/// The `best` highest and `worst` lowest win rates among a deck's matchups
+/// with at least `min_games` decided games, mirrors aside.
+fn best_and_worst(
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    deck: Deck,
+    best: usize,
+    worst: usize,
+    min_games: u32,
+) {
+    let win_rate = |matchup: &Matchup| matchup.win as f64 / (matchup.win + matchup.loss) as f64;
+    let mut ranked: Vec<&Matchup> = matchups
+        .values()
+        .filter(|matchup| matchup.deck == deck && matchup.opponent != deck)
+        .filter(|matchup| matchup.knows_archetypes())
+        .filter(|matchup| matchup.win + matchup.loss >= min_games.max(1))
+        .collect();
+    if ranked.is_empty() {
+        println!(
+            "{} has no matchups with at least {} games yet.",
+            deck, min_games
+        );
+        return;
+    }
+    ranked.sort_by(|a, b| {
+        win_rate(b)
+            .total_cmp(&win_rate(a))
+            .then((b.win + b.loss).cmp(&(a.win + a.loss)))
+    });
+    let print = |matchup: &Matchup| {
+        println!(
+            "  {} ({:.1}%{}){}",
+            matchup,
+            win_rate(matchup) * 100.0,
+            sample::marker(matchup.win + matchup.loss),
+            matchup.significance_marker()
+        )
+    };
+    println!("Best matchups (at least {} games):", min_games);
+    ranked.iter().take(best).for_each(|matchup| print(matchup));
+    println!("\nWorst matchups:");
+    ranked
+        .iter()
+        .rev()
+        .take(worst)
+        .for_each(|matchup| print(matchup));
+}
+
+fn player_wins_losses(games: &[GameLog], player: &str) -> (u32, u32) {
+    games
+        .iter()
+        .filter(|game| game.player == player && !game.is_draw())
+        .fold((0, 0), |(wins, losses), game| {
+            if game.won > game.lost {
+                (wins + 1, losses)
+            } else {
+                (wins, losses + 1)
+            }
+        })
+}
+
+fn player_record(games: &[GameLog], player: &str) {
+    let (wins, losses) = player_wins_losses(games, player);
+    println!("{}'s record: {} - {}", player, wins, losses);
+}
+
+// pseudo-games of field EV blended into a player's personal archetype record
+const RECOMMENDATION_PRIOR_GAMES: f64 = 10.0;
+// pseudo-games at 50% added to each matchup so thin samples don't dominate the EV
+const MATCHUP_PRIOR_GAMES: f64 = 2.0;
+
+fn field_shares(games: &[GameLog]) -> BTreeMap<Deck, f64> {
+    let mut counts: BTreeMap<Deck, u32> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_complement())
+        .flat_map(|game| [Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck)])
+        .flatten()
+        .for_each(|deck| *counts.entry(deck).or_insert(0) += 1);
+    let total: u32 = counts.values().sum();
+    counts
+        .into_iter()
+        .map(|(deck, count)| (deck, count as f64 / total as f64))
+        .collect()
+}
+
+fn field_ev(
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    shares: &BTreeMap<Deck, f64>,
+    deck: Deck,
+) -> f64 {
+    shares
+        .iter()
+        .map(|(opponent, share)| {
+            let win_rate = match matchups.get(&(deck, *opponent)) {
+                Some(matchup) if *opponent != deck => {
+                    (matchup.win as f64 + 0.5 * MATCHUP_PRIOR_GAMES)
+                        / ((matchup.win + matchup.loss) as f64 + MATCHUP_PRIOR_GAMES)
+                }
+                _ => 0.5,
+            };
+            win_rate * share
+        })
+        .sum()
+}
+
+fn deck_recommendations(
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    player: &str,
+    count: usize,
+) {
+    let shares = field_shares(games);
+    let mut archetype_records: BTreeMap<Archetype, (u32, u32)> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| game.player == player && !game.is_draw())
+        .filter_map(|game| Some((Deck::from_str(&game.deck).ok()?.1?, game.won > game.lost)))
+        .for_each(|(archetype, won)| {
+            let record = archetype_records.entry(archetype).or_insert((0, 0));
+            if won {
+                record.0 += 1;
+            } else {
+                record.1 += 1;
+            }
+        });
+
+    // only recommend decks someone in the pod has actually piloted, and whose
+    // archetype is known
+    let decks: BTreeSet<Deck> = games
+        .iter()
+        .flat_map(|game| Deck::from_str(&game.deck))
+        .collect();
+    let mut scored: Vec<_> = decks
+        .into_iter()
+        .filter_map(|deck| {
+            let archetype = deck.1?;
+            let ev = field_ev(matchups, &shares, deck);
+            let (wins, losses) = archetype_records.get(&archetype).copied().unwrap_or((0, 0));
+            let score = (wins as f64 + ev * RECOMMENDATION_PRIOR_GAMES)
+                / ((wins + losses) as f64 + RECOMMENDATION_PRIOR_GAMES);
+            Some(((deck, archetype), score, ev, (wins, losses)))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    println!("Best deck choices for {}:", player);
+    scored.iter().take(count).enumerate().for_each(
+        |(i, ((deck, archetype), score, ev, (wins, losses)))| {
+            println!(
+                "  {}. {}: {:.1}% (field EV {:.1}%, {} on {}: {} - {})",
+                i + 1,
+                deck,
+                score * 100.0,
+                ev * 100.0,
+                player,
+                archetype,
+                wins,
+                losses
+            )
+        },
+    );
+}
+
+fn build_matchups(games: &[GameLog]) -> BTreeMap<(Deck, Deck), Matchup> {
+    let mut matchups: BTreeMap<(Deck, Deck), Matchup> = BTreeMap::new();
+    let mut decks = Decks::default();
+
+    games.iter().for_each(|game| {
+        game.parsed_matchups(&mut decks).iter().for_each(|matchup| {
+            let mut entry = matchups
+                .entry(matchup.key())
+                .or_insert(Matchup::new(matchup.deck, matchup.opponent));
+            let result = entry.add(*matchup);
+            if result.is_err() {
+                warn!(line = game.line, "error adding matchup, keys not matched");
+            }
+        });
+    });
+    matchups
+}
+
+fn show_game(game: &GameLog, history: &[audit::Entry]) {
+    println!("Game #{}", game.id);
+    if let Some(date) = game.date {
+        println!("  Date: {}", date);
+    }
+    println!(
+        "  {} ({}) {} - {} {}",
+        game.player, game.deck, game.won, game.lost, game.opp_deck
+    );
+    [
+        ("Event", game.event.clone()),
+        ("Opponent", game.opponent.as_deref().map(str::to_string)),
+        (
+            "Reported by",
+            game.reported_by.as_deref().map(str::to_string),
+        ),
+        (
+            "Confirmed by both players",
+            game.confirmed.map(|confirmed| confirmed.to_string()),
+        ),
+        ("Pod", game.pod.clone()),
+        ("Team", game.team.clone()),
+        ("Best of", game.best_of.map(|best_of| best_of.to_string())),
+        (
+            "Mulligans",
+            game.mulligans.map(|mulligans| mulligans.to_string()),
+        ),
+        (
+            "Opponent mulligans",
+            game.opp_mulligans.map(|mulligans| mulligans.to_string()),
+        ),
+        ("Won the roll", game.won_roll.map(|won| won.to_string())),
+        (
+            "On the play in game one",
+            game.on_play.map(|on_play| on_play.to_string()),
+        ),
+        (
+            "Notes",
+            Some(game.notes.clone()).filter(|notes| !notes.is_empty()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| value.map(|value| (label, value)))
+    .for_each(|(label, value)| println!("  {}: {}", label, value));
+    println!("  Data file line: {}", game.line);
+    println!(
+        "  Source: {}",
+        game.source
+            .as_deref()
+            .unwrap_or("unknown, recorded before sources were kept")
+    );
+    let changes: Vec<&audit::Entry> = history.iter().filter(|entry| entry.id == game.id).collect();
+    if !changes.is_empty() {
+        println!("History:");
+        changes.iter().for_each(|entry| {
+            println!(
+                "  {}  {} by {}",
+                entry.at.format("%Y-%m-%d %H:%M"),
+                entry.action,
+                entry.by.as_deref().unwrap_or("unknown")
+            )
+        });
+    }
+}
+
+// how many recent commands `undo` can step back through
+const UNDO_HISTORY: usize = 10;
+
+/// Reverts one audited mutation, returning the game as it was before and after.
+fn undo(
+    games: &mut Vec<GameLog>,
+    entry: &audit::Entry,
+) -> Result<(Option<GameLog>, Option<GameLog>), Box<dyn Error>> {
+    match (entry.action, &entry.before) {
+        (audit::Action::Add, _) => {
+            let current = store::find_mut(games, entry.id)?.clone();
+            games.retain(|game| game.id != entry.id);
+            Ok((Some(current), None))
+        }
+        (audit::Action::Edit, Some(before)) => {
+            let game = store::find_mut(games, entry.id)?;
+            let current = game.clone();
+            *game = before.clone();
+            Ok((Some(current), Some(before.clone())))
+        }
+        (audit::Action::Delete, Some(before)) => {
+            let position = games.partition_point(|game| game.id < entry.id);
+            games.insert(position, before.clone());
+            Ok((None, Some(before.clone())))
+        }
+        _ => Err(format!("can't undo {} of game #{}", entry.action, entry.id).into()),
+    }
+}
+
+/// Reverts every change the latest command still in the undo history made,
+/// returning the entries it reverted, last first.
+fn undo_latest(data: &Path, mut games: Vec<GameLog>) -> Result<Vec<audit::Entry>, Box<dyn Error>> {
+    let entries = audit::read(data)?;
+    let Some(command) = audit::undo_stack(&entries).pop() else {
+        return Err("nothing to undo".into());
+    };
+    let mut undone = Vec::new();
+    for entry in command.iter().rev() {
+        let (before, after) = undo(&mut games, entry)?;
+        undone.push(audit::Entry::new(
+            audit::Action::Undo,
+            entry.id,
+            before,
+            after,
+        ));
+    }
+    store::save_games(data, &games)?;
+    audit::append(data, &undone)?;
+    Ok(command.iter().rev().cloned().collect())
+}
+
+/// Validates and appends a new game to the data file, returning it as
+/// recorded, followed by the opponent's side of it when the opponent was
+/// recorded. A game recorded without an event goes in the running event the
+/// player is paired in on that deck, if there's exactly one. `source` is the
+/// command or service it was recorded through, and `by` who entered it there
+/// when that isn't whoever's running protour.
+///
+/// A side the other player already reported is merged into their report
+/// instead, and returned as merged; a side that contradicts their report is
+/// recorded with a warning, to be sorted out through `conflicts`.
+fn add_game(
+    data: &Path,
+    mut games: Vec<GameLog>,
+    mut game: GameLog,
+    roster: &[String],
+    source: &str,
+    by: Option<&str>,
+) -> Result<Vec<GameLog>, Box<dyn Error>> {
+    game.source = Some(source.to_string());
+    let events = event::Events::load(data)?;
+    if game.event.is_none() {
+        game.event = events.infer(&game).map(str::to_string);
+    }
+    let mut added: Vec<GameLog> = [Some(game.clone()), game.complement()]
+        .into_iter()
+        .flatten()
+        .collect();
+    for game in &added {
+        import::validate(game, roster, &events)?;
+    }
+    let mut entries = Vec::new();
+    let mut recorded = Vec::new();
+    let paired = conflicts::paired(&games);
+    for game in added {
+        let reported = games
+            .iter_mut()
+            .filter(|existing| !paired.contains(&existing.id))
+            .find_map(|existing| Some((conflicts::compare(existing, &game)?, existing)));
+        match reported {
+            Some((conflicts::Report::Agrees, existing)) => {
+                let before = existing.clone();
+                conflicts::merge(existing, &game);
+                info!(id = existing.id, "merged a second report of the game");
+                entries.push(audit::Entry::new(
+                    audit::Action::Edit,
+                    existing.id,
+                    Some(before),
+                    Some(existing.clone()),
+                ));
+                recorded.push(existing.clone());
+            }
+            reported => {
+                if let Some((_, existing)) = reported {
+                    warn!(
+                        "{} reported this match as {} - {} in game #{}; see `protour conflicts`",
+                        existing.reporter(),
+                        existing.won,
+                        existing.lost,
+                        existing.id
+                    );
+                }
+                let mut game = game;
+                store::assign_ids(&games, std::slice::from_mut(&mut game));
+                entries.push(audit::Entry::new(
+                    audit::Action::Add,
+                    game.id,
+                    None,
+                    Some(game.clone()),
+                ));
+                games.push(game.clone());
+                recorded.push(game);
+            }
+        }
+    }
+    if let Some(by) = by {
+        entries = entries.into_iter().map(|entry| entry.by(by)).collect();
+    }
+    store::save_games(data, &games)?;
+    audit::append(data, &entries)?;
+    Ok(recorded)
+}
+
+/// Renames every deck linked to another to the one it's linked to, so their
+/// games count together.
+fn link_decks(games: &mut [GameLog], links: &BTreeMap<String, String>) {
+    if links.is_empty() {
+        return;
+    }
+    let links: BTreeMap<&str, Name> = links
+        .iter()
+        .map(|(deck, identity)| (deck.as_str(), Name::from(identity)))
+        .collect();
+    games.iter_mut().for_each(|game| {
+        if let Some(identity) = links.get(game.deck.as_str()) {
+            game.deck = identity.clone();
+        }
+        if let Some(identity) = links.get(game.opp_deck.as_str()) {
+            game.opp_deck = identity.clone();
+        }
+    });
+}
+
+fn init_logging(cli: &Cli) {
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_max_level(level)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// Runs the command line `protour` was started with.
+pub fn main() -> Result<(), Box<dyn Error>> {
+    run(Cli::parse())?;
+    sample::print_footnote();
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    init_logging(&cli);
+    let mut config = Config::load(cli.config.as_deref())?;
+    if let Some(profile) = &cli.profile {
+        config = config.with_profile(profile)?;
+    }
+    sample::set_threshold(config.report.small_sample);
+    let data = cli.data.clone().unwrap_or_else(|| config.data.clone());
+    let data = &data;
+    let seasons = season::Seasons::load(data)?;
+    // once a season has closed, the league's weeks count from the next one
+    if let (Some(league), Some(start)) = (config.league.as_mut(), seasons.current_start()) {
+        league.start = league.start.max(start);
+    }
+    debug!(?config, "resolved config");
+    // made-up data is generated apart from the league's own
+    if let Some(Command::BenchData { command }) = &cli.command {
+        return bench::run(command);
+    }
+    let mut games = store::load_games(data, &config.aliases)?;
+    if !cli.all_time && !cli.command.as_ref().is_some_and(Command::sees_all_seasons) {
+        games.retain(|game| seasons.is_current(game));
+    }
+    if !cli.unlinked && !cli.command.as_ref().is_some_and(Command::writes_games) {
+        link_decks(&mut games, &config.links);
+    }
+    if !cli.tags.is_empty() {
+        if cli.command.as_ref().is_some_and(Command::writes_games) {
+            return Err("--tag and --not-tag only apply to reports".into());
+        }
+        games.retain(|game| cli.tags.matches(game));
+    }
+    let events = event::Events::load(data)?;
+    if !cli
+        .command
+        .as_ref()
+        .is_some_and(Command::sees_limited_games)
+    {
+        games.retain(|game| !events.is_limited(game));
+    }
+    let matchups = build_matchups(&games);
+
+    match cli.command {
+        Some(Command::Vs { deck, opponent }) => {
+            matchup_lookup(&games, &matchups, deck, opponent);
+            return Ok(());
+        }
+        Some(Command::Colors { colors }) => {
+            colors::print(&matchups, &colors.into_iter().collect());
+            return Ok(());
+        }
+        Some(Command::Archetypes { level, csv }) => {
+            let archetypes =
+                matrix::aggregate(&matchups, |deck| rolled_up(deck, &config.archetypes, level));
+            matrix::print(&archetypes);
+            if let Some(path) = csv {
+                matrix::write_csv(&archetypes, path)?;
+            }
+            return Ok(());
+        }
+        Some(Command::Export {
+            matrix,
+            xlsx,
+            bundle,
+            bundle_schema,
+            predictions,
+        }) => {
+            if let Some(path) = matrix {
+                matrix::write_csv(&matrix::aggregate(&matchups, |deck| deck), path)?;
+            }
+            if let Some(path) = xlsx {
+                let custom = metrics::Metrics::new(&config.metrics)?;
+                xlsx::write_workbook(
+                    path,
+                    &games,
+                    &matchups,
+                    &config.roster,
+                    &custom,
+                    &config.ratings,
+                )?;
+            }
+            if let Some(path) = bundle {
+                bundle::write(&path, &games)?;
+            }
+            if let Some(path) = bundle_schema {
+                std::fs::write(path, bundle::JSON_SCHEMA)?;
+            }
+            if let Some(path) = predictions {
+                backtest::write_predictions(&path, &games, &config.ratings)?;
+            }
+            return Ok(());
+        }
+        Some(Command::Import {
+            file,
+            dry_run,
+            mtgo,
+            bundle,
+            deck,
+            opp_deck,
+            username,
+        }) => {
+            let mut roster = config.roster.clone();
+            let rows = if bundle {
+                let source = file
+                    .file_stem()
+                    .map_or("bundle".into(), |stem| stem.to_string_lossy());
+                let games = bundle::read(&file, &source, &config.aliases)?;
+                // bundle players are only ever on their own group's roster
+                roster.extend(games.iter().map(|game| game.player.to_string()));
+                games.into_iter().map(Ok).collect()
+            } else if mtgo {
+                let deck = deck.unwrap_or_default();
+                let opp_deck = opp_deck.unwrap_or_default();
+                let game = mtgo::read_game(
+                    &file,
+                    username.as_deref(),
+                    &deck,
+                    &opp_deck,
+                    &config.aliases,
+                    &config.roster,
+                )?;
+                vec![Ok(game)]
+            } else {
+                store::read_rows(&file, &config.aliases)?
+            };
+            let importer = if bundle {
+                "import --bundle"
+            } else if mtgo {
+                "import --mtgo"
+            } else {
+                "import"
+            };
+            let source = std::path::absolute(&file)?;
+            let mut plan = import::plan(&games, importer, &source, rows, &roster, &events);
+            store::assign_ids(&games, &mut plan.added);
+            plan.print(dry_run);
+            if !dry_run && !plan.added.is_empty() {
+                let entries: Vec<audit::Entry> = plan
+                    .added
+                    .iter()
+                    .map(|game| {
+                        audit::Entry::new(audit::Action::Add, game.id, None, Some(game.clone()))
+                    })
+                    .collect();
+                let mut merged = games;
+                merged.extend(plan.added);
+                store::save_games(data, &merged)?;
+                audit::append(data, &entries)?;
+            }
+            return Ok(());
+        }
+        Some(Command::Ingest { sources, dry_run }) => {
+            ingest::run(
+                &sources,
+                dry_run,
+                data,
+                games,
+                &events,
+                &config,
+                cli.offline,
+            )?;
+            return Ok(());
+        }
+        Some(Command::BenchData { .. }) => unreachable!("run before the games are loaded"),
+        Some(Command::Arena { command }) => {
+            arena::run(command, data, &config)?;
+            return Ok(());
+        }
+        Some(Command::Add {
+            shorthand,
+            mut fields,
+        }) => {
+            if let Some(line) = shorthand {
+                shorthand::parse(&line, &mut fields, &config.roster, &config.aliases)?;
+            }
+            fields.prompt_missing()?;
+            let game = fields.into_game(&config.aliases)?;
+            let last = games.iter().map(|game| game.id).max().unwrap_or(0);
+            let added = add_game(data, games, game, &config.roster, "add", None)?;
+            let (game, id) = (&added[0], added[0].id);
+            match &game.event {
+                _ if id <= last => println!("Merged with game #{}, already reported", id),
+                Some(event) => println!("Added game #{} to {}", id, event),
+                None => println!("Added game #{}", id),
+            }
+            if let Some(complement) = added.get(1) {
+                if complement.id <= last {
+                    println!(
+                        "Merged with game #{} for {}",
+                        complement.id, complement.player
+                    );
+                } else {
+                    println!("Added game #{} for {}", complement.id, complement.player);
+                }
+            }
+            if let Some(ladder) = &config.ladder {
+                let mut games = store::load_games(data, &config.aliases)?;
+                games.retain(|game| !events.is_limited(game));
+                ladder::announce(&games, ladder, &config.ratings, id);
+            }
+            return Ok(());
+        }
+        Some(Command::Audit { id, by, count }) => {
+            audit::print(&audit::read(data)?, id, by.as_deref(), count);
+            return Ok(());
+        }
+        Some(Command::Undo { list }) => {
+            if list {
+                let entries = audit::read(data)?;
+                let stack = audit::undo_stack(&entries);
+                let history = &stack[stack.len().saturating_sub(UNDO_HISTORY)..];
+                history.iter().rev().for_each(|command| {
+                    let changes: Vec<String> = command
+                        .iter()
+                        .map(|entry| format!("{} #{}", entry.action, entry.id))
+                        .collect();
+                    println!(
+                        "{} {}",
+                        command[0].at.format("%Y-%m-%d %H:%M"),
+                        changes.join(", ")
+                    )
+                });
+                return Ok(());
+            }
+            for entry in undo_latest(data, games)? {
+                println!("Undid {} of game #{}", entry.action, entry.id);
+            }
+            return Ok(());
+        }
+        Some(Command::Edit { id, fields }) => {
+            let mut games = games;
+            let game = store::find_mut(&mut games, id)?;
+            let before = game.clone();
+            fields.apply(game, &config.aliases);
+            import::validate(game, &config.roster, &events)?;
+            let after = game.clone();
+            store::save_games(data, &games)?;
+            audit::append(
+                data,
+                &[audit::Entry::new(
+                    audit::Action::Edit,
+                    id,
+                    Some(before),
+                    Some(after),
+                )],
+            )?;
+            println!("Updated game #{}", id);
+            return Ok(());
+        }
+        Some(Command::Delete { id }) => {
+            let mut games = games;
+            let before = store::find_mut(&mut games, id)?.clone();
+            games.retain(|game| game.id != id);
+            store::save_games(data, &games)?;
+            audit::append(
+                data,
+                &[audit::Entry::new(
+                    audit::Action::Delete,
+                    id,
+                    Some(before),
+                    None,
+                )],
+            )?;
+            println!("Deleted game #{}", id);
+            return Ok(());
+        }
+        Some(Command::Conflicts { merge }) => {
+            conflicts::run(data, games, merge)?;
+            return Ok(());
+        }
+        Some(Command::Show { id }) => {
+            let game = games
+                .iter()
+                .find(|game| game.id == id)
+                .ok_or_else(|| format!("no game with id {}", id))?;
+            show_game(game, &audit::read(data)?);
+            return Ok(());
+        }
+        Some(Command::Restore { list, to }) => {
+            if let Some(timestamp) = to {
+                let restored = backup::restore(data, &timestamp)?;
+                println!("Restored {} from the {} backup", data.display(), restored);
+            } else {
+                backup::list(data)?
+                    .iter()
+                    .for_each(|(timestamp, path)| println!("{}  {}", timestamp, path.display()));
+            }
+            return Ok(());
+        }
+        Some(Command::Event { command }) => {
+            event::run(command, data, &games, &config)?;
+            return Ok(());
+        }
+        Some(Command::Decklist { command }) => {
+            decklist::run(command, data, games, &config.classifier)?;
+            return Ok(());
+        }
+        Some(Command::Gauntlet { deck, command }) => {
+            gauntlet::run(&deck, command, data, &config.aliases)?;
+            return Ok(());
+        }
+        Some(Command::Season { command }) => {
+            season::run(command, data, &games, &config)?;
+            return Ok(());
+        }
+        Some(Command::League { command }) => {
+            let league = config
+                .league
+                .as_ref()
+                .ok_or("no [league] section in the config")?;
+            league::run(command, league, &games, &config.roster, &events)?;
+            return Ok(());
+        }
+        Some(Command::Summary { today, date }) => {
+            let date = match date {
+                Some(date) => date,
+                None if today => chrono::Local::now().date_naive(),
+                None => games
+                    .iter()
+                    .filter_map(|game| game.date)
+                    .max()
+                    .ok_or("no dated games recorded yet")?,
+            };
+            summary::print(&games, &config.roster, &config.ratings, date);
+            return Ok(());
+        }
+        Some(Command::Achievements { player }) => {
+            let players = match player {
+                Some(player) => vec![config.aliases.player(&player)],
+                None => config.roster.clone(),
+            };
+            achievements::print(&games, &players, &config.roster);
+            return Ok(());
+        }
+        Some(Command::Ratings {
+            system,
+            filter,
+            command,
+        }) => {
+            let games = filter.apply(games, &events, config.league.as_ref())?;
+            match command {
+                Some(ratings::RatingsCommand::Evaluate) => {
+                    ratings::evaluate(&games, &config.ratings)
+                }
+                None => ratings::print(system, &games, &config.roster, &config.ratings),
+            }
+            return Ok(());
+        }
+        Some(Command::Ladder { player, filter }) => {
+            let ladder = config
+                .ladder
+                .as_ref()
+                .ok_or("no [ladder] section in the config")?;
+            let games = filter.apply(games, &events, config.league.as_ref())?;
+            let player = player.map(|player| config.aliases.player(&player));
+            ladder::print(
+                &games,
+                ladder,
+                &config.ratings,
+                &config.roster,
+                player.as_deref(),
+            );
+            return Ok(());
+        }
+        Some(Command::Chart { command }) => {
+            chart::run(command, games, &events, &config)?;
+            return Ok(());
+        }
+        Some(Command::Leaderboard { min_games }) => {
+            let min_games = min_games.unwrap_or(config.report.min_games);
+            leaderboard::print(&games, &config.roster, min_games);
+            return Ok(());
+        }
+        Some(Command::Sync) => {
+            sync::run(data)?;
+            return Ok(());
+        }
+        Some(Command::Encrypt) => {
+            if crypt::is_encrypted(data) {
+                return Err(format!("{} is already encrypted", data.display()).into());
+            }
+            let encrypted = crypt::encrypted_path(data);
+            store::save_games(&encrypted, &games)?;
+            audit::extend(&encrypted, &audit::read(data)?)?;
+            println!(
+                "Wrote {} and {}; point `data` at the encrypted file and remove the plain ones",
+                encrypted.display(),
+                audit::path_for(&encrypted).display()
+            );
+            return Ok(());
+        }
+        Some(Command::Bot) => {
+            let token = std::env::var("DISCORD_TOKEN").map_err(|_| "DISCORD_TOKEN is not set")?;
+            bot::run(&token, data.clone(), config)?;
+            return Ok(());
+        }
+        Some(Command::Serve { address }) => {
+            let token = std::env::var(serve::TOKEN_VAR)
+                .map_err(|_| format!("{} is not set", serve::TOKEN_VAR))?;
+            serve::run(&address, token, data.clone(), config)?;
+            return Ok(());
+        }
+        Some(Command::Compare { old, new, split }) => {
+            let (old, new) = match (old, new, split) {
+                (Some(old), Some(new), _) => (
+                    store::load_games(&old, &config.aliases)?,
+                    store::load_games(&new, &config.aliases)?,
+                ),
+                (_, _, Some(split)) => {
+                    let (old, new): (Vec<GameLog>, Vec<GameLog>) = games
+                        .into_iter()
+                        .filter(|game| game.date.is_some())
+                        .partition(|game| game.date < Some(split));
+                    (old, new)
+                }
+                _ => return Err("give two data files or --split".into()),
+            };
+            let (old, new): (Vec<GameLog>, Vec<GameLog>) = (
+                old.into_iter()
+                    .filter(|game| cli.tags.matches(game))
+                    .collect(),
+                new.into_iter()
+                    .filter(|game| cli.tags.matches(game))
+                    .collect(),
+            );
+            compare::print(&old, &new, &config.roster);
+            return Ok(());
+        }
+        Some(Command::Deck { deck, detail }) => {
+            if detail {
+                let costs = prices::costs(data, &games, cli.offline)?;
+                detail::deck(&games, &matchups, deck, costs.get(&deck), &config.ratings);
+            } else {
+                let adjusted = adjusted_vs_field(&games, &ratings::elo(&games, &config.ratings));
+                let custom = metrics::Metrics::new(&config.metrics)?;
+                deck_record(&matchups, &adjusted, &custom, deck)?;
+            }
+            return Ok(());
+        }
+        Some(Command::Budget { min_games }) => {
+            let min_games = min_games.unwrap_or(config.report.min_games);
+            prices::budget(
+                &matchups,
+                &prices::costs(data, &games, cli.offline)?,
+                min_games,
+            );
+            return Ok(());
+        }
+        Some(Command::Recent { count, filter }) => {
+            let games = filter.apply(games, &events, config.league.as_ref())?;
+            if games.is_empty() {
+                println!("No games recorded yet.");
+            }
+            ratings::chronological(&games)
+                .into_iter()
+                .rev()
+                .take(count)
+                .for_each(|game| println!("{}", game_line(game)));
+            return Ok(());
+        }
+        Some(Command::Player { player, detail }) => {
+            let player = config.aliases.player(&player);
+            if detail {
+                detail::player(&games, &player, &seasons, &config.ratings);
+            } else {
+                player_record(&games, &player);
+            }
+            return Ok(());
+        }
+        Some(Command::Matchups {
+            deck,
+            best,
+            worst,
+            min_games,
+        }) => {
+            let min_games = min_games.unwrap_or(config.report.min_games);
+            best_and_worst(&matchups, deck, best, worst, min_games);
+            return Ok(());
+        }
+        Some(Command::Underplayed { count }) => {
+            let decks: BTreeSet<Deck> = if config.deck_pool.is_empty() {
+                let mut played: BTreeMap<Deck, u32> = BTreeMap::new();
+                games
+                    .iter()
+                    .flat_map(|game| [Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck)])
+                    .flatten()
+                    .filter(|deck| deck.1.is_some())
+                    .for_each(|deck| *played.entry(deck).or_default() += 1);
+                played
+                    .into_iter()
+                    .filter(|(_, count)| *count >= config.report.min_games)
+                    .map(|(deck, _)| deck)
+                    .collect()
+            } else {
+                config
+                    .deck_pool
+                    .iter()
+                    .map(|deck| Deck::from_str(&config.aliases.deck(deck)))
+                    .collect::<Result<_, _>>()?
+            };
+            underplayed::print(&games, &matchups, &decks, count);
+            return Ok(());
+        }
+        Some(Command::CompareDecks { deck, other }) => {
+            compare::decks(&matchups, deck, other);
+            return Ok(());
+        }
+        Some(Command::Rivals) => {
+            rivals::print(&games, &config.roster);
+            return Ok(());
+        }
+        Some(Command::Drafts) => {
+            limited::print(&games, &events, event::Format::Draft);
+            return Ok(());
+        }
+        Some(Command::Sealed) => {
+            limited::print(&games, &events, event::Format::Sealed);
+            return Ok(());
+        }
+        Some(Command::Teams) => {
+            teams::print(&games);
+            return Ok(());
+        }
+        Some(Command::Calibration) => {
+            backtest::print_matchups(&games);
+            return Ok(());
+        }
+        Some(Command::Notes { terms, deck, count }) => {
+            if terms {
+                notes::print_terms(&games, deck, count);
+            } else {
+                notes::print(&games);
+            }
+            return Ok(());
+        }
+        Some(Command::Mulligans) => {
+            mulligans::print(&games);
+            return Ok(());
+        }
+        Some(Command::Roll { command }) => {
+            roll::run(command, data, &games, &config)?;
+            return Ok(());
+        }
+        Some(Command::Snapshot { command }) => {
+            snapshot::run(command, &games, &matchups, &config)?;
+            return Ok(());
+        }
+        Some(Command::Trends { window }) => {
+            trends::print(&games, window.max(1));
+            return Ok(());
+        }
+        Some(Command::Script { path }) => {
+            script::run(&path, &games, &matchups, &config)?;
+            return Ok(());
+        }
+        Some(Command::Completions { shell }) => {
+            completions::print(shell, &games, &config.roster, &events);
+            return Ok(());
+        }
+        Some(Command::Report { template }) => {
+            let mut context = report::ReportContext::new(&games, &matchups, &config.roster);
+            context.plugins = plugin::run_all(&config.plugins, &games)?;
+            context.add_metrics(&metrics::Metrics::new(&config.metrics)?, &matchups)?;
+            print!("{}", report::render(&template, &context)?);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let player_decks: BTreeSet<Deck> = games
+        .iter()
+        .flat_map(|game| Deck::from_str(&game.deck))
+        .collect();
+
+    let significance = cli.significance || config.report.significance;
+    println!("Raw Matchup data:");
+    let known = matchups
+        .values()
+        .filter(|matchup| matchup.knows_archetypes());
+    known.for_each(|matchup| {
+        if significance {
+            println!(
+                "{} (p = {:.3}){}",
+                matchup,
+                matchup.p_value(),
+                matchup.significance_marker()
+            );
+        } else {
+            println!("{}", matchup);
+        }
+    });
+    if significance {
+        println!("* p < 0.05, ** p < 0.01 (exact binomial test against 50%)");
+    }
+
+    print!("\n\n");
+
+    let adjusted = adjusted_vs_field(&games, &ratings::elo(&games, &config.ratings));
+    let custom = metrics::Metrics::new(&config.metrics)?;
+    for deck in &player_decks {
+        deck_record(&matchups, &adjusted, &custom, *deck)?;
+    }
+    deck_record(
+        &matchups,
+        &adjusted,
+        &custom,
+        Deck::new(ColorIdentity::FiveColor, Some(Archetype::Atraxa)),
+    )?;
+    print!("\n\n");
+
+    config
+        .roster
+        .iter()
+        .for_each(|player| player_record(&games, player));
+    print!("\n\n");
+
+    config.roster.iter().for_each(|player| {
+        deck_recommendations(&games, &matchups, player, config.report.recommendations)
+    });
+
+    if !config.plugins.is_empty() {
+        let metrics = plugin::run_all(&config.plugins, &games)?;
+        print!("\n\n");
+        println!("Plugin metrics:");
+        metrics
+            .iter()
+            .for_each(|(name, value)| println!("  {}: {}", name, plugin::display(value)));
+    }
+    Ok(())
+}
+
+/// Game `id` for tests: Grant's Rb Aggro beating Noah's Grixis Midrange 2 - 1
+/// on 2026-10-13, as Grant reported it.
+#[cfg(test)]
+pub(crate) fn test_game(id: u32) -> GameLog {
+    GameLog {
+        id,
+        date: NaiveDate::from_ymd_opt(2026, 10, 13),
+        player: "Grant".into(),
+        deck: "Rb Aggro".into(),
+        won: 2,
+        lost: 1,
+        opp_deck: "Grixis Midrange".into(),
+        opponent: Some("Noah".into()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck(spec: &str) -> Deck {
+        Deck::from_str(spec).unwrap()
+    }
+
+    #[test]
+    fn adjusted_win_rate_stays_a_rate() {
+        let ratings = BTreeMap::from([("Noah".to_string(), 2400.0)]);
+        let games: Vec<GameLog> = (0..5).map(|_| test_game(1)).collect();
+        let adjusted = adjusted_vs_field(&games, &ratings);
+        let (winner, loser) = (
+            adjusted[&deck("Rb Aggro")],
+            adjusted[&deck("Grixis Midrange")],
+        );
+        assert!(winner > 0.5 && winner < 1.0, "{}", winner);
+        assert!(loser > 0.0 && loser < 0.5, "{}", loser);
+
+        // even against an average field, a split record is an even deck
+        let split = vec![
+            test_game(1),
+            GameLog {
+                won: 0,
+                lost: 2,
+                opponent: None,
+                ..test_game(1)
+            },
+        ];
+        let adjusted = adjusted_vs_field(&split, &BTreeMap::new());
+        assert!((adjusted[&deck("Rb Aggro")] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn field_ev_weighs_each_matchup_by_its_share_of_the_field() {
+        let games: Vec<GameLog> = (0..3).map(|_| test_game(1)).collect();
+        let shares = field_shares(&games);
+        assert_eq!(shares[&deck("Rb Aggro")], 0.5);
+        // the mirror is even; 3 - 0 with the prior's 1 - 1 added is 80%
+        let ev = field_ev(&build_matchups(&games), &shares, deck("Rb Aggro"));
+        assert!((ev - (0.5 * 0.5 + 0.5 * 0.8)).abs() < 1e-9, "{}", ev);
+        // a reported match is in the field once, not once for each player
+        let both = [games.clone(), vec![test_game(1).complement().unwrap()]].concat();
+        assert_eq!(field_shares(&both), shares);
+    }
+
+    #[test]
+    fn p_value_is_the_two_sided_binomial_test() {
+        let p_value = |win, loss| {
+            Matchup {
+                win,
+                loss,
+                ..Matchup::new(deck("Rb Aggro"), deck("Grixis Midrange"))
+            }
+            .p_value()
+        };
+        // 2 * P(X <= 2) for X ~ Binomial(10, 0.5) is 2 * 56 / 1024
+        assert!((p_value(8, 2) - 0.109375).abs() < 1e-12);
+        assert_eq!(p_value(2, 8), p_value(8, 2));
+        assert!((p_value(10, 0) - 2.0 / 1024.0).abs() < 1e-12);
+        // an even record, or none, can't be told from a coin flip
+        assert_eq!(p_value(5, 5), 1.0);
+        assert_eq!(p_value(0, 0), 1.0);
+        assert_eq!(
+            Matchup {
+                win: 10,
+                loss: 0,
+                ..Matchup::new(deck("Rb Aggro"), deck("Grixis Midrange"))
+            }
+            .significance_marker(),
+            "**"
+        );
+    }
+
+    #[test]
+    fn undo_takes_back_both_sides_of_an_added_match() {
+        let dir = std::env::temp_dir().join(format!("protour-undo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("games.csv");
+        let load = || store::load_games(&data, &Aliases::default()).unwrap();
+        let rows = |games: &[GameLog]| -> Vec<(u32, String)> {
+            games.iter().map(|game| (game.id, game.key())).collect()
+        };
+        let earlier = GameLog {
+            date: NaiveDate::from_ymd_opt(2026, 10, 6),
+            opponent: None,
+            ..test_game(1)
+        };
+        store::save_games(&data, &[earlier]).unwrap();
+        let before = rows(&load());
+
+        let roster = ["Grant".to_string(), "Noah".to_string()];
+        let game = GameLog {
+            id: 0,
+            ..test_game(1)
+        };
+        assert_eq!(
+            add_game(&data, load(), game, &roster, "add", None)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(undo_latest(&data, load()).unwrap().len(), 2);
+        assert_eq!(rows(&load()), before);
+        // the undo cancelled the whole add, leaving nothing else to undo
+        assert!(undo_latest(&data, load()).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn complement_is_the_opponents_side() {
+        let game = test_game(1);
+        let other = game.complement().unwrap();
+        assert_eq!(other.player.as_str(), "Noah");
+        assert_eq!(other.deck.as_str(), "Grixis Midrange");
+        assert_eq!(other.opp_deck.as_str(), "Rb Aggro");
+        assert_eq!((other.won, other.lost), (1, 2));
+        assert_eq!(other.opponent.as_deref(), Some("Grant"));
+        assert_eq!(other.reported_by.as_deref(), Some("Grant"));
+        assert!(other.is_complement());
+        assert!(!game.is_complement());
+        assert!(GameLog {
+            opponent: None,
+            ..test_game(1)
+        }
+        .complement()
+        .is_none());
+    }
+
+    #[test]
+    fn a_reported_match_counts_once() {
+        let game = test_game(1);
+        let games = vec![game.clone(), game.complement().unwrap()];
+        let matchups = build_matchups(&games);
+        let matchup = matchups[&(deck("Rb Aggro"), deck("Grixis Midrange"))];
+        assert_eq!((matchup.win, matchup.loss), (1, 0));
+        assert_eq!(deck_vs_field(&matchups, deck("Grixis Midrange")), (0, 1));
+    }
+
+    #[test]
+    fn a_match_both_players_reported_counts_once() {
+        let mut grant = test_game(1);
+        let mut noah = grant.complement().unwrap();
+        let (report, other) = (noah.clone(), grant.clone());
+        conflicts::merge(
+            &mut noah,
+            &GameLog {
+                reported_by: None,
+                ..report
+            },
+        );
+        conflicts::merge(
+            &mut grant,
+            &GameLog {
+                reported_by: Some("Noah".into()),
+                ..other
+            },
+        );
+        assert!(grant.reported_by.is_none() && noah.reported_by.is_none());
+        let matchups = build_matchups(&[grant, noah]);
+        let matchup = matchups[&(deck("Rb Aggro"), deck("Grixis Midrange"))];
+        assert_eq!((matchup.win, matchup.loss), (1, 0));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::event::{Events, Format};
+use crate::{sample, ColorIdentity, Deck, GameLog};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[derive(Default, Clone, Copy)]
+struct Record {
+    wins: u32,
+    losses: u32,
+}
+
+impl Record {
+    fn win_rate(&self) -> f64 {
+        self.wins as f64 / (self.wins + self.losses).max(1) as f64
+    }
+
+    fn add(&mut self, won: bool) {
+        if won {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.wins + self.losses;
+        write!(
+            f,
+            "{} - {} ({:.1}%{})",
+            self.wins,
+            self.losses,
+            self.win_rate() * 100.0,
+            sample::marker(total)
+        )
+    }
+}
+
+/// The colors a player registered for the game's event, or failing that the
+/// colors of the deck they logged.
+fn colors(game: &GameLog, events: &Events) -> Option<ColorIdentity> {
+    let registered = events
+        .iter()
+        .find(|(name, _)| game.event.as_ref() == Some(*name))
+        .and_then(|(_, event)| event.registrations.get(game.player.as_str()));
+    let deck = registered.map_or(game.deck.as_str(), String::as_str);
+    Deck::from_str(deck).ok().map(|deck| deck.0)
+}
+
+/// Win rates of each color combination across every `format` event, overall
+/// and month by month, and how each sealed pool did. Matches between two decks
+/// of the same colors are left out of the color win rates.
+pub fn print(games: &[GameLog], events: &Events, format: Format) {
+    let in_format = |game: &&GameLog| {
+        game.event
+            .as_ref()
+            .and_then(|name| events.iter().find(|(event, _)| *event == name))
+            .is_some_and(|(_, event)| event.format == format)
+    };
+    let mut overall: BTreeMap<ColorIdentity, Record> = BTreeMap::new();
+    let mut monthly: BTreeMap<ColorIdentity, BTreeMap<String, Record>> = BTreeMap::new();
+    // each player's colors in each event
+    let mut played: BTreeMap<&str, BTreeMap<&str, ColorIdentity>> = BTreeMap::new();
+    let mut entries: BTreeMap<(&str, &str), Record> = BTreeMap::new();
+    games
+        .iter()
+        .filter(in_format)
+        .filter(|game| !game.is_draw())
+        .for_each(|game| {
+            let Some(colors) = colors(game, events) else {
+                return;
+            };
+            if let Some(event) = &game.event {
+                played
+                    .entry(&game.player)
+                    .or_default()
+                    .insert(event, colors);
+                entries
+                    .entry((event, &game.player))
+                    .or_default()
+                    .add(game.won > game.lost);
+            }
+            let opponent = Deck::from_str(&game.opp_deck).ok().map(|deck| deck.0);
+            if opponent == Some(colors) {
+                return;
+            }
+            let won = game.won > game.lost;
+            overall.entry(colors).or_default().add(won);
+            let month = game.date.map_or_else(
+                || "undated".to_string(),
+                |date| date.format("%Y-%m").to_string(),
+            );
+            monthly
+                .entry(colors)
+                .or_default()
+                .entry(month)
+                .or_default()
+                .add(won);
+        });
+    if overall.is_empty() {
+        println!("No {} games recorded yet.", format);
+        return;
+    }
+
+    let mut ranked: Vec<(&ColorIdentity, &Record)> = overall.iter().collect();
+    ranked.sort_by(|(_, a), (_, b)| b.win_rate().total_cmp(&a.win_rate()));
+    println!("Win rate by colors:");
+    ranked.iter().for_each(|(colors, record)| {
+        println!("  {}: {}", colors, record);
+        monthly[colors]
+            .iter()
+            .for_each(|(month, record)| println!("    {}: {}", month, record));
+    });
+
+    println!("\nColors played:");
+    played.iter().for_each(|(player, events)| {
+        let mut counts: BTreeMap<ColorIdentity, u32> = BTreeMap::new();
+        events
+            .values()
+            .for_each(|colors| *counts.entry(*colors).or_default() += 1);
+        let counts: Vec<String> = counts
+            .iter()
+            .map(|(colors, count)| format!("{} x{}", colors, count))
+            .collect();
+        println!("  {}: {}", player, counts.join(", "));
+    });
+
+    let pools: Vec<String> = events
+        .iter()
+        .filter(|(_, event)| event.format == format)
+        .flat_map(|(name, event)| {
+            let entries = &entries;
+            event.pools.iter().map(move |(player, pool)| {
+                let colors = event.registrations.get(player).map_or("?", String::as_str);
+                let record = entries
+                    .get(&(name.as_str(), player.as_str()))
+                    .copied()
+                    .unwrap_or_default();
+                format!(
+                    "  {} pool {}: {} ({}) {}",
+                    name, pool, player, colors, record
+                )
+            })
+        })
+        .collect();
+    if !pools.is_empty() {
+        println!("\nPools:");
+        pools.iter().for_each(|line| println!("{}", line));
+    }
+}
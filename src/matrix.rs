@@ -0,0 +1,114 @@
+use crate::{Deck, Matchup};
+use csv::Writer;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Wins and losses for every (row, column) pairing of a matchup grid.
+pub type Matrix<K> = BTreeMap<(K, K), (u32, u32)>;
+
+/// Rolls deck-level matchups up to whatever level `key` groups decks by,
+/// leaving out decks whose archetype isn't known.
+pub fn aggregate<K: Ord + Clone>(
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    key: impl Fn(Deck) -> K,
+) -> Matrix<K> {
+    let mut matrix = Matrix::new();
+    let known = matchups
+        .values()
+        .filter(|matchup| matchup.knows_archetypes());
+    known.for_each(|matchup| {
+        let cell = matrix
+            .entry((key(matchup.deck), key(matchup.opponent)))
+            .or_insert((0, 0));
+        cell.0 += matchup.win;
+        cell.1 += matchup.loss;
+    });
+    matrix
+}
+
+pub fn labels<K: Ord + Clone>(matrix: &Matrix<K>) -> BTreeSet<K> {
+    matrix
+        .keys()
+        .flat_map(|(row, col)| [row.clone(), col.clone()])
+        .collect()
+}
+
+pub fn print<K: Ord + Clone + Display>(matrix: &Matrix<K>) {
+    let labels = labels(matrix);
+    let names: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+    let row_width = names.iter().map(String::len).max().unwrap_or(0);
+    let cells: Vec<Vec<String>> = labels
+        .iter()
+        .map(|row| {
+            labels
+                .iter()
+                .map(|col| match matrix.get(&(row.clone(), col.clone())) {
+                    Some((win, loss)) => format!("{}-{}", win, loss),
+                    None => ".".to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            cells
+                .iter()
+                .map(|row| row[i].len())
+                .chain([name.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut header = format!("{:row_width$}", "");
+    names
+        .iter()
+        .zip(&widths)
+        .for_each(|(name, width)| header += &format!("  {:>width$}", name));
+    println!("{}", header);
+    names.iter().zip(&cells).for_each(|(name, row)| {
+        let mut line = format!("{:row_width$}", name);
+        row.iter()
+            .zip(&widths)
+            .for_each(|(cell, width)| line += &format!("  {:>width$}", cell));
+        println!("{}", line);
+    });
+}
+
+/// Writes the grid with a win rate and a game count column per opponent, so
+/// every cell stays numeric when pasted into a spreadsheet.
+pub fn write_csv<K: Ord + Clone + Display>(
+    matrix: &Matrix<K>,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let labels = labels(matrix);
+    let mut writer = Writer::from_path(path)?;
+    let mut header = vec![String::new()];
+    labels.iter().for_each(|label| {
+        header.push(format!("{} win rate", label));
+        header.push(format!("{} games", label));
+    });
+    writer.write_record(&header)?;
+    for row in &labels {
+        let mut record = vec![row.to_string()];
+        labels
+            .iter()
+            .for_each(|col| match matrix.get(&(row.clone(), col.clone())) {
+                Some((win, loss)) if win + loss > 0 => {
+                    record.push(format!("{:.3}", *win as f64 / (win + loss) as f64));
+                    record.push((win + loss).to_string());
+                }
+                _ => {
+                    record.push(String::new());
+                    record.push("0".to_string());
+                }
+            });
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Metrics defined in the config over each deck's record, e.g.
+//!
+//! ```toml
+//! [metrics]
+//! aggro_index = "wins_vs_aggro / games_vs_aggro"
+//! ```
+//!
+//! Each is a rhai expression that sees the deck's `wins`, `losses`, and
+//! `games` against the field, mirrors excluded, and the same split by the
+//! opposing deck's archetype and color identity: `wins_vs_control`,
+//! `losses_vs_esper`, `games_vs_5c`, and so on. Every count is a float, so
+//! `/` divides as expected; a metric that comes out infinite or NaN (say,
+//! with no games to divide by) is shown as missing.
+
+use crate::{Archetype, ColorIdentity, Deck, Matchup};
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::collections::BTreeMap;
+use std::error::Error;
+use strum::IntoEnumIterator;
+
+pub struct Metrics {
+    engine: Engine,
+    definitions: Vec<(String, AST)>,
+}
+
+/// `name` as it appears in a variable: lowercased, with anything but letters
+/// and digits made an underscore.
+fn variable(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn push_record(scope: &mut Scope, suffix: &str, (wins, losses): (u32, u32)) {
+    scope.push_constant(format!("wins{}", suffix), wins as f64);
+    scope.push_constant(format!("losses{}", suffix), losses as f64);
+    scope.push_constant(format!("games{}", suffix), (wins + losses) as f64);
+}
+
+impl Metrics {
+    /// Compiles the configured definitions, by name.
+    pub fn new(definitions: &BTreeMap<String, String>) -> Result<Self, Box<dyn Error>> {
+        let engine = Engine::new();
+        let definitions = definitions
+            .iter()
+            .map(|(name, expression)| {
+                engine
+                    .compile_expression(expression)
+                    .map(|ast| (name.clone(), ast))
+                    .map_err(|err| format!("metric {}: {}", name, err))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            engine,
+            definitions,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.definitions.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Every metric's value for `deck`, in the order of `names`.
+    pub fn deck(
+        &self,
+        matchups: &BTreeMap<(Deck, Deck), Matchup>,
+        deck: Deck,
+    ) -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        if self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let field: Vec<&Matchup> = matchups
+            .values()
+            .filter(|matchup| matchup.deck == deck && matchup.opponent != deck)
+            .collect();
+        let record = |vs: &dyn Fn(Deck) -> bool| {
+            field
+                .iter()
+                .filter(|matchup| vs(matchup.opponent))
+                .fold((0, 0), |(w, l), matchup| {
+                    (w + matchup.win, l + matchup.loss)
+                })
+        };
+        let mut scope = Scope::new();
+        push_record(&mut scope, "", record(&|_| true));
+        Archetype::iter().for_each(|archetype| {
+            let suffix = format!("_vs_{}", variable(&archetype.to_string()));
+            push_record(
+                &mut scope,
+                &suffix,
+                record(&|opponent| opponent.1 == Some(archetype)),
+            );
+        });
+        ColorIdentity::iter().for_each(|color| {
+            let suffix = format!("_vs_{}", variable(&color.to_string()));
+            push_record(&mut scope, &suffix, record(&|opponent| opponent.0 == color));
+        });
+
+        self.definitions
+            .iter()
+            .map(|(name, ast)| {
+                let value: Dynamic = self
+                    .engine
+                    .eval_ast_with_scope(&mut scope.clone(), ast)
+                    .map_err(|err| format!("metric {}: {}", name, err))?;
+                let value = value
+                    .as_float()
+                    .or_else(|_| value.as_int().map(|value| value as f64))
+                    .map_err(|kind| format!("metric {} is a {}, not a number", name, kind))?;
+                Ok(Some(value).filter(|value| value.is_finite()))
+            })
+            .collect()
+    }
+}
+
+/// A metric's value as shown in text reports.
+pub fn display(value: Option<f64>) -> String {
+    value.map_or("-".to_string(), |value| format!("{:.2}", value))
+}
@@ -0,0 +1,141 @@
+//! Magic Online game logs (`Match_GameLog_*.dat`).
+//!
+//! The files are binary, but the game events are stored as readable text such
+//! as `@PAlice chooses to play first.` or `@PBob has conceded from the game.`,
+//! which is all this importer relies on. The logs don't say which decks were
+//! played, so those come from the command line.
+
+use crate::config::Aliases;
+use crate::GameLog;
+use chrono::{DateTime, Local};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const GAME_STARTS: [&str; 2] = ["chooses to play", "chooses to draw"];
+const WINS: [&str; 1] = ["wins the game"];
+const LOSSES: [&str; 3] = [
+    "has conceded from the game",
+    "has lost the game",
+    "has lost connection to the game",
+];
+
+enum Event {
+    GameStart,
+    Won(String),
+    Lost(String),
+}
+
+/// Runs of printable text in the log, which is where the events live.
+fn text_runs(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .split(|byte| !(byte.is_ascii_graphic() || *byte == b' '))
+        .filter(|run| run.len() > 3)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect()
+}
+
+/// Splits `@Pname rest of sentence` into the name and the rest.
+fn actor(text: &str) -> Option<(&str, &str)> {
+    let text = &text[text.find("@P")? + 2..];
+    let end = text.find(' ')?;
+    Some((&text[..end], text[end..].trim()))
+}
+
+fn events(runs: &[String]) -> (Vec<String>, Vec<Event>) {
+    let mut players: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+    for (name, rest) in runs.iter().filter_map(|run| actor(run)) {
+        if !players.iter().any(|player| player == name) {
+            players.push(name.to_string());
+        }
+        if GAME_STARTS.iter().any(|event| rest.starts_with(event)) {
+            events.push(Event::GameStart);
+        } else if WINS.iter().any(|event| rest.starts_with(event)) {
+            events.push(Event::Won(name.to_string()));
+        } else if LOSSES.iter().any(|event| rest.starts_with(event)) {
+            events.push(Event::Lost(name.to_string()));
+        }
+    }
+    (players, events)
+}
+
+/// Winner of each game in the match. A concession is usually followed by a
+/// win message, so only the first result after each game start counts; logs
+/// without start markers count every result.
+fn game_winners(players: &[String], events: &[Event]) -> Vec<String> {
+    let has_starts = events.iter().any(|event| matches!(event, Event::GameStart));
+    let mut decided = false;
+    let mut winners = Vec::new();
+    for event in events {
+        let winner = match event {
+            Event::GameStart => {
+                decided = false;
+                continue;
+            }
+            Event::Won(name) => Some(name.clone()),
+            Event::Lost(name) => players.iter().find(|player| *player != name).cloned(),
+        };
+        if let Some(winner) = winner.filter(|_| !decided) {
+            winners.push(winner);
+            decided = has_starts;
+        }
+    }
+    winners
+}
+
+/// Reads one match log as a game record from the point of view of `me` (an
+/// MTGO username), or of whichever player maps to someone on the roster.
+pub fn read_game(
+    path: &Path,
+    me: Option<&str>,
+    deck: &str,
+    opp_deck: &str,
+    aliases: &Aliases,
+    roster: &[String],
+) -> Result<GameLog, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    let (players, events) = events(&text_runs(&bytes));
+    if players.len() != 2 {
+        return Err(format!(
+            "{}: expected a two-player match, found players {:?}",
+            path.display(),
+            players
+        )
+        .into());
+    }
+    let me = match me {
+        Some(me) => players
+            .iter()
+            .find(|player| *player == me)
+            .ok_or_else(|| format!("{}: {} didn't play in this match", path.display(), me))?,
+        None => players
+            .iter()
+            .find(|player| roster.contains(&aliases.player(player)))
+            .ok_or_else(|| {
+                format!(
+                    "{}: neither {} is on the roster, pass --as or add a player alias",
+                    path.display(),
+                    players.join(" nor ")
+                )
+            })?,
+    };
+    let opponent = players.iter().find(|player| *player != me).unwrap();
+    let winners = game_winners(&players, &events);
+    let won = winners.iter().filter(|winner| *winner == me).count() as u32;
+    let date = fs::metadata(path)?
+        .modified()
+        .map(|modified| DateTime::<Local>::from(modified).date_naive())
+        .ok();
+
+    Ok(GameLog {
+        date,
+        player: aliases.player(me).into(),
+        deck: aliases.deck(deck).into(),
+        won,
+        lost: winners.len() as u32 - won,
+        opp_deck: aliases.deck(opp_deck).into(),
+        notes: format!("MTGO vs {}", opponent),
+        ..GameLog::default()
+    })
+}
@@ -0,0 +1,66 @@
+use crate::{sample, Deck, GameLog};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// mulligan counts from here up share a column
+const MOST_MULLIGANS: u32 = 2;
+
+/// Win-loss records of one deck by mulligans taken, from none up to `MOST_MULLIGANS`.
+type Records = [(u32, u32); MOST_MULLIGANS as usize + 1];
+
+fn record(records: &mut BTreeMap<Deck, Records>, deck: &str, mulligans: Option<u32>, won: bool) {
+    let (Ok(deck), Some(mulligans)) = (Deck::from_str(deck), mulligans) else {
+        return;
+    };
+    let record = &mut records.entry(deck).or_default()[mulligans.min(MOST_MULLIGANS) as usize];
+    if won {
+        record.0 += 1;
+    } else {
+        record.1 += 1;
+    }
+}
+
+fn column((wins, losses): (u32, u32)) -> String {
+    if wins + losses == 0 {
+        return "-".to_string();
+    }
+    format!(
+        "{}-{} ({:.0}%{})",
+        wins,
+        losses,
+        wins as f64 / (wins + losses) as f64 * 100.0,
+        sample::marker(wins + losses)
+    )
+}
+
+/// Each deck's match record by how many times its pilot mulliganed, from
+/// both sides of every match where the count was recorded.
+pub fn print(games: &[GameLog]) {
+    let mut records: BTreeMap<Deck, Records> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw() && !game.is_complement())
+        .for_each(|game| {
+            let won = game.won > game.lost;
+            record(&mut records, &game.deck, game.mulligans(), won);
+            record(&mut records, &game.opp_deck, game.opp_mulligans, !won);
+        });
+    if records.is_empty() {
+        println!("No mulligans recorded yet.");
+        return;
+    }
+    let headings: String = (0..=MOST_MULLIGANS)
+        .map(|mulligans| match mulligans {
+            MOST_MULLIGANS => format!(" {:>14}", format!("{}+ mulls", mulligans)),
+            _ => format!(" {:>14}", format!("{} mulls", mulligans)),
+        })
+        .collect();
+    println!("{:<18}{}", "Deck", headings);
+    records.iter().for_each(|(deck, records)| {
+        let columns: String = records
+            .iter()
+            .map(|record| format!(" {:>14}", column(*record)))
+            .collect();
+        println!("{:<18}{}", deck.to_string(), columns)
+    });
+}
@@ -0,0 +1,255 @@
+//! Lightweight annotations written into a game's notes, e.g. `t:5 flood mull:2`.
+
+use crate::{sample, Deck, GameLog};
+use clap::Args;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// What a game's notes say about how it went; words that aren't annotations are ignored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Annotations {
+    /// Turn the deciding game ended on, from `t:5`
+    pub turns: Option<u32>,
+    pub flood: bool,
+    pub screw: bool,
+    /// Mulligans taken, from `mull:2` (a bare `mull` is one)
+    pub mulligans: Option<u32>,
+}
+
+impl Annotations {
+    pub fn parse(notes: &str) -> Self {
+        let mut annotations = Self::default();
+        for word in notes.split_whitespace() {
+            let word = word
+                .trim_matches(|c: char| c == ',' || c == ';' || c == '.')
+                .to_lowercase();
+            match word.split_once(':') {
+                Some(("t", turns)) => annotations.turns = turns.parse().ok(),
+                Some(("mull", count)) => annotations.mulligans = count.parse().ok(),
+                _ => match word.as_str() {
+                    "flood" => annotations.flood = true,
+                    "screw" => annotations.screw = true,
+                    "mull" => annotations.mulligans = Some(1),
+                    _ => {}
+                },
+            }
+        }
+        annotations
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Turn(u32);
+
+impl std::fmt::Display for Turn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        format!("Turn {}", self.0).fmt(f)
+    }
+}
+
+/// The words of some notes, lowercased and without punctuation; an annotation
+/// like `mull:2` is tagged with its key.
+pub fn tags(notes: &str) -> BTreeSet<String> {
+    notes
+        .split_whitespace()
+        .map(|word| {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != ':');
+            let tag = word.split_once(':').map_or(word, |(key, _)| key);
+            tag.to_lowercase()
+        })
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Restricts reports to games by the tags in their notes.
+#[derive(Debug, Default, Args)]
+pub struct TagFilter {
+    /// Only games tagged with this word in their notes (`mull` also matches
+    /// `mull:2`). Repeat to require every tag (AND); separate tags with commas
+    /// to accept any of them (OR), e.g. `--tag flood,screw --tag mull`
+    #[arg(long, global = true)]
+    pub tag: Vec<String>,
+    /// Leave out games tagged with this word; repeated or comma-separated tags
+    /// leave out games with any of them
+    #[arg(long, global = true)]
+    pub not_tag: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_empty() && self.not_tag.is_empty()
+    }
+
+    pub fn matches(&self, game: &GameLog) -> bool {
+        let tags = tags(&game.notes);
+        let any = |spec: &String| {
+            spec.split(',')
+                .any(|tag| tags.contains(&tag.trim().to_lowercase()))
+        };
+        self.tag.iter().all(any) && !self.not_tag.iter().any(any)
+    }
+}
+
+fn percent(wins: u32, losses: u32) -> f64 {
+    wins as f64 / (wins + losses).max(1) as f64 * 100.0
+}
+
+fn print_records<K: std::fmt::Display>(title: &str, records: BTreeMap<K, (u32, u32)>) {
+    println!("{}:", title);
+    if records.is_empty() {
+        println!("  no annotated games yet");
+    }
+    records.iter().for_each(|(key, (wins, losses))| {
+        println!(
+            "  {:<10} {:>3} - {:<3} {:>5.1}%{}",
+            key,
+            wins,
+            losses,
+            percent(*wins, *losses),
+            sample::marker(wins + losses)
+        )
+    });
+}
+
+/// Match win rates by how long the match went and by mana trouble.
+pub fn print(games: &[GameLog]) {
+    let mut by_turns: BTreeMap<Turn, (u32, u32)> = BTreeMap::new();
+    let mut by_mana: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw())
+        .for_each(|game| {
+            let annotations = Annotations::parse(&game.notes);
+            let mut record = |record: &mut (u32, u32)| {
+                if game.won > game.lost {
+                    record.0 += 1;
+                } else {
+                    record.1 += 1;
+                }
+            };
+            if let Some(turns) = annotations.turns {
+                record(by_turns.entry(Turn(turns)).or_default());
+            }
+            let mana = match (annotations.flood, annotations.screw) {
+                (true, true) => "Both",
+                (true, false) => "Flood",
+                (false, true) => "Screw",
+                (false, false) => "Neither",
+            };
+            record(by_mana.entry(mana).or_default());
+        });
+    print_records("Win rate by turns", by_turns);
+    println!();
+    print_records("Win rate by mana issues", by_mana);
+}
+
+// words too common in notes to say anything about a game
+const STOPWORDS: [&str; 32] = [
+    "the", "and", "was", "were", "but", "for", "with", "his", "her", "their", "they", "had", "has",
+    "off", "out", "into", "then", "than", "from", "that", "this", "game", "games", "got", "just",
+    "lot", "felt", "very", "really", "not", "deck", "play",
+];
+// games a term has to appear in to be reported
+const MIN_TERM_GAMES: u32 = 3;
+// added to every count, so terms seen only in wins or only in losses get a
+// finite log-odds
+const PRIOR: f64 = 0.5;
+
+/// The z-score of a term's log-odds ratio of appearing in won rather than
+/// lost games: positive for terms that go with winning.
+fn log_odds(wins: u32, losses: u32, won: u32, lost: u32) -> f64 {
+    let (a, b) = (wins as f64 + PRIOR, (won - wins) as f64 + PRIOR);
+    let (c, d) = (losses as f64 + PRIOR, (lost - losses) as f64 + PRIOR);
+    let ratio = (a / b).ln() - (c / d).ln();
+    ratio / (1.0 / a + 1.0 / b + 1.0 / c + 1.0 / d).sqrt()
+}
+
+/// For each deck, the words in its pilots' notes that go most with winning
+/// and with losing, by log-odds. Only the player's own notes count: a game
+/// recorded for them by their opponent carries the opponent's notes.
+pub fn print_terms(games: &[GameLog], deck: Option<Deck>, count: usize) {
+    // per deck: games won and lost, and each term's games won and lost
+    type Counts<'a> = ((u32, u32), BTreeMap<String, (u32, u32)>);
+    let mut decks: BTreeMap<Deck, Counts> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw() && game.reported_by.is_none())
+        .for_each(|game| {
+            let Ok(played) = Deck::from_str(&game.deck) else {
+                return;
+            };
+            if deck.is_some_and(|deck| deck != played) {
+                return;
+            }
+            let won = game.won > game.lost;
+            let ((wins, losses), terms) = decks.entry(played).or_default();
+            if won {
+                *wins += 1;
+            } else {
+                *losses += 1;
+            }
+            tags(&game.notes)
+                .into_iter()
+                .filter(|term| term.len() > 2 && !term.chars().all(|c| c.is_ascii_digit()))
+                .filter(|term| !STOPWORDS.contains(&term.as_str()))
+                .for_each(|term| {
+                    let record = terms.entry(term).or_default();
+                    if won {
+                        record.0 += 1;
+                    } else {
+                        record.1 += 1;
+                    }
+                });
+        });
+
+    let mut printed = false;
+    for (played, ((won, lost), terms)) in &decks {
+        let mut scored: Vec<(&str, u32, f64)> = terms
+            .iter()
+            .filter(|(_, (wins, losses))| wins + losses >= MIN_TERM_GAMES)
+            .map(|(term, (wins, losses))| {
+                let z = log_odds(*wins, *losses, *won, *lost);
+                (term.as_str(), wins + losses, z)
+            })
+            .collect();
+        if scored.is_empty() {
+            continue;
+        }
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+        let describe = |terms: Vec<&(&str, u32, f64)>| {
+            terms
+                .iter()
+                .map(|(term, games, z)| format!("{} ({} games, {:+.1})", term, games, z))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let winning: Vec<_> = scored
+            .iter()
+            .filter(|(_, _, z)| *z > 0.0)
+            .take(count)
+            .collect();
+        let losing: Vec<_> = scored
+            .iter()
+            .rev()
+            .filter(|(_, _, z)| *z < 0.0)
+            .take(count)
+            .collect();
+        if printed {
+            println!();
+        }
+        printed = true;
+        println!("{} ({} - {}):", played, won, lost);
+        if !winning.is_empty() {
+            println!("  In wins:   {}", describe(winning));
+        }
+        if !losing.is_empty() {
+            println!("  In losses: {}", describe(losing));
+        }
+    }
+    if !printed {
+        println!(
+            "No word appears in the notes of {} games of one deck yet.",
+            MIN_TERM_GAMES
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Writes printable PDFs by hand: US Letter pages of Courier text, filled
+//! squares, and QR codes drawn from them, which is all a printout to post at
+//! an event needs.
+
+use qrcode::{Color, QrCode};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+pub const WIDTH: f64 = 612.0;
+pub const HEIGHT: f64 = 792.0;
+pub const MARGIN: f64 = 54.0;
+const TITLE_SIZE: f64 = 16.0;
+const TEXT_SIZE: f64 = 10.0;
+const LEADING: f64 = 14.0;
+const CODE_SIZE: f64 = 72.0;
+const CODE_GAP: f64 = 12.0;
+
+#[derive(Debug, Default)]
+pub struct Page {
+    content: String,
+}
+
+/// Text as a PDF string in the font's WinAnsi encoding; anything outside
+/// Latin-1 becomes `?`.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            '\u{a0}'..='\u{ff}' => format!("\\{:03o}", c as u32),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+impl Page {
+    /// Text with its baseline `y` points up from the bottom of the page.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, text: &str) {
+        let _ = writeln!(
+            self.content,
+            "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET",
+            size,
+            x,
+            y,
+            escape(text)
+        );
+    }
+
+    /// A black square with its lower left corner at `x`, `y`.
+    pub fn square(&mut self, x: f64, y: f64, size: f64) {
+        let _ = writeln!(
+            self.content,
+            "{:.2} {:.2} {:.2} {:.2} re f",
+            x, y, size, size
+        );
+    }
+
+    /// A QR code of `data`, `size` points square, with its lower left corner
+    /// at `x`, `y`.
+    pub fn qr_code(&mut self, x: f64, y: f64, size: f64, data: &str) -> Result<(), Box<dyn Error>> {
+        let code = QrCode::new(data)?;
+        let width = code.width();
+        let module = size / width as f64;
+        code.to_colors()
+            .iter()
+            .enumerate()
+            .filter(|(_, color)| **color == Color::Dark)
+            .for_each(|(i, _)| {
+                let (row, column) = (i / width, i % width);
+                self.square(
+                    x + column as f64 * module,
+                    y + size - (row + 1) as f64 * module,
+                    module,
+                )
+            });
+        Ok(())
+    }
+}
+
+/// Like `text_pages`, with a QR code of each line's link beside it.
+pub fn coded_pages(
+    title: &str,
+    rows: &[(String, Option<String>)],
+) -> Result<Vec<Page>, Box<dyn Error>> {
+    let top = HEIGHT - MARGIN - TITLE_SIZE;
+    let per_page = ((top - MARGIN - LEADING) / (CODE_SIZE + CODE_GAP)) as usize;
+    let mut pages = Vec::new();
+    for chunk in rows.chunks(per_page.max(1)) {
+        let mut page = Page::default();
+        page.text(MARGIN, top, TITLE_SIZE, title);
+        for (i, (line, link)) in chunk.iter().enumerate() {
+            let bottom = top - LEADING - (i + 1) as f64 * (CODE_SIZE + CODE_GAP);
+            if let Some(link) = link {
+                page.qr_code(MARGIN, bottom, CODE_SIZE, link)?;
+            }
+            let x = MARGIN + CODE_SIZE + CODE_GAP;
+            page.text(x, bottom + CODE_SIZE / 2.0, TEXT_SIZE, line);
+        }
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// The title atop each page and the lines flowed onto as many pages as they
+/// take.
+pub fn text_pages(title: &str, lines: &[String]) -> Vec<Page> {
+    let per_page = ((HEIGHT - 2.0 * MARGIN - 2.0 * LEADING) / LEADING) as usize;
+    let chunks: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(per_page).collect()
+    };
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            let mut page = Page::default();
+            let top = HEIGHT - MARGIN - TITLE_SIZE;
+            page.text(MARGIN, top, TITLE_SIZE, title);
+            chunk.iter().enumerate().for_each(|(i, line)| {
+                let y = top - 2.0 * LEADING - i as f64 * LEADING;
+                page.text(MARGIN, y, TEXT_SIZE, line)
+            });
+            page
+        })
+        .collect()
+}
+
+pub fn write(pages: &[Page], path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    // objects 1-3 are the catalog, page tree, and font; each page then takes
+    // two, itself and its content stream
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", 4 + 2 * i))
+        .collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    pages.iter().enumerate().for_each(|(i, page)| {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            WIDTH,
+            HEIGHT,
+            5 + 2 * i
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            page.content.len(),
+            page.content
+        ));
+    });
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    objects.iter().enumerate().for_each(|(i, object)| {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    });
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    offsets
+        .iter()
+        .for_each(|offset| _ = writeln!(pdf, "{:010} 00000 n ", offset));
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    fs::write(path, pdf)?;
+    Ok(())
+}
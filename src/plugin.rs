@@ -0,0 +1,90 @@
+//! WASM plugins that compute extra metrics from the games.
+//!
+//! A plugin is a WASM module (binary or `.wat` text) exporting:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning where to write `len` bytes of input
+//! - `metrics(ptr: i32, len: i32) -> i64`, given the games as a JSON array
+//!   (the data file's columns, after aliases are applied) and returning where
+//!   its output is as `ptr << 32 | len`
+//!
+//! The output is a JSON object of metric names to values, e.g.
+//! `{"Longest game": 11}`. Plugins get no imports, so they can't reach the
+//! filesystem or network.
+
+use crate::GameLog;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+use wasmi::{Engine, Linker, Module, Store};
+
+pub type Metrics = BTreeMap<String, Value>;
+
+/// Runs one plugin over the games.
+pub fn run(path: &Path, games: &[GameLog]) -> Result<Metrics, Box<dyn Error>> {
+    let plugin_error = |err: &dyn std::fmt::Display| format!("plugin {}: {}", path.display(), err);
+    let wasm = fs::read(path).map_err(|err| plugin_error(&err))?;
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).map_err(|err| plugin_error(&err))?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::<()>::new(&engine)
+        .instantiate_and_start(&mut store, &module)
+        .map_err(|err| plugin_error(&err))?;
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| plugin_error(&"doesn't export its memory"))?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|err| plugin_error(&err))?;
+    let metrics = instance
+        .get_typed_func::<(i32, i32), i64>(&store, "metrics")
+        .map_err(|err| plugin_error(&err))?;
+
+    let input = serde_json::to_vec(games)?;
+    let len = i32::try_from(input.len()).map_err(|err| plugin_error(&err))?;
+    let ptr = alloc
+        .call(&mut store, len)
+        .map_err(|err| plugin_error(&err))?;
+    memory
+        .write(&mut store, ptr as u32 as usize, &input)
+        .map_err(|err| plugin_error(&err))?;
+    let output = metrics
+        .call(&mut store, (ptr, len))
+        .map_err(|err| plugin_error(&err))? as u64;
+    let mut bytes = vec![0; (output & 0xffff_ffff) as usize];
+    memory
+        .read(&store, (output >> 32) as usize, &mut bytes)
+        .map_err(|err| plugin_error(&err))?;
+    let metrics: Metrics = serde_json::from_slice(&bytes).map_err(|err| plugin_error(&err))?;
+    info!(plugin = %path.display(), metrics = metrics.len(), "ran plugin");
+    Ok(metrics)
+}
+
+/// Every configured plugin's metrics merged together; a later plugin's
+/// metric replaces an earlier one's of the same name.
+pub fn run_all(paths: &[impl AsRef<Path>], games: &[GameLog]) -> Result<Metrics, Box<dyn Error>> {
+    let mut merged = Metrics::new();
+    for path in paths {
+        for (name, value) in run(path.as_ref(), games)? {
+            if merged.insert(name.clone(), value).is_some() {
+                warn!(metric = name, plugin = %path.as_ref().display(), "plugin replaced a metric");
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// A metric's value as shown in text reports.
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => match number.as_f64() {
+            Some(number) if number.fract() != 0.0 => format!("{:.3}", number),
+            _ => number.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,230 @@
+//! Approximate deck costs from Scryfall's card prices. A deck is priced by
+//! the list most recently attached to one of its games; each card's price is
+//! cached and fetched again once it's a week old.
+
+use crate::cache::Cache;
+use crate::decklist::Decklists;
+use crate::{http, ratings, sample, Deck, GameLog, Matchup};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+const COLLECTION_URL: &str = "https://api.scryfall.com/cards/collection";
+// the most cards Scryfall looks up in one request
+const BATCH: usize = 75;
+// how long a cached price is used before it's fetched again
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+#[derive(Deserialize)]
+struct Collection {
+    data: Vec<ScryfallCard>,
+}
+
+#[derive(Deserialize)]
+struct ScryfallCard {
+    name: String,
+    prices: ScryfallPrices,
+}
+
+#[derive(Deserialize)]
+struct ScryfallPrices {
+    usd: Option<String>,
+    usd_foil: Option<String>,
+}
+
+/// Prices of a batch of cards from Scryfall, by lowercased name. Cards it
+/// doesn't know are left out. Lists name double-faced cards by their front
+/// face, so each card is also found by that.
+async fn fetch(
+    client: &http::Client,
+    names: &[&str],
+) -> Result<BTreeMap<String, Option<f64>>, Box<dyn Error>> {
+    let identifiers: Vec<serde_json::Value> = names
+        .iter()
+        .map(|name| serde_json::json!({ "name": name }))
+        .collect();
+    let body = serde_json::json!({ "identifiers": identifiers }).to_string();
+    let response = client.post_json(COLLECTION_URL, &body).await?;
+    let collection: Collection = serde_json::from_slice(&response)?;
+    let mut prices = BTreeMap::new();
+    collection.data.into_iter().for_each(|card| {
+        let usd = card
+            .prices
+            .usd
+            .or(card.prices.usd_foil)
+            .and_then(|usd| usd.parse().ok());
+        let name = card.name.to_lowercase();
+        if let Some((front, _)) = name.split_once(" // ") {
+            prices.insert(front.to_string(), usd);
+        }
+        prices.insert(name, usd);
+    });
+    Ok(prices)
+}
+
+/// What a deck's list costs to build in paper.
+#[derive(Debug, Clone)]
+pub struct DeckCost {
+    pub usd: f64,
+    /// The game whose list was priced
+    pub list: u32,
+    /// Cards in the list without a price, which the cost leaves out
+    pub unpriced: u32,
+}
+
+/// The cost of each deck with a list attached to one of its games, fetching
+/// any prices missing from the cache or out of date. Offline, or if Scryfall
+/// can't be reached, older cached prices are used.
+pub fn costs(
+    data: &Path,
+    games: &[GameLog],
+    offline: bool,
+) -> Result<BTreeMap<Deck, DeckCost>, Box<dyn Error>> {
+    let lists = Decklists::load(data)?;
+    let mut latest: BTreeMap<Deck, u32> = BTreeMap::new();
+    ratings::chronological(games)
+        .into_iter()
+        .filter(|game| lists.get(game.id).is_some())
+        .for_each(|game| {
+            if let Ok(deck) = Deck::from_str(&game.deck) {
+                latest.insert(deck, game.id);
+            }
+        });
+    if latest.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    // each card's cheapest price, or None for cards without one
+    let cache = Cache::new(data, offline);
+    let key = |name: &str| format!("scryfall price {}", name);
+    let mut prices: BTreeMap<&str, Option<f64>> = BTreeMap::new();
+    let mut stale = Vec::new();
+    let names: BTreeSet<&str> = latest
+        .values()
+        .filter_map(|id| lists.get(*id))
+        .flat_map(|list| list.main.iter().chain(&list.sideboard))
+        .map(|card| card.name.as_str())
+        .collect();
+    names.into_iter().for_each(|name| {
+        let cached = cache
+            .fresh(&key(name), MAX_AGE)
+            .and_then(|body| serde_json::from_slice(&body).ok());
+        match cached {
+            Some(usd) => {
+                prices.insert(name, usd);
+            }
+            None => stale.push(name),
+        }
+    });
+    if !stale.is_empty() && !cache.offline {
+        // each batch is cached as it comes in, so a failure keeps the ones before
+        let runtime = tokio::runtime::Runtime::new()?;
+        let client = http::Client::new()?;
+        for batch in stale.chunks(BATCH) {
+            let fetched = match runtime.block_on(fetch(&client, batch)) {
+                Ok(fetched) => fetched,
+                Err(err) => {
+                    warn!(
+                        "couldn't fetch card prices from Scryfall, using older ones: {}",
+                        err
+                    );
+                    break;
+                }
+            };
+            for name in batch {
+                let usd = fetched.get(&name.to_lowercase()).copied().flatten();
+                cache.put(&key(name), serde_json::to_string(&usd)?.as_bytes())?;
+                prices.insert(name, usd);
+            }
+        }
+    }
+    for name in stale {
+        if prices.contains_key(name) {
+            continue;
+        }
+        let cached = cache
+            .get(&key(name))
+            .and_then(|(body, _)| serde_json::from_slice(&body).ok());
+        if let Some(usd) = cached {
+            prices.insert(name, usd);
+        }
+    }
+
+    Ok(latest
+        .into_iter()
+        .filter_map(|(deck, id)| {
+            let list = lists.get(id)?;
+            let mut cost = DeckCost {
+                usd: 0.0,
+                list: id,
+                unpriced: 0,
+            };
+            list.main.iter().chain(&list.sideboard).for_each(|card| {
+                match prices.get(card.name.as_str()).copied().flatten() {
+                    Some(usd) => cost.usd += usd * card.count as f64,
+                    None => cost.unpriced += card.count,
+                }
+            });
+            Some((deck, cost))
+        })
+        .collect())
+}
+
+/// Describes a deck's cost, e.g. "$182.40 (list from #12, 4 cards unpriced)".
+pub fn describe(cost: &DeckCost) -> String {
+    let unpriced = match cost.unpriced {
+        0 => String::new(),
+        1 => ", 1 card unpriced".to_string(),
+        n => format!(", {} cards unpriced", n),
+    };
+    format!("${:.2} (list from #{}{})", cost.usd, cost.list, unpriced)
+}
+
+/// Decks ranked by how much win rate against the field they buy per dollar,
+/// among those with a priced list and at least `min_games` decided games.
+pub fn budget(
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    costs: &BTreeMap<Deck, DeckCost>,
+    min_games: u32,
+) {
+    let mut ranked: Vec<(Deck, f64, u32, &DeckCost)> = costs
+        .iter()
+        .filter(|(_, cost)| cost.usd > 0.0)
+        .filter_map(|(deck, cost)| {
+            let (win, loss) = matchups
+                .values()
+                .filter(|matchup| matchup.deck == *deck && matchup.opponent != *deck)
+                .fold((0, 0), |(w, l), matchup| {
+                    (w + matchup.win, l + matchup.loss)
+                });
+            if win + loss < min_games.max(1) {
+                return None;
+            }
+            Some((*deck, win as f64 / (win + loss) as f64, win + loss, cost))
+        })
+        .collect();
+    if ranked.is_empty() {
+        println!("No deck with a priced list has enough games to rank; attach lists with `decklist attach`.");
+        return;
+    }
+    ranked.sort_by(|a, b| (b.1 / b.3.usd).total_cmp(&(a.1 / a.3.usd)));
+    println!("Budget leaderboard, win rate per $100:");
+    ranked
+        .iter()
+        .enumerate()
+        .for_each(|(i, (deck, rate, games, cost))| {
+            println!(
+                "  {}. {:<18} {:>6.1}  {:.1}%{} for {}",
+                i + 1,
+                deck.to_string(),
+                rate * 100.0 / cost.usd * 100.0,
+                rate * 100.0,
+                sample::marker(*games),
+                describe(cost)
+            )
+        });
+}
@@ -0,0 +1,26 @@
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use std::io::IsTerminal;
+
+/// A bar counting `len` rows for ingest-heavy commands, hidden unless stdout is
+/// a terminal so piped and scripted output stays clean.
+pub fn rows(len: u64, verb: &str) -> ProgressBar {
+    if !std::io::stdout().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    // cleared when dropped, including when the command bails out partway
+    let bar = ProgressBar::new(len)
+        .with_prefix(verb.to_string())
+        .with_finish(ProgressFinish::AndClear);
+    bar.set_style(
+        ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} rows, {msg} (ETA {eta})")
+            .expect("progress template is valid")
+            .progress_chars("=> "),
+    );
+    errors(&bar, 0);
+    bar
+}
+
+/// Shows how many rows have failed so far.
+pub fn errors(bar: &ProgressBar, count: usize) {
+    bar.set_message(format!("{} errors", count));
+}
@@ -0,0 +1,610 @@
+use crate::backtest::{self, Prediction};
+use crate::config::{LeagueConfig, RatingsConfig};
+use crate::event::{Events, Format};
+use crate::{league, Deck, GameLog};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+#[derive(Debug, Subcommand)]
+pub enum RatingsCommand {
+    /// Replay the history, predicting every match before it's rated, and
+    /// score how well each rating system predicted them
+    Evaluate,
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum System {
+    /// Head-to-head Elo; multiplayer pods aren't rated
+    #[default]
+    Elo,
+    /// TrueSkill, which also rates multiplayer pods
+    Trueskill,
+}
+
+/// Which games ratings are computed from, for e.g. sealed-only or
+/// this-season-only ratings; `--tag` and `--not-tag` narrow them too, so
+/// `--tag paper` rates paper games alone.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Filter {
+    /// Leave out mirror matches
+    #[arg(long)]
+    pub no_mirrors: bool,
+    /// Only rate games of this format; games outside events are constructed.
+    /// Repeat to rate several formats together
+    #[arg(long, value_enum)]
+    pub format: Vec<Format>,
+    /// Leave out games of this format
+    #[arg(long, value_enum)]
+    pub not_format: Vec<Format>,
+    /// Only rate games of the configured league season
+    #[arg(long)]
+    pub season: bool,
+}
+
+impl Filter {
+    pub fn apply(
+        &self,
+        mut games: Vec<GameLog>,
+        events: &Events,
+        league: Option<&LeagueConfig>,
+    ) -> Result<Vec<GameLog>, Box<dyn Error>> {
+        if self.no_mirrors {
+            games.retain(|game| {
+                !matches!(
+                    (game.deck.parse::<Deck>(), game.opp_deck.parse::<Deck>()),
+                    (Ok(deck), Ok(opponent)) if deck == opponent
+                )
+            });
+        }
+        if !self.format.is_empty() {
+            games.retain(|game| self.format.contains(&events.format(game)));
+        }
+        games.retain(|game| !self.not_format.contains(&events.format(game)));
+        if self.season {
+            let league = league.ok_or("--season needs a [league] section in the config")?;
+            games.retain(|game| {
+                game.date
+                    .is_some_and(|date| league::in_season(league, date))
+            });
+        }
+        Ok(games)
+    }
+}
+
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Games in the order they were played; undated games are the oldest.
+pub fn chronological(games: &[GameLog]) -> Vec<&GameLog> {
+    let mut games: Vec<&GameLog> = games.iter().collect();
+    games.sort_by_key(|game| game.date);
+    games
+}
+
+/// When each player last played a dated game.
+pub fn last_played(games: &[GameLog]) -> BTreeMap<String, NaiveDate> {
+    let mut last: BTreeMap<String, NaiveDate> = BTreeMap::new();
+    games
+        .iter()
+        .filter_map(|game| Some((&game.player, game.date?)))
+        .for_each(|(player, date)| {
+            let entry = last.entry(player.to_string()).or_insert(date);
+            *entry = (*entry).max(date);
+        });
+    last
+}
+
+/// Idle weeks between two games past the point where decay sets in.
+fn decay_weeks(config: &RatingsConfig, from: Option<NaiveDate>, to: Option<NaiveDate>) -> f64 {
+    let (Some(after), Some(from), Some(to)) = (config.decay_after_weeks, from, to) else {
+        return 0.0;
+    };
+    let weeks = (to - from).num_days() / 7;
+    (weeks - after as i64).max(0) as f64
+}
+
+fn decay_elo(rating: &mut f64, weeks: f64, config: &RatingsConfig) {
+    if *rating > INITIAL_RATING {
+        *rating = (*rating - weeks * config.decay_per_week).max(INITIAL_RATING);
+    }
+}
+
+fn decay_skill(skill: &mut Skill, weeks: f64, config: &RatingsConfig) {
+    skill.sigma = (skill.sigma.powi(2) + weeks * config.sigma_per_week.powi(2))
+        .sqrt()
+        .min(SIGMA);
+}
+
+pub fn expected(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+fn score(game: &GameLog) -> f64 {
+    if game.won > game.lost {
+        1.0
+    } else if game.won < game.lost {
+        0.0
+    } else {
+        0.5
+    }
+}
+
+/// Elo rating of every player after all of `games`, decayed up to today.
+///
+/// A match against a recorded opponent is rated against their rating at the
+/// time, updating both players once however many rows it has; one against
+/// someone off the roster is rated against an average (initial-rated)
+/// opponent.
+pub fn elo(games: &[GameLog], config: &RatingsConfig) -> BTreeMap<String, f64> {
+    replay_elo(games, config, |_, _, _| {})
+}
+
+/// Every rated game in the order it was played, with its player's Elo just after it.
+pub fn elo_history<'a>(games: &'a [GameLog], config: &RatingsConfig) -> Vec<(&'a GameLog, f64)> {
+    let mut history = Vec::new();
+    replay_elo(games, config, |game, _, rating| {
+        history.push((game, rating))
+    });
+    history
+}
+
+/// Each rated game's player's Elo just before it, and the win probability it
+/// gave them, by game id.
+pub fn elo_before(games: &[GameLog], config: &RatingsConfig) -> BTreeMap<u32, (f64, f64)> {
+    let mut before = BTreeMap::new();
+    replay_elo(games, config, |game, expected, rating| {
+        let rating = rating - config.k_factor * (score(game) - expected);
+        before.insert(game.id, (rating, expected));
+    });
+    before
+}
+
+/// A match as one of its rows tells it: the date, player, opponent, decks,
+/// and score.
+type Side<'a> = (
+    Option<NaiveDate>,
+    &'a str,
+    &'a str,
+    &'a str,
+    &'a str,
+    u32,
+    u32,
+);
+
+fn side(game: &GameLog) -> Side<'_> {
+    (
+        game.date,
+        &game.player,
+        game.opponent.as_deref().unwrap_or_default(),
+        &game.deck,
+        &game.opp_deck,
+        game.won,
+        game.lost,
+    )
+}
+
+/// The match as the opponent's row of it tells it.
+fn other_side(game: &GameLog) -> Side<'_> {
+    (
+        game.date,
+        game.opponent.as_deref().unwrap_or_default(),
+        &game.player,
+        &game.opp_deck,
+        &game.deck,
+        game.lost,
+        game.won,
+    )
+}
+
+/// The head-to-head games of `games` in the order they're rated: as played,
+/// with each day's complement rows after the rows their matches are rated
+/// from.
+fn rating_order(games: &[GameLog]) -> Vec<&GameLog> {
+    let mut order: Vec<&GameLog> = chronological(games)
+        .into_iter()
+        .filter(|game| game.pod.is_none())
+        .collect();
+    order.sort_by_key(|game| (game.date, game.is_complement()));
+    order
+}
+
+/// The opponent a game is rated against as well as for, if it recorded one.
+fn rated_opponent(game: &GameLog) -> Option<&str> {
+    game.opponent
+        .as_deref()
+        .filter(|opponent| *opponent != game.player.as_str() && !game.is_complement())
+}
+
+/// Rates `games` in order, telling `observe` each game's predicted win
+/// probability before it's rated and its player's rating after. The opponent's
+/// row of a match rated from the other row is observed as rated there.
+fn replay_elo<'a>(
+    games: &'a [GameLog],
+    config: &RatingsConfig,
+    mut observe: impl FnMut(&'a GameLog, f64, f64),
+) -> BTreeMap<String, f64> {
+    let mut ratings: BTreeMap<String, f64> = BTreeMap::new();
+    let mut last: BTreeMap<&str, NaiveDate> = BTreeMap::new();
+    // how each rated match went for the opponent, until their row of it comes up
+    let mut rated: BTreeMap<Side, Vec<(f64, f64)>> = BTreeMap::new();
+    // a player's rating as of `date`, decayed for the weeks they sat out
+    let mut current = |ratings: &mut BTreeMap<String, f64>, player: &'a str, date| {
+        let rating = ratings.entry(player.to_string()).or_insert(INITIAL_RATING);
+        let idle = decay_weeks(config, last.get(player).copied(), date);
+        decay_elo(rating, idle, config);
+        if let Some(date) = date {
+            last.insert(player, date);
+        }
+        *rating
+    };
+    for game in rating_order(games) {
+        if game.is_complement() {
+            if let Some((expected, rating)) = rated.get_mut(&side(game)).and_then(Vec::pop) {
+                observe(game, expected, rating);
+                continue;
+            }
+        }
+        let rating = current(&mut ratings, &game.player, game.date);
+        let opponent = rated_opponent(game)
+            .map(|opponent| (opponent, current(&mut ratings, opponent, game.date)));
+        let expected = expected(
+            rating,
+            opponent.map_or(INITIAL_RATING, |(_, rating)| rating),
+        );
+        let change = config.k_factor * (score(game) - expected);
+        ratings.insert(game.player.to_string(), rating + change);
+        observe(game, expected, rating + change);
+        if let Some((opponent, rating)) = opponent {
+            ratings.insert(opponent.to_string(), rating - change);
+            rated
+                .entry(other_side(game))
+                .or_default()
+                .push((1.0 - expected, rating - change));
+        }
+    }
+    let today = Some(Local::now().date_naive());
+    ratings.iter_mut().for_each(|(player, rating)| {
+        let idle = decay_weeks(config, last.get(player.as_str()).copied(), today);
+        decay_elo(rating, idle, config);
+    });
+    ratings
+}
+
+/// A TrueSkill skill estimate: mean and standard deviation.
+#[derive(Debug, Clone, Copy)]
+pub struct Skill {
+    pub mu: f64,
+    pub sigma: f64,
+}
+
+const MU: f64 = 25.0;
+const SIGMA: f64 = MU / 3.0;
+// performance variation within a single game
+const BETA: f64 = SIGMA / 2.0;
+// skill drift between games, so sigma never collapses to nothing
+const TAU: f64 = SIGMA / 100.0;
+
+impl Default for Skill {
+    fn default() -> Self {
+        Self {
+            mu: MU,
+            sigma: SIGMA,
+        }
+    }
+}
+
+impl Skill {
+    /// Conservative estimate the player is very likely above, used for ranking.
+    pub fn rating(&self) -> f64 {
+        self.mu - 3.0 * self.sigma
+    }
+}
+
+/// Complementary error function (Numerical Recipes' Chebyshev fit, accurate to ~1e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+fn pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn cdf(x: f64) -> f64 {
+    erfc(-x / std::f64::consts::SQRT_2) / 2.0
+}
+
+/// Chance a player with `skill` beats one with `opponent`'s.
+fn win_probability(skill: &Skill, opponent: &Skill) -> f64 {
+    let c = (2.0 * BETA.powi(2) + skill.sigma.powi(2) + opponent.sigma.powi(2)).sqrt();
+    cdf((skill.mu - opponent.mu) / c)
+}
+
+/// Updates both skills after `winner` beat `loser`.
+fn beat(winner: &mut Skill, loser: &mut Skill) {
+    winner.sigma = (winner.sigma.powi(2) + TAU.powi(2)).sqrt();
+    loser.sigma = (loser.sigma.powi(2) + TAU.powi(2)).sqrt();
+    let c = (2.0 * BETA.powi(2) + winner.sigma.powi(2) + loser.sigma.powi(2)).sqrt();
+    let t = (winner.mu - loser.mu) / c;
+    let v = pdf(t) / cdf(t).max(f64::MIN_POSITIVE);
+    let w = v * (v + t);
+    winner.mu += winner.sigma.powi(2) / c * v;
+    loser.mu -= loser.sigma.powi(2) / c * v;
+    winner.sigma *= (1.0 - winner.sigma.powi(2) / c.powi(2) * w).max(0.0).sqrt();
+    loser.sigma *= (1.0 - loser.sigma.powi(2) / c.powi(2) * w).max(0.0).sqrt();
+}
+
+/// TrueSkill estimate of every player after all of `games`, with uncertainty
+/// grown for idle weeks up to today.
+///
+/// Head-to-head matches are rated like Elo: against the recorded opponent,
+/// once per match, or an average one. A pod is rated as every winning seat
+/// beating every losing seat; losing seats tell us nothing about each other.
+pub fn trueskill(games: &[GameLog], config: &RatingsConfig) -> BTreeMap<String, Skill> {
+    replay_trueskill(games, config, |_, _| {})
+}
+
+/// The win probability TrueSkill gave each head-to-head game's player before
+/// it, by game id.
+pub fn trueskill_before(games: &[GameLog], config: &RatingsConfig) -> BTreeMap<u32, f64> {
+    let mut before = BTreeMap::new();
+    replay_trueskill(games, config, |game, probability| {
+        before.insert(game.id, probability);
+    });
+    before
+}
+
+/// Rates `games` in order, telling `observe` each head-to-head game's
+/// predicted win probability before it's rated. The opponent's row of a match
+/// rated from the other row is observed as predicted there.
+fn replay_trueskill<'a>(
+    games: &'a [GameLog],
+    config: &RatingsConfig,
+    mut observe: impl FnMut(&'a GameLog, f64),
+) -> BTreeMap<String, Skill> {
+    let mut skills: BTreeMap<String, Skill> = BTreeMap::new();
+    let mut last: BTreeMap<&str, NaiveDate> = BTreeMap::new();
+    let mut pods: BTreeMap<&str, Vec<&GameLog>> = BTreeMap::new();
+    let mut order: Vec<Vec<&GameLog>> = rating_order(games)
+        .into_iter()
+        .map(|game| vec![game])
+        .collect();
+    chronological(games).into_iter().for_each(|game| {
+        if let Some(pod) = &game.pod {
+            pods.entry(pod).or_default().push(game);
+        }
+    });
+    // a pod is played when its first seat was
+    order.extend(pods.into_values());
+    order.sort_by_key(|seats| seats[0].date);
+    // each rated match's prediction for the opponent, until their row of it comes up
+    let mut rated: BTreeMap<Side, Vec<f64>> = BTreeMap::new();
+
+    for seats in order {
+        if let [game] = seats[..] {
+            if game.is_complement() {
+                if let Some(probability) = rated.get_mut(&side(game)).and_then(Vec::pop) {
+                    observe(game, probability);
+                    continue;
+                }
+            }
+        }
+        let opponent = match seats[..] {
+            [game] => rated_opponent(game),
+            _ => None,
+        };
+        let players = seats
+            .iter()
+            .map(|game| game.player.as_str())
+            .chain(opponent);
+        for player in players {
+            let date = seats[0].date;
+            let idle = decay_weeks(config, last.get(player).copied(), date);
+            decay_skill(skills.entry(player.to_string()).or_default(), idle, config);
+            if let Some(date) = date {
+                last.insert(player, date);
+            }
+        }
+        if let [game] = seats[..] {
+            if game.pod.is_none() {
+                let mut skill = skills[game.player.as_str()];
+                let mut other = opponent.map_or_else(Skill::default, |opponent| skills[opponent]);
+                let probability = win_probability(&skill, &other);
+                observe(game, probability);
+                match score(game) {
+                    s if s > 0.5 => beat(&mut skill, &mut other),
+                    s if s < 0.5 => beat(&mut other, &mut skill),
+                    _ => {}
+                }
+                skills.insert(game.player.to_string(), skill);
+                if let Some(opponent) = opponent {
+                    skills.insert(opponent.to_string(), other);
+                    rated
+                        .entry(other_side(game))
+                        .or_default()
+                        .push(1.0 - probability);
+                }
+                continue;
+            }
+        }
+        let (winners, losers): (Vec<&GameLog>, Vec<&GameLog>) =
+            seats.iter().partition(|game| game.won > game.lost);
+        for winner in &winners {
+            for loser in &losers {
+                let mut w = skills
+                    .get(winner.player.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                let mut l = skills
+                    .get(loser.player.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                beat(&mut w, &mut l);
+                skills.insert(winner.player.to_string(), w);
+                skills.insert(loser.player.to_string(), l);
+            }
+        }
+    }
+    let today = Some(Local::now().date_naive());
+    skills.iter_mut().for_each(|(player, skill)| {
+        let idle = decay_weeks(config, last.get(player.as_str()).copied(), today);
+        decay_skill(skill, idle, config);
+    });
+    skills
+}
+
+/// The roster ordered by rating, best first; unrated players start at the initial rating.
+pub fn ranked(ratings: &BTreeMap<String, f64>, players: &[String]) -> Vec<(String, f64)> {
+    let mut ranked: Vec<(String, f64)> = players
+        .iter()
+        .map(|player| {
+            let rating = ratings.get(player).copied().unwrap_or(INITIAL_RATING);
+            (player.clone(), rating)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+pub fn print(system: System, games: &[GameLog], roster: &[String], config: &RatingsConfig) {
+    let last = last_played(games);
+    let last_played = |player: &str| {
+        last.get(player)
+            .map_or_else(|| "never".to_string(), |date| date.to_string())
+    };
+    match system {
+        System::Elo => {
+            println!("Elo ratings:");
+            ranked(&elo(games, config), roster)
+                .iter()
+                .enumerate()
+                .for_each(|(i, (player, rating))| {
+                    println!(
+                        "  {:>2}. {:<10} {:>4.0}  last played {}",
+                        i + 1,
+                        player,
+                        rating,
+                        last_played(player)
+                    )
+                });
+        }
+        System::Trueskill => {
+            let skills = trueskill(games, config);
+            let mut ranked: Vec<(&String, Skill)> = roster
+                .iter()
+                .map(|player| (player, skills.get(player).copied().unwrap_or_default()))
+                .collect();
+            ranked.sort_by(|a, b| b.1.rating().total_cmp(&a.1.rating()));
+            println!("TrueSkill ratings (mu - 3 sigma):");
+            ranked.iter().enumerate().for_each(|(i, (player, skill))| {
+                println!(
+                    "  {:>2}. {:<10} {:5.1}  ({:.1} ± {:.1})  last played {}",
+                    i + 1,
+                    player,
+                    skill.rating(),
+                    skill.mu,
+                    skill.sigma,
+                    last_played(player)
+                )
+            });
+        }
+    }
+}
+
+/// A pre-game prediction worth scoring: decided head-to-head matches only.
+fn prediction(game: &GameLog, probability: f64) -> Option<Prediction> {
+    (game.pod.is_none() && !game.is_draw()).then_some(Prediction {
+        probability,
+        won: game.won > game.lost,
+    })
+}
+
+/// Scores every system's pre-game predictions against always predicting a coin flip.
+pub fn evaluate(games: &[GameLog], config: &RatingsConfig) {
+    let mut elo = Vec::new();
+    replay_elo(games, config, |game, probability, _| {
+        elo.extend(prediction(game, probability))
+    });
+    let mut trueskill = Vec::new();
+    replay_trueskill(games, config, |game, probability| {
+        trueskill.extend(prediction(game, probability))
+    });
+    let coin_flip: Vec<Prediction> = elo
+        .iter()
+        .map(|prediction| Prediction {
+            probability: 0.5,
+            ..*prediction
+        })
+        .collect();
+
+    backtest::print_scores(&[
+        ("Elo", elo.clone()),
+        ("TrueSkill", trueskill.clone()),
+        ("Coin flip", coin_flip),
+    ]);
+    println!();
+    backtest::print_calibration("Elo", &elo);
+    println!();
+    backtest::print_calibration("TrueSkill", &trueskill);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    #[test]
+    fn elo_rates_a_match_once_against_the_opponent() {
+        let config = RatingsConfig::default();
+        let game = test_game(1);
+        let games = vec![game.clone(), game.complement().unwrap()];
+        let ratings = elo(&games, &config);
+        let change = config.k_factor * 0.5;
+        assert_eq!(ratings["Grant"], INITIAL_RATING + change);
+        assert_eq!(ratings["Noah"], INITIAL_RATING - change);
+
+        let history = elo_history(&games, &config);
+        assert_eq!(history.len(), 2);
+        let before = elo_before(&games, &config);
+        assert_eq!(before[&1], (INITIAL_RATING, 0.5));
+    }
+
+    #[test]
+    fn elo_rates_an_unknown_opponent_as_average() {
+        let config = RatingsConfig::default();
+        let game = GameLog {
+            opponent: None,
+            ..test_game(1)
+        };
+        let ratings = elo(&[game], &config);
+        assert_eq!(ratings["Grant"], INITIAL_RATING + config.k_factor * 0.5);
+        assert!(!ratings.contains_key("Noah"));
+    }
+
+    #[test]
+    fn trueskill_rates_a_match_once_against_the_opponent() {
+        let config = RatingsConfig::default();
+        let game = test_game(1);
+        let skills = trueskill(&[game.clone(), game.complement().unwrap()], &config);
+        let (mut winner, mut loser) = (Skill::default(), Skill::default());
+        beat(&mut winner, &mut loser);
+        assert_eq!(skills["Grant"].mu, winner.mu);
+        assert_eq!(skills["Noah"].mu, loser.mu);
+    }
+}
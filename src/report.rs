@@ -0,0 +1,139 @@
+use crate::achievements::{self, Earned};
+use crate::{deck_vs_field, player_wins_losses, sample, Deck, GameLog, Matchup};
+use crate::{metrics, plugin};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use tera::{Context, Tera};
+
+#[derive(Debug, Serialize)]
+pub struct Record {
+    pub name: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub games: u32,
+    pub win_rate: Option<f64>,
+    /// Too few games for the win rate to be quoted without a caveat
+    pub small_sample: bool,
+}
+
+impl Record {
+    fn new(name: impl ToString, wins: u32, losses: u32) -> Self {
+        let games = wins + losses;
+        Self {
+            name: name.to_string(),
+            wins,
+            losses,
+            games,
+            win_rate: (games > 0).then(|| wins as f64 / games as f64),
+            small_sample: sample::is_small(games),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchupRecord {
+    pub deck: String,
+    pub opponent: String,
+    #[serde(flatten)]
+    pub record: Record,
+}
+
+/// Everything a report template can see:
+///
+/// - `games`: every logged game (`date`, `player`, `deck`, `won`, `lost`, `opp_deck`, `notes`)
+/// - `matchups`: every deck-vs-deck record between decks with known archetypes (`deck`, `opponent`, `wins`, `losses`, `games`, `win_rate`, `small_sample`)
+/// - `decks`: each piloted deck's record vs. the field, mirrors excluded
+/// - `players`: each roster player's overall record
+/// - `achievements`: each roster player's achievements (`achievement`, `game`, `date`), keyed by name
+/// - `plugins`: every configured plugin's metrics, keyed by name
+/// - `metrics`: each piloted deck's configured metrics, keyed by deck and then
+///   metric name; a metric without a value is left out
+#[derive(Debug, Serialize)]
+pub struct ReportContext<'a> {
+    pub games: &'a [GameLog],
+    pub matchups: Vec<MatchupRecord>,
+    pub decks: Vec<Record>,
+    pub players: Vec<Record>,
+    pub achievements: BTreeMap<String, Vec<Earned>>,
+    pub plugins: plugin::Metrics,
+    pub metrics: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl<'a> ReportContext<'a> {
+    pub fn new(
+        games: &'a [GameLog],
+        matchups: &BTreeMap<(Deck, Deck), Matchup>,
+        roster: &[String],
+    ) -> Self {
+        let player_decks: BTreeSet<Deck> = games
+            .iter()
+            .flat_map(|game| Deck::from_str(&game.deck))
+            .collect();
+        Self {
+            games,
+            matchups: matchups
+                .values()
+                .filter(|matchup| matchup.knows_archetypes())
+                .map(|matchup| MatchupRecord {
+                    deck: matchup.deck.to_string(),
+                    opponent: matchup.opponent.to_string(),
+                    record: Record::new(matchup.deck, matchup.win, matchup.loss),
+                })
+                .collect(),
+            decks: player_decks
+                .into_iter()
+                .map(|deck| {
+                    let (wins, losses) = deck_vs_field(matchups, deck);
+                    Record::new(deck, wins, losses)
+                })
+                .collect(),
+            players: roster
+                .iter()
+                .map(|player| {
+                    let (wins, losses) = player_wins_losses(games, player);
+                    Record::new(player, wins, losses)
+                })
+                .collect(),
+            achievements: roster
+                .iter()
+                .map(|player| (player.clone(), achievements::earned(games, player, roster)))
+                .collect(),
+            plugins: plugin::Metrics::new(),
+            metrics: BTreeMap::new(),
+        }
+    }
+
+    /// Works out the configured metrics for every deck in `decks`.
+    pub fn add_metrics(
+        &mut self,
+        custom: &metrics::Metrics,
+        matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    ) -> Result<(), Box<dyn Error>> {
+        if custom.is_empty() {
+            return Ok(());
+        }
+        for record in &self.decks {
+            let deck = Deck::from_str(&record.name)?;
+            let values = custom
+                .names()
+                .zip(custom.deck(matchups, deck)?)
+                .filter_map(|(name, value)| Some((name.to_string(), value?)))
+                .collect();
+            self.metrics.insert(record.name.clone(), values);
+        }
+        Ok(())
+    }
+}
+
+pub fn render(
+    template: impl AsRef<Path>,
+    context: &ReportContext,
+) -> Result<String, Box<dyn Error>> {
+    let source = fs::read_to_string(template)?;
+    let context = Context::from_serialize(context)?;
+    Ok(Tera::one_off(&source, &context, false)?)
+}
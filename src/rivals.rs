@@ -0,0 +1,133 @@
+use crate::{ratings, sample, GameLog};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+// results shown as recent form
+const FORM: usize = 5;
+
+/// Every player's results against each other player, oldest first.
+///
+/// A match with a recorded opponent counts once, however many rows it has. In
+/// a pod every winning seat beat every losing seat, and losing seats didn't
+/// play each other.
+pub fn head_to_head(games: &[GameLog]) -> BTreeMap<(String, String), Vec<bool>> {
+    let mut pods: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut matches: Vec<Vec<&GameLog>> = Vec::new();
+    ratings::chronological(games)
+        .into_iter()
+        .for_each(|game| match game.pod.as_deref() {
+            Some(pod) => match pods.get(pod) {
+                Some(i) => matches[*i].push(game),
+                None => {
+                    pods.insert(pod, matches.len());
+                    matches.push(vec![game]);
+                }
+            },
+            None if game.opponent.is_some() && !game.is_complement() && !game.is_draw() => {
+                matches.push(vec![game])
+            }
+            None => {}
+        });
+    let mut results: BTreeMap<(String, String), Vec<bool>> = BTreeMap::new();
+    let mut beat = |winner: &str, loser: &str| {
+        results
+            .entry((winner.to_string(), loser.to_string()))
+            .or_default()
+            .push(true);
+        results
+            .entry((loser.to_string(), winner.to_string()))
+            .or_default()
+            .push(false);
+    };
+    for seats in matches {
+        if let [game] = seats[..] {
+            if let (None, Some(opponent)) = (&game.pod, &game.opponent) {
+                if game.won > game.lost {
+                    beat(&game.player, opponent);
+                } else {
+                    beat(opponent, &game.player);
+                }
+                continue;
+            }
+        }
+        let (winners, losers): (Vec<&GameLog>, Vec<&GameLog>) =
+            seats.iter().partition(|game| game.won > game.lost);
+        for winner in &winners {
+            for loser in &losers {
+                beat(&winner.player, &loser.player);
+            }
+        }
+    }
+    results
+}
+
+fn describe(opponent: &str, results: &[bool]) -> String {
+    let wins = results.iter().filter(|won| **won).count();
+    let losses = results.len() - wins;
+    let form: String = results[results.len().saturating_sub(FORM)..]
+        .iter()
+        .map(|won| if *won { 'W' } else { 'L' })
+        .collect();
+    format!(
+        "{} ({} - {}{}, recent {})",
+        opponent,
+        wins,
+        losses,
+        sample::marker(results.len() as u32),
+        form
+    )
+}
+
+/// Each player's nemesis, the opponent they're furthest behind against, and
+/// their customer, the one they're furthest ahead of.
+pub fn print(games: &[GameLog], roster: &[String]) {
+    let results = head_to_head(games);
+    if results.is_empty() {
+        println!("No head-to-head games recorded yet.");
+        return;
+    }
+    let margin = |results: &[bool]| {
+        let wins = results.iter().filter(|won| **won).count() as i64;
+        (2 * wins - results.len() as i64, results.len())
+    };
+    roster.iter().for_each(|player| {
+        let opponents: Vec<(&str, &[bool])> = results
+            .iter()
+            .filter(|((p, _), _)| p == player)
+            .map(|((_, opponent), results)| (opponent.as_str(), results.as_slice()))
+            .collect();
+        // ties go to whoever they've played most
+        let nemesis = opponents
+            .iter()
+            .filter(|(_, results)| margin(results).0 < 0)
+            .min_by_key(|(_, results)| (margin(results).0, Reverse(results.len())));
+        let customer = opponents
+            .iter()
+            .filter(|(_, results)| margin(results).0 > 0)
+            .max_by_key(|(_, results)| margin(results));
+        if nemesis.is_none() && customer.is_none() {
+            return;
+        }
+        println!("{}:", player);
+        if let Some((opponent, results)) = nemesis {
+            println!("  Nemesis: {}", describe(opponent, results));
+        }
+        if let Some((opponent, results)) = customer {
+            println!("  Customer: {}", describe(opponent, results));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    #[test]
+    fn a_match_with_an_opponent_counts_once() {
+        let game = test_game(1);
+        let results = head_to_head(&[game.clone(), game.complement().unwrap()]);
+        assert_eq!(results[&("Grant".to_string(), "Noah".to_string())], [true]);
+        assert_eq!(results[&("Noah".to_string(), "Grant".to_string())], [false]);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::config::Config;
+use crate::{crypt, sample, Deck, GameLog};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Subcommand)]
+pub enum RollCommand {
+    /// Give each player tonight a random deck from the configured deck_pool,
+    /// recording who got what; rolling again replaces tonight's decks
+    Decks {
+        #[arg(required = true)]
+        players: Vec<String>,
+        /// Favor the decks each player has played least
+        #[arg(long)]
+        least_played: bool,
+    },
+    /// The decks handed out tonight
+    Tonight,
+}
+
+/// The decks handed out each game night.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Rolls(BTreeMap<NaiveDate, BTreeMap<String, String>>);
+
+impl Rolls {
+    pub fn path_for(data: &Path) -> PathBuf {
+        crypt::plain_path(data).with_extension("rolls.json")
+    }
+
+    pub fn load(data: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path_for(data);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, data: &Path) -> Result<(), Box<dyn Error>> {
+        let path = Self::path_for(data);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Match record of whoever won the die roll.
+#[derive(Default)]
+struct Record {
+    wins: u32,
+    losses: u32,
+}
+
+impl Record {
+    fn add(&mut self, won: bool) {
+        if won {
+            self.wins += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.wins + self.losses;
+        write!(
+            f,
+            "{} - {} ({:.1}%{})",
+            self.wins,
+            self.losses,
+            self.wins as f64 / total.max(1) as f64 * 100.0,
+            sample::marker(total)
+        )
+    }
+}
+
+/// How the roll winner's matches went, overall and for each matchup. This is
+/// who chose to play or draw, not who was on the play in each game.
+pub fn print(games: &[GameLog]) {
+    let mut overall = Record::default();
+    // keyed by the roll winner's deck, then the deck they played against
+    let mut matchups: BTreeMap<(Deck, Deck), Record> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw() && game.pod.is_none() && !game.is_complement())
+        .for_each(|game| {
+            let Some(won_roll) = game.won_roll else {
+                return;
+            };
+            // the match from the roll winner's side
+            let won = (game.won > game.lost) == won_roll;
+            overall.add(won);
+            let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+            if let (Ok(deck), Ok(opponent)) = decks {
+                let key = if won_roll {
+                    (deck, opponent)
+                } else {
+                    (opponent, deck)
+                };
+                matchups.entry(key).or_default().add(won);
+            }
+        });
+    if overall.wins + overall.losses == 0 {
+        println!("No die rolls recorded yet.");
+        return;
+    }
+    println!("Roll winner's record: {}", overall);
+    println!("\nBy matchup (roll winner's deck first):");
+    matchups
+        .iter()
+        .for_each(|((deck, opponent), record)| println!("  {} vs {}: {}", deck, opponent, record));
+}
+
+/// How many more first games each player has been on the play for than on
+/// the draw, for handing out the play in formats that let it be chosen
+/// instead of rolled for.
+#[derive(Debug, Default)]
+pub struct PlayBalance(BTreeMap<String, i32>);
+
+impl PlayBalance {
+    pub fn new<'a>(games: impl IntoIterator<Item = &'a GameLog>) -> Self {
+        let mut balance = Self::default();
+        games
+            .into_iter()
+            .filter(|game| game.pod.is_none())
+            .for_each(|game| {
+                if let Some(on_play) = game.on_play {
+                    *balance.0.entry(game.player.to_string()).or_default() +=
+                        if on_play { 1 } else { -1 };
+                }
+            });
+        balance
+    }
+
+    /// Puts whoever has been on the play less first, to play first, and counts
+    /// it; the first player keeps the play on a tie.
+    pub fn assign(&mut self, player: String, opponent: String) -> (String, String) {
+        let balance = |name: &str| self.0.get(name).copied().unwrap_or(0);
+        let (first, second) = if balance(&opponent) < balance(&player) {
+            (opponent, player)
+        } else {
+            (player, opponent)
+        };
+        *self.0.entry(first.clone()).or_default() += 1;
+        *self.0.entry(second.clone()).or_default() -= 1;
+        (first, second)
+    }
+}
+
+/// A deck from the pool for each player, no two the same while the pool
+/// lasts. With `least_played`, each deck is weighted by one over one more
+/// than the games the player has played it.
+fn assign(
+    players: &[String],
+    pool: &[String],
+    games: &[GameLog],
+    least_played: bool,
+) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    let mut rng = rand::rng();
+    let mut assigned = BTreeMap::new();
+    let mut left: Vec<&String> = pool.iter().collect();
+    for player in players {
+        if left.is_empty() {
+            left = pool.iter().collect();
+        }
+        let played = |deck: &str| {
+            games
+                .iter()
+                .filter(|game| game.player == *player && game.deck.trim() == deck)
+                .count()
+        };
+        let deck = if least_played {
+            left.choose_weighted(&mut rng, |deck| 1.0 / (1 + played(deck)) as f64)?
+        } else {
+            left.choose(&mut rng).ok_or("the deck pool is empty")?
+        };
+        let deck = (*deck).clone();
+        left.retain(|other| **other != deck);
+        assigned.insert(player.clone(), deck);
+    }
+    Ok(assigned)
+}
+
+fn print_assignment(decks: &BTreeMap<String, String>) {
+    decks
+        .iter()
+        .for_each(|(player, deck)| println!("  {:<10} {}", player, deck));
+}
+
+pub fn run(
+    command: Option<RollCommand>,
+    data: &Path,
+    games: &[GameLog],
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let today = Local::now().date_naive();
+    match command {
+        None => print(games),
+        Some(RollCommand::Decks {
+            players,
+            least_played,
+        }) => {
+            if config.deck_pool.is_empty() {
+                return Err("no deck_pool in the config to roll decks from".into());
+            }
+            let pool: Vec<String> = config
+                .deck_pool
+                .iter()
+                .map(|deck| config.aliases.deck(deck))
+                .collect();
+            let players: Vec<String> = players
+                .iter()
+                .map(|player| config.aliases.player(player))
+                .collect();
+            let decks = assign(&players, &pool, games, least_played)?;
+            let mut rolls = Rolls::load(data)?;
+            let rerolled = rolls.0.insert(today, decks).is_some();
+            rolls.save(data)?;
+            let verb = if rerolled { "Rerolled" } else { "Rolled" };
+            println!("{} decks for {}:", verb, today);
+            print_assignment(&rolls.0[&today]);
+        }
+        Some(RollCommand::Tonight) => match Rolls::load(data)?.0.get(&today) {
+            Some(decks) => {
+                println!("Decks for {}:", today);
+                print_assignment(decks);
+            }
+            None => println!("No decks rolled for tonight yet; see `roll decks`"),
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Marks win rates drawn from too few games to quote without a caveat.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Follows a win rate from fewer games than the configured threshold.
+pub const MARKER: &str = "†";
+
+pub const DEFAULT_THRESHOLD: u32 = 10;
+
+static THRESHOLD: AtomicU32 = AtomicU32::new(DEFAULT_THRESHOLD);
+// whether anything printed so far was marked, so the footnote is only added when needed
+static MARKED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_threshold(games: u32) {
+    THRESHOLD.store(games, Ordering::Relaxed);
+}
+
+pub fn is_small(games: u32) -> bool {
+    games < THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// The marker for a win rate from `games` games, or nothing if there were enough.
+pub fn marker(games: u32) -> &'static str {
+    if is_small(games) {
+        MARKED.store(true, Ordering::Relaxed);
+        MARKER
+    } else {
+        ""
+    }
+}
+
+/// Explains the marker, if any printed win rate carried it.
+pub fn print_footnote() {
+    if MARKED.load(Ordering::Relaxed) {
+        println!(
+            "\n{} fewer than {} games",
+            MARKER,
+            THRESHOLD.load(Ordering::Relaxed)
+        );
+    }
+}
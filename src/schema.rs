@@ -0,0 +1,105 @@
+//! Versions of the data file's layout, and the migrations between them.
+//!
+//! Files are stamped with their version on a comment line above the header.
+//! Files from before the stamp existed are version 1.
+
+use csv::{Reader, ReaderBuilder, StringRecord, Writer};
+use std::borrow::Cow;
+use std::error::Error;
+use tracing::info;
+
+pub const VERSION: u32 = 2;
+const STAMP: &str = "# protour schema ";
+
+/// The rows of a data file as text, for migrations to rework.
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+type Migration = fn(&mut Table);
+
+/// Each migration upgrades a file from the version before it to its own.
+const MIGRATIONS: &[(u32, Migration)] = &[(2, number_games)];
+
+/// Version 2 gave every game a stable id; games are numbered in file order,
+/// the way they always were when loaded.
+fn number_games(table: &mut Table) {
+    if table.headers.iter().any(|header| header == "id") {
+        return;
+    }
+    table.headers.insert(0, "id".to_string());
+    table
+        .rows
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, row)| row.insert(0, (i + 1).to_string()));
+}
+
+/// A CSV reader that skips the version stamp.
+pub fn reader(contents: &[u8]) -> Reader<&[u8]> {
+    ReaderBuilder::new()
+        .comment(Some(b'#'))
+        .from_reader(contents)
+}
+
+fn version(contents: &[u8]) -> Result<u32, Box<dyn Error>> {
+    let first = contents.split(|byte| *byte == b'\n').next().unwrap_or(&[]);
+    let Some(version) = String::from_utf8_lossy(first)
+        .trim()
+        .strip_prefix(STAMP)
+        .map(str::to_string)
+    else {
+        return Ok(1);
+    };
+    version
+        .parse()
+        .map_err(|_| format!("unreadable schema version '{}'", version).into())
+}
+
+/// The data file brought up to the current version.
+pub fn upgrade(contents: &[u8]) -> Result<Cow<'_, [u8]>, Box<dyn Error>> {
+    let version = version(contents)?;
+    if version > VERSION {
+        return Err(format!(
+            "the data file is schema version {}, but this protour reads up to version {}; upgrade protour",
+            version, VERSION
+        )
+        .into());
+    }
+    if version == VERSION || contents.iter().all(u8::is_ascii_whitespace) {
+        return Ok(Cow::Borrowed(contents));
+    }
+
+    let mut rdr = reader(contents);
+    let mut table = Table {
+        headers: rdr.headers()?.iter().map(str::to_string).collect(),
+        rows: Vec::new(),
+    };
+    for record in rdr.records() {
+        table
+            .rows
+            .push(record?.iter().map(str::to_string).collect());
+    }
+    MIGRATIONS
+        .iter()
+        .filter(|(to, _)| *to > version)
+        .for_each(|(to, migrate)| {
+            migrate(&mut table);
+            info!(from = to - 1, to, "migrated data file");
+        });
+
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(&table.headers)?;
+    for row in &table.rows {
+        writer.write_record(&StringRecord::from(row.clone()))?;
+    }
+    Ok(Cow::Owned(writer.into_inner()?))
+}
+
+/// Puts the current version stamp above a freshly written file.
+pub fn stamp(contents: Vec<u8>) -> Vec<u8> {
+    let mut stamped = format!("{}{}\n", STAMP, VERSION).into_bytes();
+    stamped.extend(contents);
+    stamped
+}
@@ -0,0 +1,72 @@
+//! Rhai scripts for one-off stats the built-in reports don't cover.
+//!
+//! A script sees these constants:
+//!
+//! - `games`: every game, as maps with the data file's columns
+//! - `matchups`: every deck-vs-deck record (`deck`, `opponent`, `wins`, `losses`)
+//! - `ratings`: each player's Elo rating, keyed by name
+//! - `roster`: the roster's names
+//!
+//! `print` writes to stdout, `write_json(path, value)` saves any value as
+//! JSON, and a script's final value is printed unless it's `()`.
+
+use crate::config::Config;
+use crate::{ratings, Deck, GameLog, Matchup};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize)]
+struct MatchupRecord {
+    deck: String,
+    opponent: String,
+    wins: u32,
+    losses: u32,
+}
+
+fn write_json(path: &str, value: Dynamic) -> Result<(), Box<EvalAltResult>> {
+    let value: serde_json::Value = rhai::serde::from_dynamic(&value)?;
+    let json = serde_json::to_string_pretty(&value).map_err(|err| err.to_string())?;
+    fs::write(path, json).map_err(|err| format!("couldn't write {}: {}", path, err).into())
+}
+
+pub fn run(
+    path: &Path,
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let mut engine = Engine::new();
+    engine.register_fn("write_json", write_json);
+
+    let records: Vec<MatchupRecord> = matchups
+        .values()
+        .map(|matchup| MatchupRecord {
+            deck: matchup.deck.to_string(),
+            opponent: matchup.opponent.to_string(),
+            wins: matchup.win,
+            losses: matchup.loss,
+        })
+        .collect();
+    let mut scope = Scope::new();
+    scope.push_constant("games", rhai::serde::to_dynamic(games)?);
+    scope.push_constant("matchups", rhai::serde::to_dynamic(&records)?);
+    scope.push_constant(
+        "ratings",
+        rhai::serde::to_dynamic(ratings::elo(games, &config.ratings))?,
+    );
+    scope.push_constant("roster", rhai::serde::to_dynamic(&config.roster)?);
+
+    let source = fs::read_to_string(path)
+        .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+    let result: Dynamic = engine
+        .eval_with_scope(&mut scope, &source)
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    if !result.is_unit() {
+        println!("{}", result);
+    }
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Closing seasons. A closed season's final standings and ratings are frozen,
+//! its games are archived to a file of their own, and reports after that only
+//! count the games of the season since, unless run with `--all-time`.
+
+use crate::config::{Config, LeagueConfig};
+use crate::{crypt, league, ratings, store, GameLog};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum SeasonCommand {
+    /// Freeze the season so far, archive its games, and start a new one today
+    Close {
+        /// What to call the season; its first and last days if left out
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Every closed season
+    List,
+    /// A closed season's final standings and ratings
+    Show { name: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Copy of the season's games, taken when it closed
+    pub archive: PathBuf,
+    pub standings: Vec<String>,
+    /// Everyone on the roster by their rating when the season closed, best
+    /// first
+    pub ratings: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Seasons(Vec<Season>);
+
+impl Seasons {
+    pub fn path_for(data: &Path) -> PathBuf {
+        crypt::plain_path(data).with_extension("seasons.json")
+    }
+
+    pub fn load(data: &Path) -> Result<Self, Box<dyn Error>> {
+        let path = Self::path_for(data);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, data: &Path) -> Result<(), Box<dyn Error>> {
+        let path = Self::path_for(data);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// The day the current season started, once one has been closed.
+    pub fn current_start(&self) -> Option<NaiveDate> {
+        self.0.last().and_then(|season| season.end.succ_opt())
+    }
+
+    /// The closed season a game was played in, or `None` for the current
+    /// season's games; undated games belong to the first season.
+    pub fn season_of(&self, game: &GameLog) -> Option<&Season> {
+        match game.date {
+            Some(date) => self.0.iter().find(|season| date <= season.end),
+            None => self.0.first(),
+        }
+    }
+
+    /// Whether a game belongs to the current season; undated games predate
+    /// any close.
+    pub fn is_current(&self, game: &GameLog) -> bool {
+        match self.current_start() {
+            Some(start) => game.date.is_some_and(|date| date >= start),
+            None => true,
+        }
+    }
+}
+
+/// Where a closed season's games are archived: `data.csv` keeps a season
+/// named "Spring 2026" in `data.season-spring-2026.csv`, encrypted if the data
+/// file is.
+fn archive_path(data: &Path, name: &str) -> PathBuf {
+    let slug: String = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let path = crypt::plain_path(data).with_extension(format!("season-{}.csv", slug));
+    if crypt::is_encrypted(data) {
+        crypt::encrypted_path(&path)
+    } else {
+        path
+    }
+}
+
+fn close(
+    seasons: &mut Seasons,
+    name: Option<String>,
+    data: &Path,
+    games: &[GameLog],
+    league: &LeagueConfig,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let today = Local::now().date_naive();
+    let start = seasons.current_start().unwrap_or(league.start);
+    if today < start {
+        return Err(format!("the season doesn't start until {}", start).into());
+    }
+    let name = name.unwrap_or_else(|| format!("{} to {}", start, today));
+    if seasons.0.iter().any(|season| season.name == name) {
+        return Err(format!("there's already a season called '{}'", name).into());
+    }
+    let played: Vec<GameLog> = games
+        .iter()
+        .filter(|game| seasons.is_current(game) && game.date.is_none_or(|date| date <= today))
+        .cloned()
+        .collect();
+
+    let archive = archive_path(data, &name);
+    if archive.exists() {
+        return Err(format!("{} already exists", archive.display()).into());
+    }
+    store::save_games(&archive, &played)?;
+    let season = Season {
+        name,
+        start,
+        end: today,
+        archive,
+        standings: league::standings(league, &played, &config.roster, None),
+        ratings: ratings::ranked(&ratings::elo(&played, &config.ratings), &config.roster),
+    };
+    seasons.0.push(season);
+    seasons.save(data)?;
+    let season = &seasons.0[seasons.0.len() - 1];
+    println!(
+        "Closed {}: {} games archived to {}",
+        season.name,
+        played.len(),
+        season.archive.display()
+    );
+    print_season(season);
+    println!("The new season starts tomorrow");
+    Ok(())
+}
+
+fn print_season(season: &Season) {
+    println!("{} ({} to {})", season.name, season.start, season.end);
+    season
+        .standings
+        .iter()
+        .for_each(|line| println!("  {}", line));
+    println!("  Ratings:");
+    season
+        .ratings
+        .iter()
+        .enumerate()
+        .for_each(|(i, (player, rating))| println!("    {}. {} ({:.0})", i + 1, player, rating));
+}
+
+pub fn run(
+    command: SeasonCommand,
+    data: &Path,
+    games: &[GameLog],
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let mut seasons = Seasons::load(data)?;
+    match command {
+        SeasonCommand::Close { name } => {
+            let league = config
+                .league
+                .as_ref()
+                .ok_or("no [league] section in the config")?;
+            close(&mut seasons, name, data, games, league, config)?;
+        }
+        SeasonCommand::List => {
+            if seasons.0.is_empty() {
+                println!("No seasons have been closed");
+            }
+            seasons.0.iter().for_each(|season| {
+                let leader = season.ratings.first().map_or("-", |(player, _)| player);
+                println!(
+                    "{}: {} to {}, top rated {}, archived in {}",
+                    season.name,
+                    season.start,
+                    season.end,
+                    leader,
+                    season.archive.display()
+                )
+            });
+        }
+        SeasonCommand::Show { name } => {
+            let season = seasons
+                .0
+                .iter()
+                .find(|season| season.name == name)
+                .ok_or_else(|| format!("no season called '{}'", name))?;
+            print_season(season);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! Serve mode: a small HTTP server results can be submitted to from the table.
+//!
+//! `POST /results` takes a JSON object with the same fields as `protour add`
+//! (`player`, `deck`, `won`, `lost`, `opp_deck`, ...) and the shared token as a
+//! bearer token, validates it like any other new game, and appends it.
+//! `GET /results` is a form for phones that submits there, pre-filled from its
+//! query string (see `form_link`).
+//!
+//! `GET /matchups` and `GET /players/{name}` report the current season, as
+//! the CLI's reports do by default, and need no token. `client` wraps all
+//! three endpoints.
+
+use crate::client::{MatchupRecord, PlayerRecord, Recorded};
+use crate::config::Config;
+use crate::edit::GameFields;
+use crate::event::Events;
+use crate::ratings::{self, INITIAL_RATING};
+use crate::season::Seasons;
+use crate::{add_game, build_matchups, link_decks, player_wins_losses, store, GameLog};
+use axum::extract::{ConnectInfo, Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+pub const TOKEN_VAR: &str = "PROTOUR_SERVE_TOKEN";
+const FORM: &str = include_str!("results.html");
+
+struct Server {
+    data: PathBuf,
+    config: Config,
+    token: String,
+    /// Held while a submission reads and rewrites the data file
+    writing: Mutex<()>,
+}
+
+impl Server {
+    /// Compares the whole token whatever the first mismatch, so response
+    /// times don't give it away a byte at a time.
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(given) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return false;
+        };
+        given.len() == self.token.len()
+            && given
+                .bytes()
+                .zip(self.token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// The games reports see: this season's, with linked decks merged and
+    /// limited games left out. Reloaded for every request so results recorded
+    /// elsewhere show up.
+    fn games(&self) -> Result<Vec<GameLog>, Box<dyn Error>> {
+        let mut games = store::load_games(&self.data, &self.config.aliases)?;
+        let seasons = Seasons::load(&self.data)?;
+        games.retain(|game| seasons.is_current(game));
+        link_decks(&mut games, &self.config.links);
+        let events = Events::load(&self.data)?;
+        games.retain(|game| !events.is_limited(game));
+        Ok(games)
+    }
+}
+
+type Reply = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl ToString) -> Reply {
+    (status, Json(json!({ "error": message.to_string() })))
+}
+
+async fn post_result(
+    State(server): State<Arc<Server>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(fields): Json<GameFields>,
+) -> Reply {
+    if !server.authorized(&headers) {
+        return error(StatusCode::UNAUTHORIZED, "missing or wrong token");
+    }
+    let _writing = server.writing.lock().await;
+    let recorded = fields.into_game(&server.config.aliases).and_then(|game| {
+        let games = store::load_games(&server.data, &server.config.aliases)?;
+        let summary = format!(
+            "{} ({}) {} - {} {}",
+            game.player, game.deck, game.won, game.lost, game.opp_deck
+        );
+        Ok((
+            add_game(
+                &server.data,
+                games,
+                game,
+                &server.config.roster,
+                "serve",
+                // the token is shared, so where it came from is all that's known
+                Some(&format!("the web form from {}", client.ip())),
+            )?[0]
+                .id,
+            summary,
+        ))
+    });
+    match recorded {
+        Ok((id, game)) => {
+            info!(id, "recorded submitted game");
+            (StatusCode::CREATED, Json(json!(Recorded { id, game })))
+        }
+        Err(err) => error(StatusCode::UNPROCESSABLE_ENTITY, err),
+    }
+}
+
+async fn get_matchups(State(server): State<Arc<Server>>) -> Reply {
+    let games = match server.games() {
+        Ok(games) => games,
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, err),
+    };
+    let matchups: Vec<MatchupRecord> = build_matchups(&games)
+        .values()
+        .map(|matchup| MatchupRecord {
+            deck: matchup.deck.to_string(),
+            opponent: matchup.opponent.to_string(),
+            won: matchup.win,
+            lost: matchup.loss,
+        })
+        .collect();
+    (StatusCode::OK, Json(json!(matchups)))
+}
+
+async fn get_player(State(server): State<Arc<Server>>, Path(player): Path<String>) -> Reply {
+    let games = match server.games() {
+        Ok(games) => games,
+        Err(err) => return error(StatusCode::INTERNAL_SERVER_ERROR, err),
+    };
+    let player = server.config.aliases.player(&player);
+    if !games.iter().any(|game| game.player == player.as_str()) {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!("{} has no games this season", player),
+        );
+    }
+    let (won, lost) = player_wins_losses(&games, &player);
+    let rating = ratings::elo(&games, &server.config.ratings)
+        .get(&player)
+        .copied()
+        .unwrap_or(INITIAL_RATING);
+    (
+        StatusCode::OK,
+        Json(json!(PlayerRecord {
+            player,
+            won,
+            lost,
+            rating,
+        })),
+    )
+}
+
+/// `value` percent-encoded, to go in a URL's path or query string.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Link to the result form on the server at `base`, pre-filled with `fields`:
+/// `player`, `deck`, `opp_deck`, `event`, and, for the heading, `table` and
+/// `opponent`.
+pub fn form_link(base: &str, fields: &[(&str, &str)]) -> String {
+    let query: Vec<String> = fields
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| format!("{}={}", name, encode(value)))
+        .collect();
+    format!("{}/results?{}", base.trim_end_matches('/'), query.join("&"))
+}
+
+/// Serves the endpoints on `address` until the process is stopped.
+pub fn run(
+    address: &str,
+    token: String,
+    data: PathBuf,
+    config: Config,
+) -> Result<(), Box<dyn Error>> {
+    let server = Arc::new(Server {
+        data,
+        config,
+        token,
+        writing: Mutex::new(()),
+    });
+    let app = Router::new()
+        .route("/results", get(|| async { Html(FORM) }).post(post_result))
+        .route("/matchups", get(get_matchups))
+        .route("/players/{player}", get(get_player))
+        .with_state(server);
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        info!(address, "serving");
+        println!("Listening on http://{}", listener.local_addr()?);
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+    })?;
+    Ok(())
+}
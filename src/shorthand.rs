@@ -0,0 +1,214 @@
+//! Games entered as one line between rounds, e.g.
+//! `Grant RbAggro 2-1 Noah GrixisMid, flooded g2`: the player, their deck,
+//! the score, the opponent if they're on the roster, the opponent's deck,
+//! and after a comma, the notes.
+
+use crate::config::Aliases;
+use crate::edit::GameFields;
+use crate::{Archetype, Color, ColorIdentity, Deck};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+/// The color identity a deck name starts with, and the rest of the name.
+/// Identities go by name (`Grixis`, `Rb`) or, failing that, by their color
+/// letters in any order (`BR` is `Rb`).
+fn split_colors(name: &str) -> Option<(ColorIdentity, &str)> {
+    let named = ColorIdentity::iter()
+        .map(|identity| (identity, identity.to_string()))
+        .filter(|(_, label)| {
+            name.get(..label.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(label))
+        })
+        .max_by_key(|(_, label)| label.len());
+    if let Some((identity, label)) = named {
+        return Some((identity, &name[label.len()..]));
+    }
+    let letters = name
+        .find(|c: char| !"WUBRGwubrg".contains(c))
+        .unwrap_or(name.len());
+    let colors: BTreeSet<Color> = name[..letters]
+        .chars()
+        .map(|letter| Color::from_str(&letter.to_string()))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    ColorIdentity::iter()
+        .filter(|identity| *identity != ColorIdentity::FourColor)
+        .find(|identity| identity.colors() == colors)
+        .map(|identity| (identity, &name[letters..]))
+}
+
+/// An archetype by its name or the start of it, e.g. `Mid` or `ctrl`.
+fn archetype(name: &str) -> Result<Archetype, String> {
+    let lowered = name.trim().to_lowercase();
+    if lowered == "ctrl" {
+        return Ok(Archetype::Control);
+    }
+    let matches: Vec<Archetype> = Archetype::iter()
+        .filter(|archetype| archetype.to_string().to_lowercase().starts_with(&lowered))
+        .collect();
+    match matches[..] {
+        [archetype] => Ok(archetype),
+        _ => {
+            let known: Vec<String> = Archetype::iter().map(|a| a.to_string()).collect();
+            Err(format!(
+                "'{}' isn't an archetype; try one of {}",
+                name,
+                known.join(", ")
+            ))
+        }
+    }
+}
+
+/// A deck written as `Rb Aggro`, `RbAggro`, or `rb aggro`, as its spec; a
+/// configured deck alias is used as it is, and a bare color identity, or one
+/// followed by `?`, is left without an archetype.
+fn deck(name: &str, aliases: &Aliases) -> Result<String, String> {
+    let resolved = aliases.deck_spec(name);
+    if resolved != name.trim() {
+        return Ok(resolved.to_string());
+    }
+    let compact: String = name.split_whitespace().collect();
+    let (identity, rest) = split_colors(&compact)
+        .ok_or_else(|| format!("'{}' doesn't start with its colors", name))?;
+    if rest.is_empty() || rest == "?" {
+        return Ok(Deck(identity, None).to_string());
+    }
+    Ok(Deck(identity, Some(archetype(rest)?)).to_string())
+}
+
+/// The roster player a word names, through the player aliases and
+/// ignoring case.
+fn roster_player(word: &str, roster: &[String], aliases: &Aliases) -> Option<String> {
+    let name = aliases.player_name(word);
+    roster
+        .iter()
+        .find(|player| player.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Fills `fields` from a shorthand line, leaving alone whatever flags already
+/// set. A line without notes records none, rather than prompting for them.
+pub fn parse(
+    line: &str,
+    fields: &mut GameFields,
+    roster: &[String],
+    aliases: &Aliases,
+) -> Result<(), String> {
+    let (game, notes) = line.split_once(',').unwrap_or((line, ""));
+    let words: Vec<&str> = game
+        .split_whitespace()
+        .filter(|word| !["vs", "vs.", "v", "v."].contains(&word.to_lowercase().as_str()))
+        .collect();
+    let score = |word: &str| {
+        let (won, lost) = word.split_once(['-', '–', ':'])?;
+        Some((won.parse().ok()?, lost.parse().ok()?))
+    };
+    let at = words
+        .iter()
+        .position(|word| score(word).is_some())
+        .ok_or_else(|| format!("no score like 2-1 in '{}'", line.trim()))?;
+    let (won, lost) = score(words[at]).unwrap();
+    let (before, after) = (&words[..at], &words[at + 1..]);
+
+    let [player, own_deck @ ..] = before else {
+        return Err("the line should start with the player".to_string());
+    };
+    if own_deck.is_empty() {
+        return Err(format!("no deck for {} before the score", player));
+    }
+    let (opponent, opp_deck) = match after {
+        [first, rest @ ..] if !rest.is_empty() => match roster_player(first, roster, aliases) {
+            Some(opponent) => (Some(opponent), rest),
+            None => (None, after),
+        },
+        _ => (None, after),
+    };
+    if opp_deck.is_empty() {
+        return Err("no opponent's deck after the score".to_string());
+    }
+
+    let player = roster_player(player, roster, aliases).unwrap_or_else(|| player.to_string());
+    fields.player.get_or_insert(player);
+    fields
+        .deck
+        .get_or_insert(deck(&own_deck.join(" "), aliases)?);
+    fields.won.get_or_insert(won);
+    fields.lost.get_or_insert(lost);
+    if let Some(opponent) = opponent {
+        fields.opponent.get_or_insert(opponent);
+    }
+    fields
+        .opp_deck
+        .get_or_insert(deck(&opp_deck.join(" "), aliases)?);
+    fields.notes.get_or_insert(notes.trim().to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster() -> Vec<String> {
+        vec!["Grant".to_string(), "Noah".to_string()]
+    }
+
+    fn parsed(line: &str) -> Result<GameFields, String> {
+        let mut fields = GameFields::default();
+        parse(line, &mut fields, &roster(), &Aliases::default())?;
+        Ok(fields)
+    }
+
+    #[test]
+    fn a_line_fills_in_the_game() {
+        let fields = parsed("grant RbAggro 2-1 noah GrixisMid, flooded g2").unwrap();
+        assert_eq!(fields.player.as_deref(), Some("Grant"));
+        assert_eq!(fields.deck.as_deref(), Some("Rb Aggro"));
+        assert_eq!((fields.won, fields.lost), (Some(2), Some(1)));
+        assert_eq!(fields.opponent.as_deref(), Some("Noah"));
+        assert_eq!(fields.opp_deck.as_deref(), Some("Grixis Midrange"));
+        assert_eq!(fields.notes.as_deref(), Some("flooded g2"));
+    }
+
+    #[test]
+    fn opponents_off_the_roster_are_part_of_the_deck() {
+        let fields = parsed("Grant BR aggro 0:2 vs Grixis ctrl").unwrap();
+        assert_eq!(fields.deck.as_deref(), Some("Rb Aggro"));
+        assert_eq!((fields.won, fields.lost), (Some(0), Some(2)));
+        assert_eq!(fields.opponent, None);
+        assert_eq!(fields.opp_deck.as_deref(), Some("Grixis Control"));
+        assert_eq!(fields.notes.as_deref(), Some(""));
+        // a bare color identity leaves the archetype unknown
+        let fields = parsed("Grant Rb 2-0 Noah Grixis ?").unwrap();
+        assert_eq!(fields.opp_deck.as_deref(), Some("Grixis ?"));
+    }
+
+    #[test]
+    fn flags_already_given_win_over_the_line() {
+        let mut fields = GameFields {
+            deck: Some("Ub Control".to_string()),
+            ..Default::default()
+        };
+        parse(
+            "Grant RbAggro 2-1 Noah GrixisMid",
+            &mut fields,
+            &roster(),
+            &Aliases::default(),
+        )
+        .unwrap();
+        assert_eq!(fields.deck.as_deref(), Some("Ub Control"));
+        assert_eq!(fields.won, Some(2));
+    }
+
+    #[test]
+    fn lines_missing_a_part_are_refused() {
+        assert!(parsed("Grant RbAggro Noah GrixisMid").is_err());
+        assert!(parsed("Grant 2-1 Noah GrixisMid").is_err());
+        assert!(parsed("Grant RbAggro 2-1").is_err());
+        // a roster player alone after the score is taken for the deck
+        assert!(parsed("Grant RbAggro 2-1 Noah").is_err());
+        assert!(parsed("Grant RbAggro 2-1 GrixisMid").is_ok());
+        assert!(parsed("Grant Aggro 2-1 GrixisMid").is_err());
+        assert!(parsed("Grant RbZoo 2-1 GrixisMid").is_err());
+    }
+}
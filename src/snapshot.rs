@@ -0,0 +1,292 @@
+use crate::config::Config;
+use crate::{deck_vs_field, player_wins_losses, ratings, sample, Deck, GameLog, Matchup};
+use chrono::{Local, NaiveDate};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotCommand {
+    /// Write the current ratings, records, and matchups to a JSON file
+    Save { path: PathBuf },
+    /// Show what changed between two saved snapshots
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Print the changes as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    /// Too few games for the win rate to be quoted without a caveat
+    #[serde(default)]
+    pub small_sample: bool,
+}
+
+impl Record {
+    fn new((wins, losses): (u32, u32)) -> Self {
+        Self {
+            wins,
+            losses,
+            small_sample: sample::is_small(wins + losses),
+        }
+    }
+
+    fn win_rate(&self) -> Option<f64> {
+        let games = self.wins + self.losses;
+        (games > 0).then(|| self.wins as f64 / games as f64)
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.win_rate() {
+            Some(rate) => write!(
+                f,
+                "{}-{} ({:.1}%{})",
+                self.wins,
+                self.losses,
+                rate * 100.0,
+                sample::marker(self.wins + self.losses)
+            ),
+            None => write!(f, "{}-{}", self.wins, self.losses),
+        }
+    }
+}
+
+/// The computed stats at one point in time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken: NaiveDate,
+    pub games: usize,
+    /// Elo rating of each player on the roster
+    pub ratings: BTreeMap<String, f64>,
+    pub players: BTreeMap<String, Record>,
+    /// Each deck's record vs. the field
+    pub decks: BTreeMap<String, Record>,
+    /// Every non-mirror matchup, keyed like `Rb Midrange vs Ub Toxic`
+    pub matchups: BTreeMap<String, Record>,
+}
+
+impl Snapshot {
+    pub fn new(
+        games: &[GameLog],
+        matchups: &BTreeMap<(Deck, Deck), Matchup>,
+        config: &Config,
+    ) -> Self {
+        let elo = ratings::elo(games, &config.ratings);
+        let decks: BTreeSet<Deck> = matchups.keys().map(|(deck, _)| *deck).collect();
+        Self {
+            taken: Local::now().date_naive(),
+            games: games.len(),
+            ratings: ratings::ranked(&elo, &config.roster).into_iter().collect(),
+            players: config
+                .roster
+                .iter()
+                .map(|player| {
+                    (
+                        player.clone(),
+                        Record::new(player_wins_losses(games, player)),
+                    )
+                })
+                .collect(),
+            decks: decks
+                .iter()
+                .map(|deck| {
+                    (
+                        deck.to_string(),
+                        Record::new(deck_vs_field(matchups, *deck)),
+                    )
+                })
+                .collect(),
+            matchups: matchups
+                .values()
+                .filter(|matchup| matchup.deck != matchup.opponent && matchup.knows_archetypes())
+                .map(|matchup| {
+                    (
+                        format!("{} vs {}", matchup.deck, matchup.opponent),
+                        Record::new((matchup.win, matchup.loss)),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("couldn't read {}: {}", path.display(), err))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// One stat in both snapshots; `change` is in rating points for ratings and
+/// in win rate (as a fraction) for records.
+#[derive(Debug, Serialize)]
+struct Change<T> {
+    before: Option<T>,
+    after: Option<T>,
+    change: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Diff {
+    before: NaiveDate,
+    after: NaiveDate,
+    games: Change<usize>,
+    ratings: BTreeMap<String, Change<f64>>,
+    players: BTreeMap<String, Change<Record>>,
+    decks: BTreeMap<String, Change<Record>>,
+    matchups: BTreeMap<String, Change<Record>>,
+}
+
+/// Everything that differs between the two maps, with `value` giving the
+/// number the change is measured in.
+fn changes<T: Copy + PartialEq>(
+    old: &BTreeMap<String, T>,
+    new: &BTreeMap<String, T>,
+    value: impl Fn(&T) -> Option<f64>,
+) -> BTreeMap<String, Change<T>> {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| {
+            let (before, after) = (old.get(key).copied(), new.get(key).copied());
+            let change = match (
+                before.as_ref().and_then(&value),
+                after.as_ref().and_then(&value),
+            ) {
+                (Some(before), Some(after)) => Some(after - before),
+                _ => None,
+            };
+            (
+                key.clone(),
+                Change {
+                    before,
+                    after,
+                    change,
+                },
+            )
+        })
+        .collect()
+}
+
+impl Diff {
+    fn new(old: &Snapshot, new: &Snapshot) -> Self {
+        Self {
+            before: old.taken,
+            after: new.taken,
+            games: Change {
+                before: Some(old.games),
+                after: Some(new.games),
+                change: Some(new.games as f64 - old.games as f64),
+            },
+            ratings: changes(&old.ratings, &new.ratings, |rating| Some(*rating)),
+            players: changes(&old.players, &new.players, Record::win_rate),
+            decks: changes(&old.decks, &new.decks, Record::win_rate),
+            matchups: changes(&old.matchups, &new.matchups, Record::win_rate),
+        }
+    }
+}
+
+fn shown<T: std::fmt::Display>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+/// Prints the changes biggest first, leaving out sections with none.
+fn print_changes<T: std::fmt::Display>(
+    title: &str,
+    changes: &BTreeMap<String, Change<T>>,
+    show: impl Fn(&T) -> String,
+    unit: f64,
+) {
+    if changes.is_empty() {
+        return;
+    }
+    let mut changes: Vec<(&String, &Change<T>)> = changes.iter().collect();
+    changes.sort_by(|a, b| {
+        let size = |change: &Change<T>| change.change.map_or(f64::INFINITY, f64::abs);
+        size(b.1).total_cmp(&size(a.1))
+    });
+    println!("{}:", title);
+    changes.iter().for_each(|(key, change)| {
+        let delta = change
+            .change
+            .map(|change| format!(" ({:+.1})", change * unit))
+            .unwrap_or_default();
+        println!(
+            "  {}: {} -> {}{}",
+            key,
+            change
+                .before
+                .as_ref()
+                .map_or_else(|| "-".to_string(), &show),
+            change.after.as_ref().map_or_else(|| "-".to_string(), &show),
+            delta
+        )
+    });
+    println!();
+}
+
+impl Diff {
+    fn print(&self) {
+        println!(
+            "{} -> {}: {} -> {} games\n",
+            self.before,
+            self.after,
+            shown(&self.games.before),
+            shown(&self.games.after)
+        );
+        print_changes(
+            "Ratings",
+            &self.ratings,
+            |rating| format!("{:.0}", rating),
+            1.0,
+        );
+        print_changes("Player records", &self.players, Record::to_string, 100.0);
+        print_changes(
+            "Deck records vs. field",
+            &self.decks,
+            Record::to_string,
+            100.0,
+        );
+        print_changes("Matchups", &self.matchups, Record::to_string, 100.0);
+    }
+}
+
+pub fn run(
+    command: SnapshotCommand,
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        SnapshotCommand::Save { path } => {
+            let snapshot = Snapshot::new(games, matchups, config);
+            fs::write(&path, serde_json::to_string_pretty(&snapshot)?)?;
+            println!(
+                "Saved a snapshot of {} games to {}",
+                snapshot.games,
+                path.display()
+            );
+        }
+        SnapshotCommand::Diff { old, new, json } => {
+            let diff = Diff::new(&Snapshot::load(&old)?, &Snapshot::load(&new)?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                diff.print();
+            }
+        }
+    }
+    Ok(())
+}
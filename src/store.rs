@@ -0,0 +1,239 @@
+use crate::config::Aliases;
+use crate::intern::Interner;
+use crate::{backup, crypt, progress, schema, GameLog};
+use chrono::NaiveDate;
+use csv::{ByteRecord, Writer};
+use serde::Deserialize;
+use std::error::Error;
+use std::path::Path;
+use std::time::Instant;
+use tracing::info;
+
+/// A row of the data file borrowing its text from the record being read, so
+/// nothing is copied out of it until its aliases are resolved and its names
+/// interned. Mirrors `GameLog`'s columns.
+#[derive(Deserialize)]
+struct Record<'a> {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    date: Option<NaiveDate>,
+    player: &'a str,
+    deck: &'a str,
+    won: u32,
+    lost: u32,
+    opp_deck: &'a str,
+    #[serde(default)]
+    opponent: Option<&'a str>,
+    #[serde(default)]
+    reported_by: Option<&'a str>,
+    #[serde(default)]
+    confirmed: Option<bool>,
+    notes: &'a str,
+    #[serde(default)]
+    event: Option<&'a str>,
+    #[serde(default)]
+    best_of: Option<u32>,
+    #[serde(default)]
+    pod: Option<&'a str>,
+    #[serde(default)]
+    mulligans: Option<u32>,
+    #[serde(default)]
+    opp_mulligans: Option<u32>,
+    #[serde(default)]
+    won_roll: Option<bool>,
+    #[serde(default)]
+    on_play: Option<bool>,
+    #[serde(default)]
+    team: Option<&'a str>,
+    #[serde(default)]
+    source: Option<&'a str>,
+}
+
+impl Record<'_> {
+    fn game(self, line: u64, aliases: &Aliases, names: &mut Interner) -> GameLog {
+        GameLog {
+            id: self.id,
+            line,
+            date: self.date,
+            player: names.intern(aliases.player_name(self.player)),
+            deck: names.intern(aliases.deck_spec(self.deck)),
+            won: self.won,
+            lost: self.lost,
+            opp_deck: names.intern(aliases.deck_spec(self.opp_deck)),
+            opponent: self
+                .opponent
+                .filter(|opponent| !opponent.trim().is_empty())
+                .map(|opponent| names.intern(aliases.player_name(opponent))),
+            reported_by: self
+                .reported_by
+                .filter(|reporter| !reporter.trim().is_empty())
+                .map(|reporter| names.intern(aliases.player_name(reporter))),
+            confirmed: self.confirmed,
+            notes: self.notes.to_string(),
+            event: self.event.map(str::to_string),
+            best_of: self.best_of,
+            pod: self.pod.map(str::to_string),
+            mulligans: self.mulligans,
+            opp_mulligans: self.opp_mulligans,
+            won_roll: self.won_roll,
+            on_play: self.on_play,
+            team: self.team.map(str::to_string),
+            source: self.source.map(str::to_string),
+        }
+    }
+}
+
+/// Calls `each` with every row of `contents` and its line, reading them all
+/// into one reused record and sharing each player and deck name among the
+/// games that repeat it.
+fn each_row(
+    contents: &[u8],
+    aliases: &Aliases,
+    mut each: impl FnMut(u64, Result<GameLog, csv::Error>) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut rdr = schema::reader(contents);
+    let headers = rdr.byte_headers()?.clone();
+    let mut record = ByteRecord::new();
+    let mut names = Interner::default();
+    while rdr.read_byte_record(&mut record)? {
+        let line = record.position().map_or(0, |position| position.line());
+        let game = record
+            .deserialize::<Record>(Some(&headers))
+            .map(|row| row.game(line, aliases, &mut names));
+        each(line, game)?;
+    }
+    Ok(())
+}
+
+/// Rows in a CSV, near enough: one per line after the header.
+fn lines(contents: &[u8]) -> usize {
+    contents
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+        .saturating_sub(1)
+}
+
+pub fn load_games(path: &Path, aliases: &Aliases) -> Result<Vec<GameLog>, Box<dyn Error>> {
+    let started = Instant::now();
+    let contents = crypt::read(path)?;
+    let contents = schema::upgrade(&contents)?;
+    // sized up front so a huge history isn't copied again every time it outgrows the vec
+    let mut games = Vec::with_capacity(lines(&contents));
+    each_row(&contents, aliases, |_, game| {
+        games.push(game?);
+        Ok(())
+    })?;
+    // games recorded before ids existed are numbered after every known id, in file order
+    assign_ids(&[], &mut games);
+    info!(games = games.len(), path = %path.display(), elapsed = ?started.elapsed(), "loaded games");
+    Ok(games)
+}
+
+/// Gives every game in `new` without an id the next free one after `existing`.
+pub fn assign_ids(existing: &[GameLog], new: &mut [GameLog]) {
+    let mut next = existing
+        .iter()
+        .chain(new.iter())
+        .map(|game| game.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    new.iter_mut().filter(|game| game.id == 0).for_each(|game| {
+        game.id = next;
+        next += 1;
+    });
+}
+
+/// The game with `id`. An id more than one game has (say, after the data file
+/// was edited by hand) is refused, so an edit or delete never lands on the
+/// wrong game, or on several.
+pub fn find_mut(games: &mut [GameLog], id: u32) -> Result<&mut GameLog, Box<dyn Error>> {
+    let mut found = games.iter_mut().filter(|game| game.id == id);
+    let game = found
+        .next()
+        .ok_or_else(|| format!("no game with id {}", id))?;
+    let others = found.count();
+    if others > 0 {
+        return Err(format!(
+            "{} games have id {}; give each its own id in the data file first",
+            others + 1,
+            id
+        )
+        .into());
+    }
+    Ok(game)
+}
+
+/// A parsed game, or the source line and reason it couldn't be parsed.
+pub type Row = Result<GameLog, (u64, String)>;
+
+/// Reads every row of a CSV, keeping rows that fail to deserialize instead of
+/// aborting, for importers that report them.
+pub fn read_rows(path: &Path, aliases: &Aliases) -> Result<Vec<Row>, Box<dyn Error>> {
+    parse_rows(&crypt::read(path)?, aliases)
+}
+
+/// Like `read_rows`, for a CSV already in memory.
+pub fn parse_rows(contents: &[u8], aliases: &Aliases) -> Result<Vec<Row>, Box<dyn Error>> {
+    let contents = schema::upgrade(contents)?;
+    let bar = progress::rows(lines(&contents) as u64, "Reading");
+    let mut rows = Vec::new();
+    let mut errors = 0;
+    each_row(&contents, aliases, |line, game| {
+        match game {
+            Ok(game) => rows.push(Ok(game)),
+            Err(err) => {
+                errors += 1;
+                progress::errors(&bar, errors);
+                rows.push(Err((line, err.to_string())));
+            }
+        }
+        bar.inc(1);
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Rewrites the data file with `games`, backing up the old file first and going
+/// through a temporary file so a failed write never leaves it half-written.
+pub fn save_games(path: &Path, games: &[GameLog]) -> Result<(), Box<dyn Error>> {
+    backup::create(path)?;
+    let mut writer = Writer::from_writer(Vec::new());
+    for game in games {
+        writer.serialize(game)?;
+    }
+    crypt::write(path, &schema::stamp(writer.into_inner()?))?;
+    info!(games = games.len(), path = %path.display(), "saved games");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: u32) -> GameLog {
+        GameLog {
+            id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn new_games_are_numbered_after_every_known_id() {
+        let existing = [game(1), game(4)];
+        let mut new = [game(0), game(7), game(0)];
+        assign_ids(&existing, &mut new);
+        let ids: Vec<u32> = new.iter().map(|game| game.id).collect();
+        assert_eq!(ids, [8, 7, 9]);
+    }
+
+    #[test]
+    fn a_shared_id_is_refused() {
+        let mut games = [game(1), game(2), game(2)];
+        assert_eq!(find_mut(&mut games, 1).unwrap().id, 1);
+        assert!(find_mut(&mut games, 2).is_err());
+        assert!(find_mut(&mut games, 3).is_err());
+    }
+}
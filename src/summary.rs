@@ -0,0 +1,174 @@
+//! A recap of one game night, short enough to paste into the group chat.
+
+use crate::config::RatingsConfig;
+use crate::{achievements, ratings, GameLog};
+use chrono::NaiveDate;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+// the shortest run of wins worth a mention
+const STREAK: u32 = 3;
+
+/// Matches in `games`: a match recorded for both players counts once, as does
+/// every seat of a pod.
+fn matches(games: &[&GameLog]) -> usize {
+    let pods: BTreeSet<&str> = games
+        .iter()
+        .filter_map(|game| game.pod.as_deref())
+        .collect();
+    let head_to_head = games
+        .iter()
+        .filter(|game| game.pod.is_none() && !game.is_complement());
+    pods.len() + head_to_head.count()
+}
+
+/// The player's wins in a row as of the end of `games`.
+fn streak(games: &[&GameLog], player: &str) -> u32 {
+    games
+        .iter()
+        .rev()
+        .filter(|game| game.player == player && !game.is_draw())
+        .take_while(|game| game.won > game.lost)
+        .count() as u32
+}
+
+/// The night's win that was least expected, if any was less likely than not:
+/// against the opponent's rating going into the night when they're on the
+/// roster, else as Elo rates it.
+fn upset<'a>(
+    night: &[&'a GameLog],
+    before: &BTreeMap<String, f64>,
+    config: &RatingsConfig,
+    all: &[GameLog],
+) -> Option<(&'a GameLog, f64)> {
+    let predicted = ratings::elo_before(all, config);
+    night
+        .iter()
+        .filter(|game| game.won > game.lost && game.pod.is_none())
+        .filter_map(|game| {
+            let chance = match &game.opponent {
+                Some(opponent) => {
+                    let rating = |player: &str| {
+                        before
+                            .get(player)
+                            .copied()
+                            .unwrap_or(ratings::INITIAL_RATING)
+                    };
+                    ratings::expected(rating(&game.player), rating(opponent))
+                }
+                None => predicted.get(&game.id)?.1,
+            };
+            Some((*game, chance))
+        })
+        .filter(|(_, chance)| *chance < 0.5)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Everything that happened on `date`: how many matches were played, each
+/// player's record, the biggest upset, achievements and streaks, and how
+/// everyone's Elo moved.
+pub fn print(games: &[GameLog], roster: &[String], config: &RatingsConfig, date: NaiveDate) {
+    let night: Vec<&GameLog> = ratings::chronological(games)
+        .into_iter()
+        .filter(|game| game.date == Some(date))
+        .collect();
+    if night.is_empty() {
+        println!("No games on {}.", date);
+        return;
+    }
+    println!(
+        "Game night {}: {} matches",
+        date.format("%a %-d %b %Y"),
+        matches(&night)
+    );
+
+    let mut records: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    night
+        .iter()
+        .filter(|game| !game.is_draw())
+        .for_each(|game| {
+            let record = records.entry(&game.player).or_default();
+            if game.won > game.lost {
+                record.0 += 1;
+            } else {
+                record.1 += 1;
+            }
+        });
+    let mut standings: Vec<(&str, (u32, u32))> = records.into_iter().collect();
+    standings.sort_by_key(|(_, (wins, losses))| (Reverse(*wins), *losses));
+    let standings: Vec<String> = standings
+        .iter()
+        .map(|(player, (wins, losses))| format!("{} {}-{}", player, wins, losses))
+        .collect();
+    println!("Records: {}", standings.join(", "));
+
+    let earlier: Vec<GameLog> = games
+        .iter()
+        .filter(|game| game.date.is_none_or(|day| day < date))
+        .cloned()
+        .collect();
+    let through: Vec<GameLog> = games
+        .iter()
+        .filter(|game| game.date.is_none_or(|day| day <= date))
+        .cloned()
+        .collect();
+    let before = ratings::elo(&earlier, config);
+    let after = ratings::elo(&through, config);
+
+    if let Some((game, chance)) = upset(&night, &before, config, &through) {
+        let beaten = match &game.opponent {
+            Some(opponent) => format!("{} ({})", opponent, game.opp_deck),
+            None => game.opp_deck.to_string(),
+        };
+        println!(
+            "Biggest upset: {} ({}) beat {}, a {:.0}% shot",
+            game.player,
+            game.deck,
+            beaten,
+            chance * 100.0
+        );
+    }
+
+    let played: BTreeSet<&str> = night.iter().map(|game| game.player.as_str()).collect();
+    let history = ratings::chronological(&through);
+    let mut highlights = Vec::new();
+    roster
+        .iter()
+        .filter(|player| played.contains(player.as_str()))
+        .for_each(|player| {
+            achievements::earned(&through, player, roster)
+                .into_iter()
+                .filter(|earned| earned.date == Some(date))
+                .for_each(|earned| highlights.push(format!("{}: {}", player, earned.achievement)));
+            // a run the night added to, unless its achievement says it already
+            let streak = streak(&history, player);
+            let extended = night
+                .iter()
+                .rfind(|game| game.player == *player && !game.is_draw())
+                .is_some_and(|game| game.won > game.lost);
+            if extended && streak >= STREAK && streak != achievements::STREAK {
+                highlights.push(format!("{} has won {} in a row", player, streak));
+            }
+        });
+    if !highlights.is_empty() {
+        println!("Highlights: {}", highlights.join("; "));
+    }
+
+    let changes: Vec<String> = played
+        .iter()
+        .filter_map(|player| {
+            let now = *after.get(*player)?;
+            let then = before
+                .get(*player)
+                .copied()
+                .unwrap_or(ratings::INITIAL_RATING);
+            Some((player, then, now))
+        })
+        .map(|(player, then, now)| {
+            format!("{} {:.0} -> {:.0} ({:+.0})", player, then, now, now - then)
+        })
+        .collect();
+    if !changes.is_empty() {
+        println!("Elo: {}", changes.join(", "));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{audit, crypt, event, schema, store, GameLog};
+use csv::Writer;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use std::process::{Command, Output};
+use tracing::{debug, info};
+
+/// Runs git in the directory holding the data file.
+fn git(dir: &Path, args: &[&str]) -> Result<Output, Box<dyn Error>> {
+    debug!(?args, "running git");
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    Ok(output)
+}
+
+/// Like `git`, but failing with git's stderr when the command doesn't succeed.
+fn git_ok(dir: &Path, args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = git(dir, args)?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// One side of a conflicted file; a file missing from that side reads as empty.
+fn stage(dir: &Path, number: u8, file: &str) -> Result<String, Box<dyn Error>> {
+    let output = git(dir, &["show", &format!(":{}:./{}", number, file)])?;
+    let contents = if crypt::is_encrypted(Path::new(file)) && !output.stdout.is_empty() {
+        crypt::decrypt(&output.stdout)?
+    } else {
+        output.stdout
+    };
+    Ok(String::from_utf8_lossy(&contents).into_owned())
+}
+
+fn parse_games(text: &str) -> Result<Vec<GameLog>, Box<dyn Error>> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut games = Vec::new();
+    let text = schema::upgrade(text.as_bytes())?;
+    for game in schema::reader(&text).deserialize() {
+        games.push(game?);
+    }
+    Ok(games)
+}
+
+fn same_game(a: &GameLog, b: &GameLog) -> bool {
+    a.id == b.id && a.key() == b.key()
+}
+
+/// Rows `side` appended after everything in `base`, or `None` if it changed
+/// or removed any of them.
+fn appended<'a, T>(base: &[T], side: &'a [T], same: impl Fn(&T, &T) -> bool) -> Option<&'a [T]> {
+    let unchanged = base.len() <= side.len() && base.iter().zip(side).all(|(a, b)| same(a, b));
+    unchanged.then(|| &side[base.len()..])
+}
+
+/// The games both sides appended, merged: upstream's are kept as they are and
+/// local ones go after them, renumbered past upstream's ids. Also returns the
+/// renumbering, or `None` if either side changed or removed a game `base` had.
+fn merge(
+    base: &[GameLog],
+    upstream: &[GameLog],
+    local: &[GameLog],
+) -> Option<(Vec<GameLog>, BTreeMap<u32, u32>)> {
+    appended(base, upstream, same_game)?;
+    let mut added = appended(base, local, same_game)?.to_vec();
+    let old_ids: Vec<u32> = added.iter().map(|game| game.id).collect();
+    added.iter_mut().for_each(|game| game.id = 0);
+    store::assign_ids(upstream, &mut added);
+    let renumbered = old_ids
+        .into_iter()
+        .zip(added.iter().map(|game| game.id))
+        .collect();
+    Some(([upstream, &added].concat(), renumbered))
+}
+
+/// Merges the games both sides appended to a conflicted data file (see
+/// `merge`). Returns the renumbering so the audit log can follow it.
+fn merge_games(dir: &Path, file: &str) -> Result<Option<BTreeMap<u32, u32>>, Box<dyn Error>> {
+    // while rebasing, stage 2 is the upstream branch and stage 3 the local commit
+    let base = parse_games(&stage(dir, 1, file)?)?;
+    let upstream = parse_games(&stage(dir, 2, file)?)?;
+    let local = parse_games(&stage(dir, 3, file)?)?;
+    let Some((merged, renumbered)) = merge(&base, &upstream, &local) else {
+        return Ok(None);
+    };
+
+    let mut writer = Writer::from_writer(Vec::new());
+    for game in &merged {
+        writer.serialize(game)?;
+    }
+    crypt::write(&dir.join(file), &schema::stamp(writer.into_inner()?))?;
+    info!(
+        file,
+        added = merged.len() - upstream.len(),
+        "merged appended games"
+    );
+    Ok(Some(renumbered))
+}
+
+/// Merges the entries both sides appended to the audit log, pointing local
+/// entries at their games' new ids.
+fn merge_audit(
+    dir: &Path,
+    file: &str,
+    renumbered: &BTreeMap<u32, u32>,
+) -> Result<bool, Box<dyn Error>> {
+    let lines = |text: String| -> Vec<String> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let base = lines(stage(dir, 1, file)?);
+    let upstream = lines(stage(dir, 2, file)?);
+    let local = lines(stage(dir, 3, file)?);
+    let (Some(_), Some(added)) = (
+        appended(&base, &upstream, String::eq),
+        appended(&base, &local, String::eq),
+    ) else {
+        return Ok(false);
+    };
+
+    let mut merged = upstream.clone();
+    for line in added {
+        let mut entry: audit::Entry = serde_json::from_str(line)?;
+        let id = renumbered.get(&entry.id).copied().unwrap_or(entry.id);
+        entry.id = id;
+        entry
+            .before
+            .iter_mut()
+            .chain(entry.after.iter_mut())
+            .for_each(|game| game.id = id);
+        merged.push(serde_json::to_string(&entry)?);
+    }
+    crypt::write(&dir.join(file), (merged.join("\n") + "\n").as_bytes())?;
+    Ok(true)
+}
+
+/// Resolves the conflicts in one replayed commit, as long as both sides only
+/// appended games; anything else is left for a person to sort out.
+fn resolve(dir: &Path, data: &str, audit: &str) -> Result<(), Box<dyn Error>> {
+    let conflicted = git_ok(
+        dir,
+        &["diff", "--name-only", "--relative", "--diff-filter=U"],
+    )?;
+    let conflicted: Vec<&str> = conflicted.lines().collect();
+    if conflicted.is_empty() {
+        return Err("the rebase stopped without conflicts, finish it by hand".into());
+    }
+    let mut renumbered = BTreeMap::new();
+    if conflicted.contains(&data) {
+        renumbered = merge_games(dir, data)?.ok_or_else(|| {
+            format!(
+                "{} was edited on both sides, resolve the rebase by hand",
+                data
+            )
+        })?;
+    }
+    if conflicted.contains(&audit) && !merge_audit(dir, audit, &renumbered)? {
+        return Err(format!("{} was rewritten, resolve the rebase by hand", audit).into());
+    }
+    if let Some(other) = conflicted
+        .iter()
+        .find(|file| **file != data && **file != audit)
+    {
+        return Err(format!("{} conflicts, resolve the rebase by hand", other).into());
+    }
+    let mut add = vec!["add", "--"];
+    add.extend(&conflicted);
+    git_ok(dir, &add)?;
+    Ok(())
+}
+
+/// Commits local changes to the data files, rebases them onto the shared
+/// repo's history, and pushes the result.
+pub fn run(data: &Path) -> Result<(), Box<dyn Error>> {
+    let dir = data
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    git_ok(dir, &["rev-parse", "--is-inside-work-tree"])
+        .map_err(|_| format!("{} isn't in a git repository", data.display()))?;
+    let name = |path: &Path| -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let data_file = name(data);
+    let audit_file = name(&audit::path_for(data));
+    let events_file = name(&event::Events::path_for(data));
+
+    let tracked: Vec<&str> = [&data_file, &audit_file, &events_file]
+        .into_iter()
+        .filter(|file| dir.join(file).exists())
+        .map(String::as_str)
+        .collect();
+    let on_tracked = |args: &[&'static str]| -> Vec<&str> {
+        args.iter()
+            .copied()
+            .chain(["--"])
+            .chain(tracked.iter().copied())
+            .collect()
+    };
+    git_ok(dir, &on_tracked(&["add"]))?;
+    let staged = !git(dir, &on_tracked(&["diff", "--cached", "--quiet"]))?
+        .status
+        .success();
+    if staged {
+        git_ok(dir, &on_tracked(&["commit", "-m", "Record games"]))?;
+        println!("Committed local changes");
+    }
+
+    let mut pull = git(dir, &["pull", "--rebase"])?;
+    while !pull.status.success() {
+        let in_rebase = git(dir, &["rev-parse", "-q", "--verify", "REBASE_HEAD"])?
+            .status
+            .success();
+        if !in_rebase {
+            return Err(format!(
+                "git pull failed: {}",
+                String::from_utf8_lossy(&pull.stderr).trim()
+            )
+            .into());
+        }
+        resolve(dir, &data_file, &audit_file)?;
+        println!("Merged games recorded on both sides");
+        pull = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rebase", "--continue"])
+            .env("GIT_EDITOR", "true")
+            .output()?;
+    }
+
+    git_ok(dir, &["push"])?;
+    println!("Synced {}", data.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_game;
+
+    fn game(id: u32, player: &str, won: u32) -> GameLog {
+        GameLog {
+            player: player.into(),
+            won,
+            lost: 2 - won.min(2),
+            ..test_game(id)
+        }
+    }
+
+    #[test]
+    fn games_both_sides_added_are_kept_with_local_ones_renumbered() {
+        let base = vec![game(1, "Grant", 2), game(2, "Noah", 0)];
+        let upstream = [base.clone(), vec![game(3, "Isaac", 2), game(4, "Isaac", 1)]].concat();
+        let local = [base.clone(), vec![game(3, "Eamonn", 2)]].concat();
+        let (merged, renumbered) = merge(&base, &upstream, &local).unwrap();
+        let rows: Vec<(u32, &str)> = merged
+            .iter()
+            .map(|game| (game.id, game.player.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (1, "Grant"),
+                (2, "Noah"),
+                (3, "Isaac"),
+                (4, "Isaac"),
+                (5, "Eamonn")
+            ]
+        );
+        assert_eq!(renumbered, BTreeMap::from([(3, 5)]));
+    }
+
+    #[test]
+    fn games_changed_on_either_side_are_left_to_a_person() {
+        let base = vec![game(1, "Grant", 2), game(2, "Noah", 0)];
+        let added = [base.clone(), vec![game(3, "Isaac", 2)]].concat();
+        let edited = vec![game(1, "Grant", 1), game(2, "Noah", 0)];
+        let removed = vec![game(1, "Grant", 2)];
+        assert!(merge(&base, &added, &edited).is_none());
+        assert!(merge(&base, &edited, &added).is_none());
+        assert!(merge(&base, &added, &removed).is_none());
+        // a side that added nothing takes the other's games as they are
+        let (merged, renumbered) = merge(&base, &base, &added).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(renumbered, BTreeMap::from([(3, 3)]));
+    }
+}
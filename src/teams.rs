@@ -0,0 +1,94 @@
+use crate::{sample, GameLog};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Default)]
+struct Team<'a> {
+    members: BTreeSet<&'a str>,
+    wins: u32,
+    losses: u32,
+}
+
+/// Each pod's teams and whether they won, from the seats that name a team.
+/// Teammates share a result, so a team won if any of its seats did.
+fn results(games: &[GameLog]) -> BTreeMap<&str, BTreeMap<&str, bool>> {
+    let mut pods: BTreeMap<&str, BTreeMap<&str, bool>> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| !game.is_draw())
+        .for_each(|game| {
+            if let (Some(pod), Some(team)) = (&game.pod, &game.team) {
+                let won = pods.entry(pod).or_default().entry(team).or_default();
+                *won |= game.won > game.lost;
+            }
+        });
+    pods
+}
+
+fn record(wins: u32, losses: u32) -> String {
+    let total = wins + losses;
+    format!(
+        "{} - {} ({:.1}%{})",
+        wins,
+        losses,
+        wins as f64 / total.max(1) as f64 * 100.0,
+        sample::marker(total)
+    )
+}
+
+/// Every team's record and members, then how the teams have done against each
+/// other. Members' own records already count their team games, as pod seats.
+pub fn print(games: &[GameLog]) {
+    let pods = results(games);
+    let mut teams: BTreeMap<&str, Team> = BTreeMap::new();
+    games.iter().for_each(|game| {
+        if let Some(team) = &game.team {
+            teams.entry(team).or_default().members.insert(&game.player);
+        }
+    });
+    if teams.is_empty() {
+        println!("No team games recorded yet.");
+        return;
+    }
+    // keyed by the winning team, then the team it beat
+    let mut head_to_head: BTreeMap<(&str, &str), u32> = BTreeMap::new();
+    pods.values().for_each(|results| {
+        results.iter().for_each(|(team, won)| {
+            let entry = teams.entry(team).or_default();
+            if *won {
+                entry.wins += 1;
+            } else {
+                entry.losses += 1;
+            }
+        });
+        let (winners, losers): (Vec<_>, Vec<_>) = results.iter().partition(|(_, won)| **won);
+        for (winner, _) in &winners {
+            for (loser, _) in &losers {
+                *head_to_head.entry((winner, loser)).or_default() += 1;
+            }
+        }
+    });
+
+    println!("Teams:");
+    teams.iter().for_each(|(name, team)| {
+        println!(
+            "  {}: {} [{}]",
+            name,
+            record(team.wins, team.losses),
+            team.members.iter().copied().collect::<Vec<_>>().join(", ")
+        )
+    });
+
+    let pairs: BTreeSet<(&str, &str)> = head_to_head
+        .keys()
+        .map(|&(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    if pairs.is_empty() {
+        return;
+    }
+    println!("\nTeam vs. team:");
+    pairs.iter().for_each(|&(team, other)| {
+        let wins = head_to_head.get(&(team, other)).copied().unwrap_or(0);
+        let losses = head_to_head.get(&(other, team)).copied().unwrap_or(0);
+        println!("  {} vs {}: {}", team, other, record(wins, losses))
+    });
+}
@@ -0,0 +1,100 @@
+use crate::{ratings, sample, Deck, GameLog};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// recent windows this unlikely under the lifetime win rate are flagged
+const SIGNIFICANCE: f64 = 0.05;
+
+/// Two-sided exact binomial test of `wins` in `n` games against win rate `p`:
+/// the chance of a result at most as likely as this one.
+fn p_value(wins: u32, n: u32, p: f64) -> f64 {
+    let probabilities: Vec<f64> = (0..=n)
+        .scan(1.0, |choose, k| {
+            let probability = *choose * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32);
+            *choose *= (n - k) as f64 / (k + 1) as f64;
+            Some(probability)
+        })
+        .collect();
+    let observed = probabilities[wins as usize];
+    // a little slack so ties aren't lost to rounding
+    probabilities
+        .iter()
+        .filter(|probability| **probability <= observed * (1.0 + 1e-7))
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Every deck's match results in the order they were played, from both sides
+/// of each match; mirrors, draws, and pod seats are left out.
+fn results(games: &[GameLog]) -> BTreeMap<Deck, Vec<bool>> {
+    let mut results: BTreeMap<Deck, Vec<bool>> = BTreeMap::new();
+    ratings::chronological(games)
+        .into_iter()
+        .filter(|game| !game.is_draw() && game.pod.is_none() && !game.is_complement())
+        .for_each(|game| {
+            let decks = (Deck::from_str(&game.deck), Deck::from_str(&game.opp_deck));
+            if let (Ok(deck), Ok(opponent)) = decks {
+                if deck != opponent {
+                    let won = game.won > game.lost;
+                    results.entry(deck).or_default().push(won);
+                    results.entry(opponent).or_default().push(!won);
+                }
+            }
+        });
+    results
+}
+
+/// Each deck's lifetime win rate next to the range of its rolling-window win
+/// rates and its latest window, flagging decks whose latest window is
+/// significantly off their lifetime rate.
+pub fn print(games: &[GameLog], window: usize) {
+    let decks: Vec<(Deck, Vec<bool>)> = results(games)
+        .into_iter()
+        .filter(|(_, results)| results.len() >= window)
+        .collect();
+    if decks.is_empty() {
+        println!("No deck has played {} matches yet.", window);
+        return;
+    }
+    println!(
+        "{:<18} {:>5} {:>9} {:>7} {:>7} {:>7}",
+        "Deck",
+        "Games",
+        "Lifetime",
+        "Low",
+        "High",
+        format!("Last {}", window)
+    );
+    let mut flagged = false;
+    decks.iter().for_each(|(deck, results)| {
+        let rate = |results: &[bool]| {
+            results.iter().filter(|won| **won).count() as f64 / results.len() as f64
+        };
+        let lifetime = rate(results);
+        let windows: Vec<f64> = results.windows(window).map(rate).collect();
+        let low = windows.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = windows.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let recent = &results[results.len() - window..];
+        let wins = recent.iter().filter(|won| **won).count() as u32;
+        let diverges = p_value(wins, window as u32, lifetime) < SIGNIFICANCE;
+        flagged |= diverges;
+        println!(
+            "{:<18} {:>5} {:>8.1}%{} {:>6.1}% {:>6.1}% {:>6.1}%{}{}",
+            deck.to_string(),
+            results.len(),
+            lifetime * 100.0,
+            sample::marker(results.len() as u32),
+            low * 100.0,
+            high * 100.0,
+            rate(recent) * 100.0,
+            sample::marker(window as u32),
+            if diverges { " *" } else { "" }
+        )
+    });
+    if flagged {
+        println!(
+            "* latest window is significantly off the lifetime rate (p < {})",
+            SIGNIFICANCE
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! The matchups we know least about, to play next in a testing session.
+
+use crate::{Deck, GameLog, Matchup};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+// z for a 95% interval
+const Z: f64 = 1.96;
+
+/// The 95% Wilson score interval of a win rate; the whole range with no
+/// games.
+fn wilson(wins: u32, losses: u32) -> (f64, f64) {
+    let n = (wins + losses) as f64;
+    if n == 0.0 {
+        return (0.0, 1.0);
+    }
+    let p = wins as f64 / n;
+    let centre = p + Z * Z / (2.0 * n);
+    let spread = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+    let scale = 1.0 + Z * Z / n;
+    ((centre - spread) / scale, (centre + spread) / scale)
+}
+
+/// Who has played `deck` most, leaving out anyone in `taken`.
+fn pilot<'a>(games: &'a [GameLog], deck: Deck, taken: Option<&str>) -> Option<&'a str> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    games
+        .iter()
+        .filter(|game| Deck::from_str(&game.deck).is_ok_and(|d| d == deck))
+        .filter(|game| Some(game.player.as_str()) != taken)
+        .for_each(|game| *counts.entry(&game.player).or_default() += 1);
+    counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(player, _)| player)
+}
+
+struct Pairing {
+    deck: Deck,
+    opponent: Deck,
+    win: u32,
+    loss: u32,
+    interval: (f64, f64),
+}
+
+impl Pairing {
+    fn width(&self) -> f64 {
+        self.interval.1 - self.interval.0
+    }
+}
+
+/// The `count` pairings of `decks` with the widest win-rate intervals, and who
+/// should play them: each deck's most regular pilot.
+pub fn print(
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    decks: &BTreeSet<Deck>,
+    count: usize,
+) {
+    let mut pairings: Vec<Pairing> = decks
+        .iter()
+        .flat_map(|deck| {
+            decks
+                .range(deck..)
+                .skip(1)
+                .map(move |opponent| (*deck, *opponent))
+        })
+        .map(|(deck, opponent)| {
+            let (win, loss) = matchups
+                .get(&(deck, opponent))
+                .map_or((0, 0), |matchup| (matchup.win, matchup.loss));
+            Pairing {
+                deck,
+                opponent,
+                win,
+                loss,
+                interval: wilson(win, loss),
+            }
+        })
+        .collect();
+    if pairings.is_empty() {
+        println!("Not enough decks to pair up.");
+        return;
+    }
+    pairings.sort_by(|a, b| {
+        b.width()
+            .total_cmp(&a.width())
+            .then((a.win + a.loss).cmp(&(b.win + b.loss)))
+    });
+    println!("Matchups to play next, least certain first:");
+    pairings.iter().take(count).for_each(|pairing| {
+        let first = pilot(games, pairing.deck, None);
+        let second = pilot(games, pairing.opponent, first);
+        let pilots = match (first, second) {
+            (Some(first), Some(second)) => format!(", {} vs. {}", first, second),
+            _ => String::new(),
+        };
+        println!(
+            "  {} vs. {}: {} - {} in {} games, 95% interval {:.0}%-{:.0}%{}",
+            pairing.deck,
+            pairing.opponent,
+            pairing.win,
+            pairing.loss,
+            pairing.win + pairing.loss,
+            pairing.interval.0 * 100.0,
+            pairing.interval.1 * 100.0,
+            pilots
+        )
+    });
+}
@@ -0,0 +1,231 @@
+use crate::config::RatingsConfig;
+use crate::{deck_vs_field, player_wins_losses, sample, Deck, GameLog, Matchup};
+use crate::{matrix, metrics, ratings};
+use rust_xlsxwriter::{Format, Note, Workbook, Worksheet, XlsxError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+fn write_header(sheet: &mut Worksheet, columns: &[&str]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    for (col, name) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *name, &bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn games_sheet(sheet: &mut Worksheet, games: &[GameLog]) -> Result<(), XlsxError> {
+    sheet.set_name("Games")?;
+    write_header(
+        sheet,
+        &[
+            "Date",
+            "Player",
+            "Deck",
+            "Won",
+            "Lost",
+            "Opponent Deck",
+            "Notes",
+            "Best Of",
+            "Mulligans",
+            "Opponent Mulligans",
+            "Won Roll",
+            "Team",
+            "Source",
+            "Opponent",
+            "On the Play",
+        ],
+    )?;
+    for (i, game) in games.iter().enumerate() {
+        let row = i as u32 + 1;
+        if let Some(date) = game.date {
+            sheet.write_string(row, 0, date.to_string())?;
+        }
+        sheet.write_string(row, 1, game.player.as_str())?;
+        sheet.write_string(row, 2, game.deck.trim())?;
+        sheet.write_number(row, 3, game.won)?;
+        sheet.write_number(row, 4, game.lost)?;
+        sheet.write_string(row, 5, game.opp_deck.trim())?;
+        sheet.write_string(row, 6, &game.notes)?;
+        sheet.write_number(row, 7, game.best_of())?;
+        if let Some(mulligans) = game.mulligans() {
+            sheet.write_number(row, 8, mulligans)?;
+        }
+        if let Some(opp_mulligans) = game.opp_mulligans {
+            sheet.write_number(row, 9, opp_mulligans)?;
+        }
+        if let Some(won_roll) = game.won_roll {
+            sheet.write_boolean(row, 10, won_roll)?;
+        }
+        if let Some(team) = &game.team {
+            sheet.write_string(row, 11, team)?;
+        }
+        if let Some(source) = &game.source {
+            sheet.write_string(row, 12, source)?;
+        }
+        if let Some(opponent) = &game.opponent {
+            sheet.write_string(row, 13, opponent.as_str())?;
+        }
+        if let Some(on_play) = game.on_play {
+            sheet.write_boolean(row, 14, on_play)?;
+        }
+    }
+    sheet.set_autofit_max_width(400);
+    sheet.autofit();
+    Ok(())
+}
+
+/// Flags a win rate cell from a small sample with a note saying so.
+fn note_sample(sheet: &mut Worksheet, row: u32, col: u16, games: u32) -> Result<(), XlsxError> {
+    if sample::is_small(games) {
+        let note = Note::new(format!("{} only {} games", sample::MARKER, games));
+        sheet.insert_note(row, col, &note)?;
+    }
+    Ok(())
+}
+
+fn matchups_sheet(
+    sheet: &mut Worksheet,
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+) -> Result<(), XlsxError> {
+    sheet.set_name("Matchups")?;
+    let grid = matrix::aggregate(matchups, |deck| deck);
+    let labels: Vec<Deck> = matrix::labels(&grid).into_iter().collect();
+    let bold = Format::new().set_bold();
+    let percent = Format::new().set_num_format("0%");
+    for (i, label) in labels.iter().enumerate() {
+        let col = 1 + 2 * i as u16;
+        sheet.write_string_with_format(0, col, format!("{} win rate", label), &bold)?;
+        sheet.write_string_with_format(0, col + 1, format!("{} games", label), &bold)?;
+        sheet.write_string_with_format(i as u32 + 1, 0, label.to_string(), &bold)?;
+    }
+    for (i, row) in labels.iter().enumerate() {
+        for (j, col) in labels.iter().enumerate() {
+            let (win, loss) = grid.get(&(*row, *col)).copied().unwrap_or((0, 0));
+            let col = 1 + 2 * j as u16;
+            if win + loss > 0 {
+                let win_rate = win as f64 / (win + loss) as f64;
+                sheet.write_number_with_format(i as u32 + 1, col, win_rate, &percent)?;
+                note_sample(sheet, i as u32 + 1, col, win + loss)?;
+            }
+            sheet.write_number(i as u32 + 1, col + 1, win + loss)?;
+        }
+    }
+    sheet.set_freeze_panes(1, 1)?;
+    sheet.autofit();
+    Ok(())
+}
+
+fn players_sheet(
+    sheet: &mut Worksheet,
+    games: &[GameLog],
+    roster: &[String],
+) -> Result<(), XlsxError> {
+    sheet.set_name("Players")?;
+    write_header(sheet, &["Player", "Wins", "Losses", "Win Rate"])?;
+    let percent = Format::new().set_num_format("0.0%");
+    for (i, player) in roster.iter().enumerate() {
+        let row = i as u32 + 1;
+        let (wins, losses) = player_wins_losses(games, player);
+        sheet.write_string(row, 0, player)?;
+        sheet.write_number(row, 1, wins)?;
+        sheet.write_number(row, 2, losses)?;
+        if wins + losses > 0 {
+            let win_rate = wins as f64 / (wins + losses) as f64;
+            sheet.write_number_with_format(row, 3, win_rate, &percent)?;
+            note_sample(sheet, row, 3, wins + losses)?;
+        }
+    }
+    sheet.autofit();
+    Ok(())
+}
+
+/// Each rated player's Elo rating, best first, and when they last played.
+fn ratings_sheet(
+    sheet: &mut Worksheet,
+    games: &[GameLog],
+    config: &RatingsConfig,
+) -> Result<(), XlsxError> {
+    sheet.set_name("Ratings")?;
+    write_header(sheet, &["Player", "Elo", "Last Played"])?;
+    let mut rated: Vec<(String, f64)> = ratings::elo(games, config).into_iter().collect();
+    rated.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let whole = Format::new().set_num_format("0");
+    for (i, (player, rating)) in rated.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_string(row, 0, player)?;
+        sheet.write_number_with_format(row, 1, *rating, &whole)?;
+        let last = games
+            .iter()
+            .filter(|game| game.player == player.as_str())
+            .filter_map(|game| game.date)
+            .max();
+        if let Some(last) = last {
+            sheet.write_string(row, 2, last.to_string())?;
+        }
+    }
+    sheet.autofit();
+    Ok(())
+}
+
+/// Each piloted deck's record against the field, mirrors excluded, and its
+/// configured metrics.
+fn decks_sheet(
+    sheet: &mut Worksheet,
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    decks: &BTreeMap<Deck, Vec<Option<f64>>>,
+    custom: &metrics::Metrics,
+) -> Result<(), XlsxError> {
+    sheet.set_name("Decks")?;
+    let mut columns = vec!["Deck", "Wins", "Losses", "Win Rate"];
+    columns.extend(custom.names());
+    write_header(sheet, &columns)?;
+    let percent = Format::new().set_num_format("0.0%");
+    for (i, (deck, values)) in decks.iter().enumerate() {
+        let row = i as u32 + 1;
+        let (wins, losses) = deck_vs_field(matchups, *deck);
+        sheet.write_string(row, 0, deck.to_string())?;
+        sheet.write_number(row, 1, wins)?;
+        sheet.write_number(row, 2, losses)?;
+        if wins + losses > 0 {
+            let win_rate = wins as f64 / (wins + losses) as f64;
+            sheet.write_number_with_format(row, 3, win_rate, &percent)?;
+            note_sample(sheet, row, 3, wins + losses)?;
+        }
+        for (j, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                sheet.write_number(row, 4 + j as u16, *value)?;
+            }
+        }
+    }
+    sheet.autofit();
+    Ok(())
+}
+
+pub fn write_workbook(
+    path: impl AsRef<Path>,
+    games: &[GameLog],
+    matchups: &BTreeMap<(Deck, Deck), Matchup>,
+    roster: &[String],
+    custom: &metrics::Metrics,
+    ratings: &RatingsConfig,
+) -> Result<(), Box<dyn Error>> {
+    let piloted: BTreeSet<Deck> = games
+        .iter()
+        .flat_map(|game| Deck::from_str(&game.deck))
+        .collect();
+    let mut decks = BTreeMap::new();
+    for deck in piloted {
+        decks.insert(deck, custom.deck(matchups, deck)?);
+    }
+    let mut workbook = Workbook::new();
+    games_sheet(workbook.add_worksheet(), games)?;
+    matchups_sheet(workbook.add_worksheet(), matchups)?;
+    players_sheet(workbook.add_worksheet(), games, roster)?;
+    decks_sheet(workbook.add_worksheet(), matchups, &decks, custom)?;
+    ratings_sheet(workbook.add_worksheet(), games, ratings)?;
+    workbook.save(path)?;
+    Ok(())
+}